@@ -8,6 +8,8 @@ const DEFAULT_FAUCET_URL =
   process.env.REACT_APP_LINERA_FAUCET_URL || "http://localhost:8080";
 
 const DEFAULT_APPLICATION_ID = process.env.REACT_APP_LINERA_APPLICATION_ID || "";
+// Room codes are short; anything longer is treated as a raw host chain ID.
+const ROOM_CODE_MAX_LENGTH = 8;
 
 const getCookie = (name) => {
   try {
@@ -105,6 +107,46 @@ const defaultPlayerName = (chainId) => {
   return `Player-${String(chainId).slice(0, 6)}`;
 };
 
+/** A random salt for sealing a word, as 32 hex digits. */
+const newSalt = () => {
+  const bytes = new Uint8Array(16);
+  crypto.getRandomValues(bytes);
+  return Array.from(bytes, (b) => b.toString(16).padStart(2, "0")).join("");
+};
+
+/** Where the sealed word waits for its reveal, so a reload does not lose the salt. */
+const SEALED_WORD_STORAGE_KEY = "word_duel_sealed_word";
+
+const loadSealedWord = () => {
+  try {
+    return JSON.parse(sessionStorage.getItem(SEALED_WORD_STORAGE_KEY) || "null");
+  } catch {
+    return null;
+  }
+};
+
+const storeSealedWord = (sealed) => {
+  try {
+    if (sealed) {
+      sessionStorage.setItem(SEALED_WORD_STORAGE_KEY, JSON.stringify(sealed));
+    } else {
+      sessionStorage.removeItem(SEALED_WORD_STORAGE_KEY);
+    }
+  } catch {}
+};
+
+/** Match rules as GraphQL fields, usable as `createMatch` arguments or a `MatchRulesInput`. */
+const matchRulesFields = (
+  totalRounds,
+  { simultaneous = false, mode = "CLASSIC", customLetters = "", categories = false, bonuses = false, handicaps = "OFF" } = {}
+) => {
+  const rounds = Math.max(1, Math.min(20, Number(totalRounds) || 5));
+  const turnOrder = simultaneous ? "SIMULTANEOUS" : "ALTERNATING";
+  const rack = escapeGqlString(String(customLetters || "").trim());
+  const letters = rack ? `, letters: "${rack}"` : "";
+  return `totalRounds: ${rounds}, turnOrder: ${turnOrder}, mode: ${mode}${letters}, categories: ${Boolean(categories)}, bonuses: ${Boolean(bonuses)}, handicaps: ${handicaps}`;
+};
+
 /** Clear Linera-related IndexedDB so a refresh can reconnect to a recreated network. */
 const clearLineraIndexedDB = () => {
   try {
@@ -140,17 +182,25 @@ const LineraContextProvider = ({ children }) => {
   const [game, setGame] = useState(null);
   const [matchStatus, setMatchStatus] = useState(null);
   const [letters, setLetters] = useState("");
+  const [chainLetter, setChainLetter] = useState(null);
   const [round, setRound] = useState(0);
   const [roundPhase, setRoundPhase] = useState(null);
   const [isHost, setIsHost] = useState(false);
   const [opponentChainId, setOpponentChainId] = useState(null);
   const [myWord, setMyWord] = useState(null);
   const [opponentWord, setOpponentWord] = useState(null);
+  const [revealDue, setRevealDue] = useState(false);
   const [myScore, setMyScore] = useState(0);
   const [opponentScore, setOpponentScore] = useState(0);
   const [lastRoundRecord, setLastRoundRecord] = useState(null);
+  const [challengeDeadline, setChallengeDeadline] = useState(null);
   const [roundHistory, setRoundHistory] = useState([]);
   const [lastNotification, setLastNotification] = useState(null);
+  const [friends, setFriends] = useState([]);
+  const [invites, setInvites] = useState([]);
+  const [sentInvites, setSentInvites] = useState([]);
+  const [chat, setChat] = useState([]);
+  const [chatReactions, setChatReactions] = useState([]);
 
   const clientRef = useRef(null);
   const chainRef = useRef(null);
@@ -162,6 +212,8 @@ const LineraContextProvider = ({ children }) => {
   const lastSnapshotRef = useRef({});
   const isMountedRef = useRef(true);
   const initInProgressRef = useRef(false);
+  const sealedWordRef = useRef(loadSealedWord());
+  const revealInFlightRef = useRef(false);
 
   const gql = useCallback(async (query) => {
     if (!appRef.current) throw new Error("Linera app not initialized");
@@ -180,6 +232,7 @@ const LineraContextProvider = ({ children }) => {
       setGame(null);
       setMatchStatus(null);
       setLetters("");
+      setChainLetter(null);
       setRound(0);
       setRoundPhase(null);
       setIsHost(false);
@@ -190,6 +243,10 @@ const LineraContextProvider = ({ children }) => {
       setOpponentScore(0);
       setLastRoundRecord(null);
       setRoundHistory([]);
+      setFriends([]);
+      setInvites([]);
+      setSentInvites([]);
+      setChat([]);
       return;
     }
     if (refreshInFlightRef.current) return;
@@ -200,9 +257,17 @@ const LineraContextProvider = ({ children }) => {
           game {
             matchId
             hostChainId
+            roomCode
             status
-            players { chainId name }
+            players { chainId name handicap { scorePercent extraLetters extraSecs } profile { displayName avatarId language country } }
             letters
+            letterSource
+            letterPack
+            mode
+            chain
+            category
+            bonuses
+            handicapMode
             totalRounds
             currentRound
             hostScore
@@ -210,22 +275,33 @@ const LineraContextProvider = ({ children }) => {
             roundPhase
             hostWord
             guestWord
+            hostSeal
+            guestSeal
             winnerChainId
-            history { round hostWord guestWord hostPoints guestPoints hostScore guestScore timestamp }
+            history { round hostWord guestWord hostPoints guestPoints hostScore guestScore timestamp category hostVerdict guestVerdict hostBonuses { kind points } guestBonuses { kind points } }
           }
           matchStatus
           letters
+          myLetters
+          chainLetter
           round
           roundPhase
           isHost
           opponentChainId
           myWord
           opponentWord
+          revealDue
           myScore
           opponentScore
-          lastRoundRecord { round hostWord guestWord hostPoints guestPoints hostScore guestScore timestamp }
+          lastRoundRecord { round hostWord guestWord hostPoints guestPoints hostScore guestScore timestamp hostBonuses { kind points } guestBonuses { kind points } hostChallenge { upheld struckWords points } guestChallenge { upheld struckWords points } }
+          challengeDeadline
           roundHistory { round hostWord guestWord hostPoints guestPoints hostScore guestScore timestamp }
           lastNotification
+          friends { chainId name }
+          invites { chainId name rating rules { totalRounds turnOrder mode customLetters letterPack categories bonuses handicapMode } }
+          sentInvites
+          chat { senderChainId senderName text reaction timestamp }
+          chatReactions
         }
       `);
       const nextGame = data?.game ?? null;
@@ -236,15 +312,18 @@ const LineraContextProvider = ({ children }) => {
       }
 
       setMatchStatus(data?.matchStatus ?? null);
-      setLetters(data?.letters ?? nextGame?.letters ?? "");
+      setLetters(data?.myLetters ?? data?.letters ?? nextGame?.letters ?? "");
+      setChainLetter(data?.chainLetter ?? null);
       setRound(Number(nextGame?.currentRound ?? data?.round ?? 0));
       setRoundPhase(data?.roundPhase ?? nextGame?.roundPhase ?? null);
       setIsHost(Boolean(data?.isHost));
       setOpponentChainId(data?.opponentChainId ?? null);
       setMyWord(data?.myWord ?? null);
       setOpponentWord(data?.opponentWord ?? null);
+      setRevealDue(Boolean(data?.revealDue));
       setMyScore(Number(data?.myScore ?? 0));
       setOpponentScore(Number(data?.opponentScore ?? 0));
+      setChallengeDeadline(data?.challengeDeadline ?? null);
 
       const statusStr = String(nextGame?.status ?? data?.matchStatus ?? "").toLowerCase();
       if (statusStr === "ended") {
@@ -275,6 +354,30 @@ const LineraContextProvider = ({ children }) => {
         setRoundHistory(nextRoundHistory);
       }
 
+      const nextSocial = {
+        friends: Array.isArray(data?.friends) ? data.friends : [],
+        invites: Array.isArray(data?.invites) ? data.invites : [],
+        sentInvites: Array.isArray(data?.sentInvites) ? data.sentInvites : [],
+      };
+      const nextSocialJson = JSON.stringify(nextSocial);
+      if (nextSocialJson !== lastSnapshotRef.current.socialJson) {
+        lastSnapshotRef.current.socialJson = nextSocialJson;
+        setFriends(nextSocial.friends);
+        setInvites(nextSocial.invites);
+        setSentInvites(nextSocial.sentInvites);
+      }
+
+      const nextChat = Array.isArray(data?.chat) ? data.chat : [];
+      const nextChatJson = JSON.stringify(nextChat);
+      if (nextChatJson !== lastSnapshotRef.current.chatJson) {
+        lastSnapshotRef.current.chatJson = nextChatJson;
+        setChat(nextChat);
+      }
+      if (Array.isArray(data?.chatReactions) && !lastSnapshotRef.current.chatReactionsLoaded) {
+        lastSnapshotRef.current.chatReactionsLoaded = true;
+        setChatReactions(data.chatReactions);
+      }
+
       setLastNotification(data?.lastNotification ?? null);
     } catch (e) {
       setLastNotification(String(e?.message || e));
@@ -506,20 +609,73 @@ const LineraContextProvider = ({ children }) => {
   }, []);
 
   const createMatch = useCallback(
-    async (hostName, totalRounds = 5) => {
+    async (hostName, totalRounds = 5, options = {}) => {
       const name = escapeGqlString(hostName || defaultPlayerName(chainId));
-      const rounds = Math.max(1, Math.min(20, Number(totalRounds) || 5));
-      await gql(`mutation { createMatch(hostName: "${name}", totalRounds: ${rounds}) }`);
+      await gql(
+        `mutation { createMatch(hostName: "${name}", ${matchRulesFields(totalRounds, options)}) }`
+      );
       await refresh();
     },
     [chainId, gql, refresh]
   );
 
+  const addFriend = useCallback(
+    async (friendChainId, name) => {
+      const id = escapeGqlString(String(friendChainId || "").trim());
+      const friendName = escapeGqlString(String(name || "").trim());
+      await gql(`mutation { addFriend(chainId: "${id}", name: "${friendName}") }`);
+      await refresh();
+    },
+    [gql, refresh]
+  );
+
+  const removeFriend = useCallback(
+    async (friendChainId) => {
+      const id = escapeGqlString(String(friendChainId || "").trim());
+      await gql(`mutation { removeFriend(chainId: "${id}") }`);
+      await refresh();
+    },
+    [gql, refresh]
+  );
+
+  const challengePlayer = useCallback(
+    async (playerChainId, totalRounds = 5, options = {}) => {
+      const id = escapeGqlString(String(playerChainId || "").trim());
+      await gql(
+        `mutation { challengePlayer(chainId: "${id}", rules: { ${matchRulesFields(totalRounds, options)} }) }`
+      );
+      await refresh();
+    },
+    [gql, refresh]
+  );
+
+  const acceptChallenge = useCallback(
+    async (challengerChainId) => {
+      const id = escapeGqlString(String(challengerChainId || "").trim());
+      await gql(`mutation { acceptChallenge(chainId: "${id}") }`);
+      await refresh();
+    },
+    [gql, refresh]
+  );
+
+  const declineChallenge = useCallback(
+    async (challengerChainId) => {
+      const id = escapeGqlString(String(challengerChainId || "").trim());
+      await gql(`mutation { declineChallenge(chainId: "${id}") }`);
+      await refresh();
+    },
+    [gql, refresh]
+  );
+
   const joinMatch = useCallback(
-    async (hostChainId, playerName) => {
-      const host = escapeGqlString(hostChainId);
+    async (hostChainIdOrCode, playerName) => {
+      const target = escapeGqlString(String(hostChainIdOrCode || "").trim());
       const name = escapeGqlString(playerName || defaultPlayerName(chainId));
-      await gql(`mutation { joinMatch(hostChainId: "${host}", playerName: "${name}") }`);
+      if (target.replace(/[\s-]/g, "").length <= ROOM_CODE_MAX_LENGTH) {
+        await gql(`mutation { joinByCode(code: "${target}", playerName: "${name}") }`);
+      } else {
+        await gql(`mutation { joinMatch(hostChainId: "${target}", playerName: "${name}") }`);
+      }
       await refresh();
     },
     [chainId, gql, refresh]
@@ -528,17 +684,84 @@ const LineraContextProvider = ({ children }) => {
   const submitWord = useCallback(
     async (word) => {
       const w = escapeGqlString(String(word || "").trim());
-      await gql(`mutation { submitWord(word: "${w}") }`);
+      if (String(roundPhase || "").toLowerCase().replace(/[^a-z]/g, "") !== "bothtoplay") {
+        await gql(`mutation { submitWord(word: "${w}") }`);
+        await refresh();
+        return;
+      }
+      // Simultaneous rounds only send the seal now; the word and salt stay here until the
+      // opponent has sealed theirs too.
+      const salt = newSalt();
+      await gql(`mutation { sealWord(word: "${w}", salt: "${salt}") }`);
+      sealedWordRef.current = { matchId: game?.matchId ?? null, round, word: w, salt };
+      storeSealedWord(sealedWordRef.current);
+      await refresh();
+    },
+    [game?.matchId, gql, refresh, round, roundPhase]
+  );
+
+  useEffect(() => {
+    const sealed = sealedWordRef.current;
+    if (!revealDue || !sealed || revealInFlightRef.current) return;
+    if (sealed.matchId !== (game?.matchId ?? null) || sealed.round !== round) {
+      sealedWordRef.current = null;
+      storeSealedWord(null);
+      return;
+    }
+    revealInFlightRef.current = true;
+    gql(`mutation { revealWord(word: "${sealed.word}", salt: "${sealed.salt}") }`)
+      .then(() => {
+        sealedWordRef.current = null;
+        storeSealedWord(null);
+        return refresh();
+      })
+      .catch((e) => console.warn("Failed to reveal the sealed word:", e))
+      .finally(() => {
+        revealInFlightRef.current = false;
+      });
+  }, [game?.matchId, gql, refresh, revealDue, round]);
+
+  const saveProfile = useCallback(
+    async ({ displayName, avatarId = 0, language = "", country = "" }) => {
+      const name = escapeGqlString(String(displayName || "").trim());
+      const lang = escapeGqlString(String(language || "").trim());
+      const land = escapeGqlString(String(country || "").trim());
+      await gql(
+        `mutation { setProfile(profile: { displayName: "${name}", avatarId: ${Number(avatarId) || 0}, language: "${lang}", country: "${land}" }) }`
+      );
+    },
+    [gql]
+  );
+
+  const sendChat = useCallback(
+    async (text) => {
+      const t = escapeGqlString(String(text || "").trim());
+      await gql(`mutation { sendChat(text: "${t}") }`);
+      await refresh();
+    },
+    [gql, refresh]
+  );
+
+  const sendReaction = useCallback(
+    async (emoji) => {
+      await gql(`mutation { sendReaction(emoji: "${escapeGqlString(emoji)}") }`);
       await refresh();
     },
     [gql, refresh]
   );
 
+  const challengeWords = useCallback(async () => {
+    await gql(`mutation { challenge }`);
+    await refresh();
+  }, [gql, refresh]);
+
   const leaveMatch = useCallback(async () => {
     await gql(`mutation { leaveMatch }`);
     await refresh();
   }, [gql, refresh]);
 
+  const mySeal = (isHost ? game?.hostSeal : game?.guestSeal) ?? null;
+
   const value = useMemo(
     () => ({
       ready,
@@ -553,34 +776,62 @@ const LineraContextProvider = ({ children }) => {
       game,
       matchStatus,
       letters,
+      chainLetter,
       round,
       roundPhase,
       isHost,
       opponentChainId,
       myWord,
       opponentWord,
+      mySeal,
       myScore,
       opponentScore,
       lastRoundRecord,
+      challengeDeadline,
       roundHistory,
       lastNotification,
+      friends,
+      invites,
+      sentInvites,
+      chat,
+      chatReactions,
       setApplicationId,
       setFaucetUrl,
       refresh,
       createMatch,
       joinMatch,
       submitWord,
+      addFriend,
+      removeFriend,
+      challengePlayer,
+      acceptChallenge,
+      declineChallenge,
+      sendChat,
+      sendReaction,
+      challengeWords,
+      saveProfile,
       leaveMatch,
     }),
     [
+      acceptChallenge,
+      addFriend,
       applicationId,
       chainId,
+      chainLetter,
+      chat,
+      chatReactions,
+      challengeDeadline,
+      challengePlayer,
+      challengeWords,
       createMatch,
+      declineChallenge,
       finalResult,
+      friends,
       faucetUrl,
       game,
       initError,
       initStage,
+      invites,
       isHost,
       joinMatch,
       lastNotification,
@@ -592,11 +843,17 @@ const LineraContextProvider = ({ children }) => {
       opponentChainId,
       opponentScore,
       opponentWord,
+      mySeal,
       round,
       roundHistory,
       roundPhase,
       ready,
       refresh,
+      removeFriend,
+      saveProfile,
+      sendChat,
+      sendReaction,
+      sentInvites,
       submitWord,
       leaveMatch,
       syncHeight,
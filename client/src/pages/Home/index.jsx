@@ -8,10 +8,33 @@ const PLAYER_NAME_STORAGE_KEY = "word_duel_player_name";
 
 const Home = () => {
   const navigate = useNavigate();
-  const { ready, initError, chainId, createMatch } = useContext(LineraContext);
+  const {
+    ready,
+    initError,
+    chainId,
+    createMatch,
+    saveProfile,
+    friends,
+    invites,
+    sentInvites,
+    addFriend,
+    removeFriend,
+    challengePlayer,
+    acceptChallenge,
+    declineChallenge,
+  } = useContext(LineraContext);
+  const [profileSaved, setProfileSaved] = useState(false);
   const [friendMenuOpen, setFriendMenuOpen] = useState(false);
   const [hostChainIdInput, setHostChainIdInput] = useState("");
   const [totalRounds, setTotalRounds] = useState(5);
+  const [simultaneous, setSimultaneous] = useState(false);
+  const [mode, setMode] = useState("CLASSIC");
+  const [customLetters, setCustomLetters] = useState("");
+  const [categories, setCategories] = useState(false);
+  const [bonuses, setBonuses] = useState(false);
+  const [handicaps, setHandicaps] = useState("OFF");
+  const [friendChainIdInput, setFriendChainIdInput] = useState("");
+  const [friendNameInput, setFriendNameInput] = useState("");
   const [playerName, setPlayerName] = useState(() => {
     try {
       return localStorage.getItem(PLAYER_NAME_STORAGE_KEY) || "";
@@ -38,6 +61,18 @@ const Home = () => {
 
   const canOpenMenus = normalizedPlayerName.length > 0;
 
+  const canAddFriend =
+    ready && friendChainIdInput.trim().length > 0 && friendNameInput.trim().length > 0;
+
+  const matchOptions = {
+    simultaneous: simultaneous || mode === "RUSH",
+    mode,
+    customLetters,
+    categories: categories && mode === "CLASSIC",
+    bonuses,
+    handicaps,
+  };
+
   return (
     <>
       <div className={styles.container}>
@@ -54,14 +89,46 @@ const Home = () => {
               onChange={(e) => {
                 const next = e.target.value;
                 setPlayerName(next);
+                setProfileSaved(false);
                 try {
                   localStorage.setItem(PLAYER_NAME_STORAGE_KEY, next);
                 } catch {}
               }}
               placeholder="Enter your name"
             />
+            <Button
+              name={profileSaved ? "Profile saved" : "Save as profile"}
+              disabled={!ready || !canOpenMenus || profileSaved}
+              onClick={async () => {
+                const language = String(navigator.language || "").slice(0, 2);
+                await saveProfile({ displayName: normalizedPlayerName, language });
+                setProfileSaved(true);
+              }}
+            />
           </div>
 
+          {ready && invites.length > 0 && (
+            <div className={styles.section}>
+              <div className={styles.section_title}>INVITES</div>
+              {invites.map((invite) => (
+                <div key={invite.chainId} className={styles.invite_row}>
+                  <div className={styles.section_hint}>
+                    {invite.name} ({invite.rating}) - {invite.rules.totalRounds} rounds,{" "}
+                    {String(invite.rules.mode).toLowerCase()}
+                  </div>
+                  <Button
+                    name="Accept"
+                    onClick={async () => {
+                      await acceptChallenge(invite.chainId);
+                      navigate(`/room/${invite.chainId}?invite=1`);
+                    }}
+                  />
+                  <Button name="Decline" onClick={() => declineChallenge(invite.chainId)} />
+                </div>
+              ))}
+            </div>
+          )}
+
           <div className={styles.btn_container}>
             <Button
               name="Play with Friend"
@@ -120,10 +187,70 @@ const Home = () => {
                       }
                     />
                   </div>
+                  <div className={styles.rounds_row}>
+                    <label className={styles.rounds_label}>
+                      <input
+                        type="checkbox"
+                        checked={simultaneous}
+                        onChange={(e) => setSimultaneous(e.target.checked)}
+                      />{" "}
+                      Play at the same time
+                    </label>
+                  </div>
+                  <div className={styles.rounds_row}>
+                    <label className={styles.rounds_label}>Mode:</label>
+                    <select value={mode} onChange={(e) => setMode(e.target.value)}>
+                      <option value="CLASSIC">Classic</option>
+                      <option value="RUSH">Rush (many words per round)</option>
+                      <option value="CHAIN">Word chain</option>
+                    </select>
+                  </div>
+                  {mode === "CLASSIC" && (
+                    <div className={styles.rounds_row}>
+                      <label className={styles.rounds_label}>
+                        <input
+                          type="checkbox"
+                          checked={categories}
+                          onChange={(e) => setCategories(e.target.checked)}
+                        />{" "}
+                        Category rounds (animals, countries, food)
+                      </label>
+                    </div>
+                  )}
+                  <div className={styles.rounds_row}>
+                    <label className={styles.rounds_label}>
+                      <input
+                        type="checkbox"
+                        checked={bonuses}
+                        onChange={(e) => setBonuses(e.target.checked)}
+                      />{" "}
+                      Bonuses (pangrams, streaks, speed, longer words)
+                    </label>
+                  </div>
+                  <div className={styles.rounds_row}>
+                    <label className={styles.rounds_label}>Handicaps:</label>
+                    <select value={handicaps} onChange={(e) => setHandicaps(e.target.value)}>
+                      <option value="OFF">None</option>
+                      <option value="FROM_RATING">From rating</option>
+                    </select>
+                  </div>
+                  <div className={styles.rounds_row}>
+                    <label className={styles.rounds_label}>Letters:</label>
+                    <input
+                      type="text"
+                      maxLength={12}
+                      className={styles.rounds_input}
+                      placeholder="random"
+                      value={customLetters}
+                      onChange={(e) =>
+                        setCustomLetters(e.target.value.toUpperCase().replace(/[^A-Z?]/g, ""))
+                      }
+                    />
+                  </div>
                   <Button
                     name="Create Room"
                     onClick={async () => {
-                      await createMatch(normalizedPlayerName, totalRounds);
+                      await createMatch(normalizedPlayerName, totalRounds, matchOptions);
                       setFriendMenuOpen(false);
                       navigate(`/room/${chainId}`);
                     }}
@@ -132,6 +259,47 @@ const Home = () => {
 
                 <div className={styles.divider} />
 
+                <div className={styles.section}>
+                  <div className={styles.section_title}>FRIENDS</div>
+                  <div className={styles.section_hint}>
+                    Challenge a friend to a match with the settings above.
+                  </div>
+                  {friends.map((friend) => (
+                    <div key={friend.chainId} className={styles.invite_row}>
+                      <div className={styles.section_hint}>{friend.name}</div>
+                      <Button
+                        name={sentInvites.includes(friend.chainId) ? "Invited" : "Challenge"}
+                        disabled={sentInvites.includes(friend.chainId)}
+                        onClick={() => challengePlayer(friend.chainId, totalRounds, matchOptions)}
+                      />
+                      <Button name="Remove" onClick={() => removeFriend(friend.chainId)} />
+                    </div>
+                  ))}
+                  <input
+                    className={styles.input}
+                    value={friendChainIdInput}
+                    onChange={(e) => setFriendChainIdInput(e.target.value)}
+                    placeholder="Friend chain id"
+                  />
+                  <input
+                    className={styles.input}
+                    value={friendNameInput}
+                    onChange={(e) => setFriendNameInput(e.target.value)}
+                    placeholder="Friend name"
+                  />
+                  <Button
+                    name="Add Friend"
+                    disabled={!canAddFriend}
+                    onClick={async () => {
+                      await addFriend(friendChainIdInput, friendNameInput);
+                      setFriendChainIdInput("");
+                      setFriendNameInput("");
+                    }}
+                  />
+                </div>
+
+                <div className={styles.divider} />
+
                 <div className={styles.section}>
                   <div className={styles.section_title}>JOIN ROOM</div>
                   <div className={styles.section_hint}>
@@ -1,4 +1,4 @@
-import { useMemo, useContext } from "react";
+import { useMemo, useContext, useState } from "react";
 import { useNavigate } from "react-router-dom";
 import { LineraContext } from "../../context/LineraContext";
 import Button from "../../components/Button";
@@ -14,7 +14,37 @@ const Result = () => {
     leaveMatch,
     chainId,
     game,
+    isHost,
+    lastRoundRecord,
+    challengeDeadline,
+    challengeWords,
+    lastNotification,
   } = useContext(LineraContext);
+  const [challenging, setChallenging] = useState(false);
+
+  // The last round can still be challenged for a while after the match ends.
+  const ownChallenge = isHost
+    ? lastRoundRecord?.hostChallenge
+    : lastRoundRecord?.guestChallenge;
+  const opponentPoints = isHost
+    ? lastRoundRecord?.guestPoints
+    : lastRoundRecord?.hostPoints;
+  const canChallenge =
+    !!challengeDeadline &&
+    Date.now() * 1000 < Number(challengeDeadline) &&
+    !ownChallenge &&
+    opponentPoints > 0 &&
+    !challenging;
+
+  const handleChallenge = async () => {
+    if (!canChallenge) return;
+    setChallenging(true);
+    try {
+      await challengeWords();
+    } finally {
+      setChallenging(false);
+    }
+  };
 
   const derivedScores = useMemo(() => {
     if (
@@ -79,7 +109,11 @@ const Result = () => {
             <div className={styles.score_value}>{derivedScores.opp}</div>
           </div>
         </div>
+        {lastNotification && <div>{lastNotification}</div>}
         <div className={styles.btn_container}>
+          {canChallenge && (
+            <Button name="Challenge Last Round" onClick={handleChallenge} />
+          )}
           <Button
             name="Back to Lobby"
             onClick={async () => {
@@ -11,9 +11,35 @@ const normalizePhase = (value) =>
     .toLowerCase()
     .replace(/[^a-z0-9]/g, "");
 
+const bonusText = (bonuses) =>
+  bonuses?.length
+    ? bonuses
+        .map((b) => `${b.kind.toLowerCase().replace(/_/g, " ")} +${b.points}`)
+        .join(", ")
+    : "—";
+
+const handicapText = (handicap) =>
+  !handicap ||
+  (handicap.scorePercent === 100 && !handicap.extraLetters && !handicap.extraSecs)
+    ? "none"
+    : `${handicap.scorePercent}% points, +${handicap.extraLetters} letters, +${handicap.extraSecs}s`;
+
+const profileText = (player) =>
+  player.profile
+    ? [player.profile.displayName, player.profile.language, player.profile.country]
+        .filter(Boolean)
+        .join(" · ")
+    : player.name;
+
+const challengeText = (challenge) =>
+  challenge.upheld
+    ? `upheld, ${challenge.struckWords.join(", ")} struck (-${challenge.points})`
+    : `failed (-${challenge.points})`;
+
 const Room = () => {
   const [wordInput, setWordInput] = useState("");
   const [submitting, setSubmitting] = useState(false);
+  const [chatInput, setChatInput] = useState("");
   const { id } = useParams();
   const navigate = useNavigate();
   const location = useLocation();
@@ -27,15 +53,23 @@ const Room = () => {
     opponentChainId,
     matchStatus,
     letters,
+    chainLetter,
     round,
     roundPhase,
     myWord,
+    mySeal,
     opponentWord,
     myScore,
     opponentScore,
     lastRoundRecord,
+    challengeDeadline,
     joinMatch,
     submitWord,
+    challengeWords,
+    chat,
+    chatReactions,
+    sendChat,
+    sendReaction,
     lastNotification,
   } = useContext(LineraContext);
   const hasJoinedRef = useRef(false);
@@ -52,9 +86,12 @@ const Room = () => {
       return;
     }
 
+    const params = new URLSearchParams(location.search || "");
+    // An accepted invite already put this chain in the challenger's match.
+    if (params.get("invite")) return;
+
     if (hasJoinedRef.current) return;
     hasJoinedRef.current = true;
-    const params = new URLSearchParams(location.search || "");
     let playerName = String(params.get("name") || "").trim();
     if (!playerName) {
       try {
@@ -87,10 +124,40 @@ const Room = () => {
   const phaseStr = normalizePhase(roundPhase);
   const isHostTurn = phaseStr === "hosttoplay";
   const isGuestTurn = phaseStr === "guesttoplay";
+  const isBothTurn = phaseStr === "bothtoplay";
   const myTurn =
-    (isHost && isHostTurn) || (!isHost && isGuestTurn);
+    (isHost && isHostTurn) || (!isHost && isGuestTurn) || isBothTurn;
   const canSubmit =
-    myTurn && !myWord && wordInput.trim().length >= 3 && !submitting;
+    myTurn && !myWord && !mySeal && wordInput.trim().length >= 3 && !submitting;
+  const ownChallenge = isHost
+    ? lastRoundRecord?.hostChallenge
+    : lastRoundRecord?.guestChallenge;
+  const opponentPoints = isHost
+    ? lastRoundRecord?.guestPoints
+    : lastRoundRecord?.hostPoints;
+  const canChallenge =
+    !!challengeDeadline &&
+    Date.now() * 1000 < Number(challengeDeadline) &&
+    !ownChallenge &&
+    opponentPoints > 0 &&
+    !submitting;
+
+  const handleChallenge = async () => {
+    if (!canChallenge) return;
+    setSubmitting(true);
+    try {
+      await challengeWords();
+    } finally {
+      setSubmitting(false);
+    }
+  };
+
+  const handleSendChat = async () => {
+    const text = chatInput.trim();
+    if (!text) return;
+    setChatInput("");
+    await sendChat(text);
+  };
 
   const handleSubmit = async () => {
     if (!canSubmit) return;
@@ -142,6 +209,37 @@ const Room = () => {
                   ))
               : "—"}
           </div>
+          {game?.mode === "CHAIN" && (
+            <div className={styles.letters_label}>
+              {game.chain?.length ? game.chain.join(" → ") : "Any word can start the chain"}
+              {chainLetter && ` — next word starts with ${chainLetter}`}
+            </div>
+          )}
+          {(game?.players || []).some((p) => p.profile) && (
+            <div className={styles.letters_label}>
+              Players: {game.players.map(profileText).join(" vs ")}
+            </div>
+          )}
+          {game?.handicapMode && game.handicapMode !== "OFF" && (
+            <div className={styles.letters_label}>
+              Handicaps:{" "}
+              {(game.players || [])
+                .map((p) => `${p.name} ${handicapText(p.handicap)}`)
+                .join(", ")}
+            </div>
+          )}
+          {game?.category && (
+            <div className={styles.letters_label}>
+              Category: {game.category.charAt(0) + game.category.slice(1).toLowerCase()}
+            </div>
+          )}
+          {game?.letterSource && game.letterSource !== "NO_RACK" && (
+            <div className={styles.letters_label}>
+              {game.letterSource === "CUSTOM"
+                ? "Chosen by the host"
+                : `From the ${game.letterPack || "classic"} pack`}
+            </div>
+          )}
         </div>
 
         <div className={styles.scores_block}>
@@ -168,6 +266,26 @@ const Room = () => {
                 <div className={styles.last_round_points}>
                   +{lastRoundRecord.hostPoints} / +{lastRoundRecord.guestPoints}
                 </div>
+                {[...(lastRoundRecord.hostBonuses || []), ...(lastRoundRecord.guestBonuses || [])]
+                  .length > 0 && (
+                  <div className={styles.last_round_points}>
+                    Bonuses: {bonusText(lastRoundRecord.hostBonuses)} /{" "}
+                    {bonusText(lastRoundRecord.guestBonuses)}
+                  </div>
+                )}
+                {lastRoundRecord.hostChallenge && (
+                  <div className={styles.last_round_points}>
+                    Host challenge: {challengeText(lastRoundRecord.hostChallenge)}
+                  </div>
+                )}
+                {lastRoundRecord.guestChallenge && (
+                  <div className={styles.last_round_points}>
+                    Guest challenge: {challengeText(lastRoundRecord.guestChallenge)}
+                  </div>
+                )}
+                {canChallenge && (
+                  <Button name="Challenge" onClick={handleChallenge} />
+                )}
               </div>
             )}
 
@@ -177,8 +295,8 @@ const Room = () => {
                 value={wordInput}
                 onChange={(e) => setWordInput(e.target.value.toUpperCase())}
                 placeholder="Enter a word (min 3 letters)"
-                maxLength={letters?.length || 10}
-                disabled={!myTurn || !!myWord}
+                maxLength={game?.mode === "CLASSIC" ? letters?.length || 10 : undefined}
+                disabled={!myTurn || !!myWord || !!mySeal}
               />
               <Button
                 name="Submit Word"
@@ -192,26 +310,75 @@ const Room = () => {
                 Your word: <strong>{myWord}</strong>
               </div>
             )}
+            {mySeal && !myWord && (
+              <div className={styles.my_word_display}>
+                Word sealed, revealed once your opponent has sealed theirs
+              </div>
+            )}
             {opponentWord && !myWord && (
               <div className={styles.opponent_word_display}>
                 Opponent word: <strong>{opponentWord}</strong>
               </div>
             )}
-            {myTurn && !myWord && (
+            {myTurn && !myWord && !mySeal && (
               <div className={styles.turn_hint}>Your turn — submit a word</div>
             )}
-            {!myTurn && !opponentWord && (
+            {(!myTurn || myWord || mySeal) && !opponentWord && (
               <div className={styles.turn_hint}>Waiting for opponent...</div>
             )}
             {lastNotification && (
               <div className={styles.notification}>{lastNotification}</div>
             )}
+
+            <div className={styles.chat}>
+              <div className={styles.chat_log}>
+                {chat.map((message) => (
+                  <div
+                    key={`${message.senderChainId}-${message.timestamp}-${message.text}`}
+                    className={styles.chat_line}
+                  >
+                    <strong>
+                      {message.senderChainId === chainId ? "You" : message.senderName}
+                    </strong>
+                    {message.reaction ? ` ${message.text}` : `: ${message.text}`}
+                  </div>
+                ))}
+              </div>
+              <div className={styles.chat_reactions}>
+                {chatReactions.map((emoji) => (
+                  <button
+                    key={emoji}
+                    type="button"
+                    className={styles.chat_reaction}
+                    onClick={() => sendReaction(emoji)}
+                  >
+                    {emoji}
+                  </button>
+                ))}
+              </div>
+              <div className={styles.chat_input_row}>
+                <input
+                  className={styles.chat_input}
+                  value={chatInput}
+                  maxLength={200}
+                  onChange={(e) => setChatInput(e.target.value)}
+                  onKeyDown={(e) => {
+                    if (e.key === "Enter") handleSendChat();
+                  }}
+                  placeholder="Say something"
+                />
+                <Button name="Send" disabled={!chatInput.trim()} onClick={handleSendChat} />
+              </div>
+            </div>
           </>
         ) : (
           <div className={styles.waiting}>
             <div className={styles.waiting_text}>
               Waiting for opponent to join...
             </div>
+            {game?.roomCode && (
+              <div className={styles.room_id}>Room code: {game.roomCode}</div>
+            )}
             <div className={styles.room_id}>Room ID: {chainId}</div>
           </div>
         )}
@@ -0,0 +1,24 @@
+//! Packs `data/words.txt` into the sections `src/dictionary.rs` includes, so chains look words
+//! up in the bundled dictionary without parsing or sorting it first.
+
+use std::{collections::BTreeMap, env, fs, path::Path};
+
+fn main() {
+    println!("cargo:rerun-if-changed=data/words.txt");
+    let text = fs::read_to_string("data/words.txt").expect("Failed to read data/words.txt");
+    let mut sections: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for word in text.lines().map(|line| line.trim().to_uppercase()) {
+        if !word.is_empty() {
+            sections.entry(word.len()).or_default().push(word);
+        }
+    }
+    let mut out = String::from("&[\n");
+    for (len, mut words) in sections {
+        words.sort_unstable();
+        words.dedup();
+        out.push_str(&format!("    ({len}, {:?}),\n", words.concat()));
+    }
+    out.push(']');
+    let path = Path::new(&env::var("OUT_DIR").expect("OUT_DIR not set")).join("english.rs");
+    fs::write(path, out).expect("Failed to write the packed dictionary");
+}
@@ -0,0 +1,212 @@
+//! A computer opponent that plays on its own chain through a local `linera service`.
+//!
+//! By default the bot hosts a match, prints its room code and opens a new one whenever a match
+//! ends. With `--join` it joins the given match instead. The bot only plays matches it hosts or
+//! joins: invites to its chain are declined.
+
+use std::{thread, time::Duration};
+
+use clap::Parser;
+use word_duel::{
+    bot::{Bot, Difficulty},
+    dictionary::Dictionary,
+    handicap::HandicapChoice,
+    GameMode, MatchStatus, RoundPhase, TurnOrder,
+};
+use word_duel_cli::client::{self, Client, MatchSettings};
+
+#[derive(Parser)]
+#[command(name = "word-duel-bot", about = "Let the computer play Word Duel")]
+struct Cli {
+    /// Base URL of `linera service`.
+    #[arg(
+        long,
+        env = "LINERA_SERVICE_URL",
+        default_value = "http://localhost:8081"
+    )]
+    url: String,
+    /// The chain the bot plays on.
+    #[arg(long, env = "LINERA_CHAIN_ID")]
+    chain_id: String,
+    /// The Word Duel application ID.
+    #[arg(long, env = "LINERA_APPLICATION_ID")]
+    application_id: String,
+    /// `random`, `greedy` or `optimal`.
+    #[arg(long, default_value_t = Difficulty::Greedy)]
+    difficulty: Difficulty,
+    #[arg(long, default_value = "Bot")]
+    name: String,
+    /// Rounds of the matches the bot hosts.
+    #[arg(long, default_value_t = 3)]
+    rounds: u32,
+    /// Host simultaneous matches, where both players play each round at once.
+    #[arg(long)]
+    simultaneous: bool,
+    /// Host rush matches, where each player submits many words per round.
+    #[arg(long, conflicts_with = "chain")]
+    rush: bool,
+    /// Host word-chain matches.
+    #[arg(long)]
+    chain: bool,
+    /// Host classic matches with category rounds.
+    #[arg(long, conflicts_with_all = ["rush", "chain"])]
+    categories: bool,
+    /// Host matches with bonuses.
+    #[arg(long)]
+    bonuses: bool,
+    /// Host matches that give the weaker player a handicap derived from the ratings.
+    #[arg(long)]
+    handicap_from_rating: bool,
+    /// Join this match (room code or host chain ID) instead of hosting.
+    #[arg(long)]
+    join: Option<String>,
+    /// Play a single match, then exit.
+    #[arg(long)]
+    once: bool,
+    /// Seconds between polls.
+    #[arg(long, default_value_t = 2)]
+    interval: u64,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let client = Client::new(&cli.url, cli.chain_id.clone(), &cli.application_id);
+    if let Err(error) = run(&cli, &client) {
+        eprintln!("error: {error}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: &Cli, client: &Client) -> client::Result<()> {
+    let bot = Bot::new(Dictionary::english(), cli.difficulty);
+    let interval = Duration::from_secs(cli.interval.max(1));
+    // The match being played, and whether a new one was asked for since it ended.
+    let mut current = None;
+    let mut requested = false;
+    let mut notification = None;
+    let mut played = None;
+    // In a simultaneous round: the turn, word and salt of the bot's seal, until revealed.
+    let mut sealed: Option<((String, u32), String, String)> = None;
+    loop {
+        let status = client.status()?;
+        if status.last_notification != notification {
+            if let Some(text) = &status.last_notification {
+                println!("* {text}");
+                // The host may not have opened its next match yet; ask again.
+                requested &= !text.starts_with("Join rejected");
+            }
+            notification = status.last_notification.clone();
+        }
+        for invite in client.invites()? {
+            client.decline_challenge(&invite.chain_id)?;
+            println!("Declined an invite from {}", invite.name);
+        }
+
+        match status.game.as_ref() {
+            Some(game) if game.status != MatchStatus::Ended => {
+                current = Some(game.match_id.clone());
+                requested = false;
+            }
+            _ => {
+                if current.take().is_some() {
+                    if let Some(result) = status.match_result {
+                        println!("Match over: {result:?}. Rating {}", status.rating);
+                    }
+                    if cli.once {
+                        return Ok(());
+                    }
+                }
+                if !requested {
+                    match &cli.join {
+                        Some(host) => println!("{}", client.join(host, &cli.name, None)?),
+                        None => {
+                            let settings = MatchSettings {
+                                turn_order: if cli.simultaneous {
+                                    TurnOrder::Simultaneous
+                                } else {
+                                    TurnOrder::Alternating
+                                },
+                                mode: if cli.rush {
+                                    GameMode::Rush
+                                } else if cli.chain {
+                                    GameMode::Chain
+                                } else {
+                                    GameMode::Classic
+                                },
+                                categories: cli.categories,
+                                bonuses: cli.bonuses,
+                                handicaps: if cli.handicap_from_rating {
+                                    HandicapChoice::FromRating
+                                } else {
+                                    HandicapChoice::Off
+                                },
+                                ..MatchSettings::default()
+                            };
+                            let result = client.create_match(&cli.name, cli.rounds, &settings)?;
+                            println!("{result}");
+                            if let Some(game) = client.status()?.game {
+                                println!("Room code: {}", game.room_code);
+                            }
+                        }
+                    }
+                    requested = true;
+                }
+            }
+        }
+
+        if let Some(game) = status.game.as_ref().filter(|_| status.my_turn()) {
+            let turn = (game.match_id.clone(), game.current_round);
+            if played.as_ref() != Some(&turn) {
+                let seed =
+                    game.match_id.parse::<u64>().unwrap_or_default() ^ game.current_round as u64;
+                let word = if game.mode == GameMode::Rush {
+                    let words = bot.choose_words(&game.rack(status.is_host), seed);
+                    (!words.is_empty()).then(|| words.join(" "))
+                } else {
+                    match (game.mode, game.category) {
+                        (GameMode::Chain, _) => bot.choose_chain_word(&game.current_chain(), seed),
+                        (_, Some(category)) => {
+                            Bot::new(Dictionary::category(category), cli.difficulty)
+                                .choose_word(&game.rack(status.is_host), seed)
+                        }
+                        _ => bot.choose_word(&game.rack(status.is_host), seed),
+                    }
+                };
+                match word {
+                    None => {
+                        // Skip the turn: once it runs out it scores nothing.
+                        println!(
+                            "Round {}: nothing to play, letting the turn run out",
+                            game.current_round
+                        );
+                        played = Some(turn);
+                    }
+                    Some(word) => {
+                        println!("Round {}: playing {word}", game.current_round);
+                        let result = if game.round_phase == RoundPhase::BothToPlay {
+                            client
+                                .seal_word(&word)
+                                .map(|salt| sealed = Some((turn.clone(), word, salt)))
+                        } else {
+                            client.submit_word(&word).map(drop)
+                        };
+                        match result {
+                            Ok(()) => played = Some(turn),
+                            // Tried again at the next poll.
+                            Err(error) => eprintln!("Could not play: {error}"),
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(game) = status.game.as_ref().filter(|_| status.reveal_due) {
+            let turn = (game.match_id.clone(), game.current_round);
+            if let Some((_, word, salt)) = sealed.take().filter(|(sealed, ..)| *sealed == turn) {
+                if let Err(error) = client.reveal_word(&word, &salt) {
+                    eprintln!("Could not reveal: {error}");
+                }
+            }
+        }
+        thread::sleep(interval);
+    }
+}
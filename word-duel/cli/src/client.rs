@@ -0,0 +1,545 @@
+//! GraphQL over HTTP against the application on one chain of a local `linera service`.
+
+use std::fmt;
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use word_duel::{
+    chat::ChatMessage,
+    handicap::{Handicap, HandicapChoice, HandicapMode},
+    profile::Profile,
+    Friend, Game, GameMode, LetterChoice, MatchResult, MatchStatus, RoundPhase, TurnOrder,
+    ROOM_CODE_LEN,
+};
+
+/// Selection for a whole `Game`, aliased to the crate's field names so it deserializes into
+/// `word_duel::Game` as is.
+const GAME_FIELDS: &str = "{
+    match_id: matchId host_chain_id: hostChainId room_code: roomCode status
+    players {
+        chain_id: chainId name rating
+        handicap { score_percent: scorePercent extra_letters: extraLetters extra_secs: extraSecs }
+        profile { display_name: displayName avatar_id: avatarId language country }
+    }
+    letters letter_source: letterSource letter_pack: letterPack
+    mode turn_order: turnOrder chain categories category bonuses handicap_mode: handicapMode
+    guest_handicap: guestHandicap {
+        score_percent: scorePercent extra_letters: extraLetters extra_secs: extraSecs
+    }
+    total_rounds: totalRounds current_round: currentRound
+    host_score: hostScore guest_score: guestScore round_phase: roundPhase
+    host_word: hostWord guest_word: guestWord host_seal: hostSeal guest_seal: guestSeal
+    turn_deadline: turnDeadline
+    turn_started: turnStarted host_elapsed_micros: hostElapsedMicros
+    guest_elapsed_micros: guestElapsedMicros
+    winner_chain_id: winnerChainId end_reason: endReason
+    history {
+        round host_word: hostWord guest_word: guestWord host_points: hostPoints
+        guest_points: guestPoints host_score: hostScore guest_score: guestScore timestamp
+        host_words: hostWords { word points blanks } guest_words: guestWords { word points blanks }
+        host_blanks: hostBlanks guest_blanks: guestBlanks
+        category host_verdict: hostVerdict guest_verdict: guestVerdict
+        host_elapsed_micros: hostElapsedMicros guest_elapsed_micros: guestElapsedMicros
+        host_streak: hostStreak guest_streak: guestStreak
+        host_bonuses: hostBonuses { kind points } guest_bonuses: guestBonuses { kind points }
+        host_handicap_points: hostHandicapPoints guest_handicap_points: guestHandicapPoints
+        host_challenge: hostChallenge { upheld struck_words: struckWords points }
+        guest_challenge: guestChallenge { upheld struck_words: struckWords points }
+    }
+    version
+}";
+
+/// How a new match is set up, besides its host name and length.
+#[derive(Debug, Clone, Default)]
+pub struct MatchSettings {
+    /// Only these chains may join; empty lets anyone in.
+    pub allow_list: Vec<String>,
+    pub invite_secret: Option<String>,
+    pub require_approval: bool,
+    pub turn_order: TurnOrder,
+    pub mode: GameMode,
+    pub letters: LetterChoice,
+    /// Play category rounds; only classic matches have them.
+    pub categories: bool,
+    /// Award bonuses on top of word points.
+    pub bonuses: bool,
+    pub handicaps: HandicapChoice,
+}
+
+/// An invite to a match waiting for this chain's answer.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedInvite {
+    /// The challenger, who will host the match.
+    pub chain_id: String,
+    pub name: String,
+    pub rating: u32,
+    pub rules: InviteRules,
+}
+
+/// The rules an invite offers, as the service lists them.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteRules {
+    pub total_rounds: u32,
+    pub turn_order: TurnOrder,
+    pub mode: GameMode,
+    pub custom_letters: Option<String>,
+    pub letter_pack: Option<String>,
+    pub categories: bool,
+    pub bonuses: bool,
+    pub handicap_mode: HandicapMode,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Http(Box<ureq::Error>),
+    Io(std::io::Error),
+    GraphQl(Vec<String>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(error) => write!(f, "request failed: {error}"),
+            Error::Io(error) => write!(f, "invalid response: {error}"),
+            Error::GraphQl(messages) => write!(f, "{}", messages.join("; ")),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// What this chain knows about its current match.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+    pub game: Option<Game>,
+    pub is_host: bool,
+    pub my_word: Option<String>,
+    pub opponent_word: Option<String>,
+    pub last_notification: Option<String>,
+    pub match_result: Option<MatchResult>,
+    pub rating: u32,
+    /// Whether this chain's sealed word should be revealed now.
+    pub reveal_due: bool,
+}
+
+impl Status {
+    /// Whether this chain is the one expected to submit a word now.
+    pub fn my_turn(&self) -> bool {
+        let Some(game) = &self.game else {
+            return false;
+        };
+        if game.status != MatchStatus::Active {
+            return false;
+        }
+        match game.round_phase {
+            RoundPhase::HostToPlay => self.is_host,
+            RoundPhase::GuestToPlay => !self.is_host && self.my_word.is_none(),
+            RoundPhase::BothToPlay if self.is_host => game.host_seal.is_none(),
+            RoundPhase::BothToPlay => game.guest_seal.is_none(),
+            RoundPhase::RoundComplete => false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct History {
+    archived_matches: Vec<Game>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Export {
+    export_match: Option<ExportedReplay>,
+}
+
+#[derive(Deserialize)]
+struct ExportedReplay {
+    json: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Friends {
+    friends: Vec<Friend>,
+    sent_invites: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Invites {
+    invites: Vec<ReceivedInvite>,
+}
+
+#[derive(Deserialize)]
+struct Chat {
+    chat: Vec<ChatMessage>,
+}
+
+#[derive(Deserialize)]
+struct ProfileLookup {
+    profile: Option<Profile>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct GraphQlResponse {
+    data: Option<Value>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+pub struct Client {
+    endpoint: String,
+    chain_id: String,
+}
+
+impl Client {
+    pub fn new(service_url: &str, chain_id: String, application_id: &str) -> Self {
+        let endpoint = format!(
+            "{}/chains/{chain_id}/applications/{application_id}",
+            service_url.trim_end_matches('/')
+        );
+        Client { endpoint, chain_id }
+    }
+
+    /// The chain this client plays on.
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    pub fn status(&self) -> Result<Status> {
+        self.request(
+            &format!(
+                "query {{ game {GAME_FIELDS} isHost myWord opponentWord lastNotification \
+                 matchResult rating revealDue }}"
+            ),
+            json!({}),
+        )
+    }
+
+    /// Ended matches archived on this chain, oldest first.
+    pub fn archived_matches(&self) -> Result<Vec<Game>> {
+        let history: History = self.request(
+            &format!("query {{ archivedMatches {GAME_FIELDS} }}"),
+            json!({}),
+        )?;
+        Ok(history.archived_matches)
+    }
+
+    /// The JSON replay of an ended match, or `None` if this chain has no such match.
+    pub fn export_match(&self, match_id: &str) -> Result<Option<String>> {
+        let export: Export = self.request(
+            "query($id: String!) { exportMatch(matchId: $id) { json } }",
+            json!({ "id": match_id }),
+        )?;
+        Ok(export.export_match.map(|replay| replay.json))
+    }
+
+    /// The profile of `chain_id` as this chain knows it.
+    pub fn profile(&self, chain_id: &str) -> Result<Option<Profile>> {
+        let lookup: ProfileLookup = self.request(
+            "query($chain: String!) { profile(chainId: $chain) { \
+             display_name: displayName avatar_id: avatarId language country } }",
+            json!({ "chain": chain_id }),
+        )?;
+        Ok(lookup.profile)
+    }
+
+    pub fn set_profile(&self, profile: &Profile) -> Result<String> {
+        self.mutate(
+            "mutation($profile: ProfileInput!) { setProfile(profile: $profile) }",
+            json!({
+                "profile": {
+                    "displayName": profile.display_name,
+                    "avatarId": profile.avatar_id,
+                    "language": profile.language,
+                    "country": profile.country,
+                }
+            }),
+        )
+    }
+
+    pub fn create_match(
+        &self,
+        host_name: &str,
+        total_rounds: u32,
+        settings: &MatchSettings,
+    ) -> Result<String> {
+        let mut variables = rules_input(total_rounds, settings);
+        variables["hostName"] = json!(host_name);
+        variables["allowList"] = json!(settings.allow_list);
+        variables["inviteSecret"] = json!(settings.invite_secret);
+        variables["requireApproval"] = json!(settings.require_approval);
+        self.mutate(
+            "mutation($hostName: String!, $totalRounds: Int!, $allowList: [String!], \
+             $inviteSecret: String, $requireApproval: Boolean, $turnOrder: TurnOrder, \
+             $mode: GameMode, $letters: String, $letterPack: String, $categories: Boolean, \
+             $bonuses: Boolean, $handicaps: HandicapMode, $hostHandicap: HandicapInput, \
+             $guestHandicap: HandicapInput) { createMatch(hostName: $hostName, \
+             totalRounds: $totalRounds, allowList: $allowList, inviteSecret: $inviteSecret, \
+             requireApproval: $requireApproval, turnOrder: $turnOrder, mode: $mode, \
+             letters: $letters, letterPack: $letterPack, categories: $categories, \
+             bonuses: $bonuses, handicaps: $handicaps, hostHandicap: $hostHandicap, \
+             guestHandicap: $guestHandicap) }",
+            variables,
+        )
+    }
+
+    /// Friends of this chain's player, and the chains they have challenged but not heard from.
+    pub fn friends(&self) -> Result<(Vec<Friend>, Vec<String>)> {
+        let friends: Friends = self.request(
+            "query { friends { chain_id: chainId name } sentInvites }",
+            json!({}),
+        )?;
+        Ok((friends.friends, friends.sent_invites))
+    }
+
+    pub fn add_friend(&self, chain_id: &str, name: &str) -> Result<String> {
+        self.mutate(
+            "mutation($chain: String!, $name: String!) { addFriend(chainId: $chain, name: $name) }",
+            json!({ "chain": chain_id, "name": name }),
+        )
+    }
+
+    pub fn remove_friend(&self, chain_id: &str) -> Result<String> {
+        self.mutate(
+            "mutation($chain: String!) { removeFriend(chainId: $chain) }",
+            json!({ "chain": chain_id }),
+        )
+    }
+
+    /// Invites waiting for this chain's answer.
+    pub fn invites(&self) -> Result<Vec<ReceivedInvite>> {
+        let invites: Invites = self.request(
+            "query { invites { chainId name rating rules { totalRounds turnOrder mode \
+             customLetters letterPack categories bonuses handicapMode } } }",
+            json!({}),
+        )?;
+        Ok(invites.invites)
+    }
+
+    /// Invites `chain_id` to a match hosted by this chain; its join settings are not used.
+    pub fn challenge_player(
+        &self,
+        chain_id: &str,
+        total_rounds: u32,
+        settings: &MatchSettings,
+    ) -> Result<String> {
+        self.mutate(
+            "mutation($chain: String!, $rules: MatchRulesInput!) { \
+             challengePlayer(chainId: $chain, rules: $rules) }",
+            json!({ "chain": chain_id, "rules": rules_input(total_rounds, settings) }),
+        )
+    }
+
+    pub fn accept_challenge(&self, chain_id: &str) -> Result<String> {
+        self.mutate(
+            "mutation($chain: String!) { acceptChallenge(chainId: $chain) }",
+            json!({ "chain": chain_id }),
+        )
+    }
+
+    pub fn decline_challenge(&self, chain_id: &str) -> Result<String> {
+        self.mutate(
+            "mutation($chain: String!) { declineChallenge(chainId: $chain) }",
+            json!({ "chain": chain_id }),
+        )
+    }
+
+    /// Joins by room code or, for anything longer than a code, by host chain ID.
+    pub fn join(
+        &self,
+        host: &str,
+        player_name: &str,
+        invite_secret: Option<&str>,
+    ) -> Result<String> {
+        // Room codes may be typed with a dash or a space.
+        if host.len() <= ROOM_CODE_LEN + 2 {
+            self.join_by_code(host, player_name, invite_secret)
+        } else {
+            self.join_match(host, player_name, invite_secret)
+        }
+    }
+
+    pub fn join_match(
+        &self,
+        host_chain_id: &str,
+        player_name: &str,
+        invite_secret: Option<&str>,
+    ) -> Result<String> {
+        self.mutate(
+            "mutation($host: String!, $name: String!, $secret: String) { \
+             joinMatch(hostChainId: $host, playerName: $name, inviteSecret: $secret) }",
+            json!({ "host": host_chain_id, "name": player_name, "secret": invite_secret }),
+        )
+    }
+
+    pub fn join_by_code(
+        &self,
+        code: &str,
+        player_name: &str,
+        invite_secret: Option<&str>,
+    ) -> Result<String> {
+        self.mutate(
+            "mutation($code: String!, $name: String!, $secret: String) { \
+             joinByCode(code: $code, playerName: $name, inviteSecret: $secret) }",
+            json!({ "code": code, "name": player_name, "secret": invite_secret }),
+        )
+    }
+
+    pub fn submit_word(&self, word: &str) -> Result<String> {
+        self.mutate(
+            "mutation($word: String!) { submitWord(word: $word) }",
+            json!({ "word": word }),
+        )
+    }
+
+    /// Submits several words at once, for rush matches.
+    pub fn submit_words(&self, words: &[String]) -> Result<String> {
+        self.mutate(
+            "mutation($words: [String!]!) { submitWords(words: $words) }",
+            json!({ "words": words }),
+        )
+    }
+
+    /// Simultaneous rounds: locks in `word` (in rush rounds, the words separated by spaces)
+    /// by its seal with a fresh random salt, and returns the salt `reveal_word` needs.
+    pub fn seal_word(&self, word: &str) -> Result<String> {
+        let salt = new_salt();
+        self.mutate(
+            "mutation($word: String!, $salt: String!) { sealWord(word: $word, salt: $salt) }",
+            json!({ "word": word, "salt": salt }),
+        )?;
+        Ok(salt)
+    }
+
+    /// Reveals the word sealed with `seal_word`, once the status says it is due.
+    pub fn reveal_word(&self, word: &str, salt: &str) -> Result<String> {
+        self.mutate(
+            "mutation($word: String!, $salt: String!) { revealWord(word: $word, salt: $salt) }",
+            json!({ "word": word, "salt": salt }),
+        )
+    }
+
+    pub fn claim_timeout(&self) -> Result<String> {
+        self.mutate("mutation { claimTimeout }", json!({}))
+    }
+
+    /// Challenges the opponent's words in the last scored round.
+    pub fn challenge(&self) -> Result<String> {
+        self.mutate("mutation { challenge }", json!({}))
+    }
+
+    /// The chat of the current match, or of the ended match `match_id`, oldest first.
+    pub fn chat(&self, match_id: Option<&str>) -> Result<Vec<ChatMessage>> {
+        let chat: Chat = self.request(
+            "query($id: String) { chat(matchId: $id) { sender_chain_id: senderChainId \
+             sender_name: senderName text reaction timestamp } }",
+            json!({ "id": match_id }),
+        )?;
+        Ok(chat.chat)
+    }
+
+    pub fn send_chat(&self, text: &str) -> Result<String> {
+        self.mutate(
+            "mutation($text: String!) { sendChat(text: $text) }",
+            json!({ "text": text }),
+        )
+    }
+
+    pub fn send_reaction(&self, emoji: &str) -> Result<String> {
+        self.mutate(
+            "mutation($emoji: String!) { sendReaction(emoji: $emoji) }",
+            json!({ "emoji": emoji }),
+        )
+    }
+
+    pub fn leave_match(&self) -> Result<String> {
+        self.mutate("mutation { leaveMatch }", json!({}))
+    }
+
+    /// Runs a mutation and returns its (only) result.
+    fn mutate(&self, mutation: &str, variables: Value) -> Result<String> {
+        let data: Value = self.request(mutation, variables)?;
+        Ok(match data.as_object().and_then(|o| o.values().next()) {
+            Some(Value::String(result)) => result.clone(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        })
+    }
+
+    fn request<T: DeserializeOwned>(&self, query: &str, variables: Value) -> Result<T> {
+        let response: GraphQlResponse = ureq::post(&self.endpoint)
+            .send_json(json!({ "query": query, "variables": variables }))
+            .map_err(|error| Error::Http(Box::new(error)))?
+            .into_json()
+            .map_err(Error::Io)?;
+        if !response.errors.is_empty() {
+            return Err(Error::GraphQl(
+                response.errors.into_iter().map(|e| e.message).collect(),
+            ));
+        }
+        let data = response.data.unwrap_or(Value::Null);
+        serde_json::from_value(data).map_err(|error| Error::Io(error.into()))
+    }
+}
+
+/// The rules of `settings` as a `MatchRulesInput` variable, whose fields are also the names
+/// of the matching `createMatch` arguments.
+fn rules_input(total_rounds: u32, settings: &MatchSettings) -> Value {
+    let turn_order = match settings.turn_order {
+        TurnOrder::Alternating => "ALTERNATING",
+        TurnOrder::Simultaneous => "SIMULTANEOUS",
+    };
+    let mode = match settings.mode {
+        GameMode::Classic => "CLASSIC",
+        GameMode::Rush => "RUSH",
+        GameMode::Chain => "CHAIN",
+    };
+    let (handicaps, host_handicap, guest_handicap) = match settings.handicaps {
+        HandicapChoice::Off => ("OFF", None, None),
+        HandicapChoice::FromRating => ("FROM_RATING", None, None),
+        HandicapChoice::Fixed { host, guest } => ("FIXED", Some(host), Some(guest)),
+    };
+    let (letters, pack) = match &settings.letters {
+        LetterChoice::Custom(letters) => (Some(letters), None),
+        LetterChoice::Pack(name) => (None, Some(name)),
+    };
+    json!({
+        "totalRounds": total_rounds,
+        "turnOrder": turn_order,
+        "mode": mode,
+        "letters": letters,
+        "letterPack": pack,
+        "categories": settings.categories,
+        "bonuses": settings.bonuses,
+        "handicaps": handicaps,
+        "hostHandicap": host_handicap.map(handicap_input),
+        "guestHandicap": guest_handicap.map(handicap_input),
+    })
+}
+
+/// A handicap as a `HandicapInput` variable.
+fn handicap_input(handicap: Handicap) -> Value {
+    json!({
+        "scorePercent": handicap.score_percent,
+        "extraLetters": handicap.extra_letters,
+        "extraSecs": handicap.extra_secs,
+    })
+}
+
+/// 32 random hex digits to seal a word with.
+fn new_salt() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("No randomness available");
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -0,0 +1,3 @@
+//! Shared pieces of the terminal clients.
+
+pub mod client;
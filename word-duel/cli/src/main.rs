@@ -0,0 +1,673 @@
+//! Play Word Duel from the terminal through a local `linera service`.
+
+use std::{thread, time::Duration};
+
+use clap::{Args, Parser, Subcommand};
+use word_duel::{
+    challenge::Challenge,
+    chat::{self, ChatMessage},
+    handicap::{Handicap, HandicapChoice, HandicapMode},
+    profile::Profile,
+    replay::MatchReplay,
+    Game, GameMode, LetterChoice, LetterSource, MatchStatus, RoundPhase, RoundRecord, TurnOrder,
+};
+use word_duel_cli::client::{self, Client, MatchSettings, ReceivedInvite, Status};
+
+#[derive(Parser)]
+#[command(name = "word-duel-cli", about = "Play Word Duel from the terminal")]
+struct Cli {
+    /// Base URL of `linera service`.
+    #[arg(
+        long,
+        env = "LINERA_SERVICE_URL",
+        default_value = "http://localhost:8081"
+    )]
+    url: String,
+    /// The chain to play on.
+    #[arg(long, env = "LINERA_CHAIN_ID")]
+    chain_id: String,
+    /// The Word Duel application ID.
+    #[arg(long, env = "LINERA_APPLICATION_ID")]
+    application_id: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Host a new match.
+    Create {
+        /// Your name in this match; defaults to your profile's display name.
+        #[arg(long)]
+        name: Option<String>,
+        /// Only let these chains join.
+        #[arg(long = "allow")]
+        allow_list: Vec<String>,
+        /// Require joiners to present this secret.
+        #[arg(long)]
+        secret: Option<String>,
+        /// Accept each join request by hand.
+        #[arg(long)]
+        approve: bool,
+        #[command(flatten)]
+        rules: RulesArgs,
+    },
+    /// Invite a player to a match you host, played by the given rules.
+    Invite {
+        chain_id: String,
+        #[command(flatten)]
+        rules: RulesArgs,
+    },
+    /// List invites waiting for your answer.
+    Invites,
+    /// Accept the invite from a chain; its match starts at once.
+    AcceptInvite { chain_id: String },
+    /// Decline the invite from a chain.
+    DeclineInvite { chain_id: String },
+    /// Add a chain to your friends under a name.
+    AddFriend { chain_id: String, name: String },
+    /// Remove a chain from your friends.
+    RemoveFriend { chain_id: String },
+    /// List your friends and the players you invited who have not answered.
+    Friends,
+    /// Join a match by its host chain ID or room code.
+    Join {
+        host: String,
+        /// Your name in this match; defaults to your profile's display name.
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        secret: Option<String>,
+    },
+    /// Submit a word for the current round, or several in a rush match. In a simultaneous
+    /// round they are sealed first and revealed once both players have sealed theirs.
+    Play {
+        #[arg(required = true)]
+        words: Vec<String>,
+    },
+    /// End the opponent's turn once its deadline has passed.
+    ClaimTimeout,
+    /// Challenge the opponent's words in the last round; costs points if they all hold up.
+    Challenge,
+    /// Leave the current match; forfeits it if it is running.
+    Leave,
+    /// Send a message to your opponent.
+    Say {
+        #[arg(required = true)]
+        words: Vec<String>,
+    },
+    /// React to your opponent with one of the chat emoji.
+    React {
+        #[arg(value_parser = clap::builder::PossibleValuesParser::new(chat::REACTIONS))]
+        emoji: String,
+    },
+    /// Show the chat of the current match, or of an ended one.
+    Chat { match_id: Option<String> },
+    /// Set the profile shown to everyone you play from now on.
+    SetProfile {
+        /// Display name, also used as your name in matches unless you give another.
+        name: String,
+        /// One of the avatars, numbered from 0.
+        #[arg(long, default_value_t = 0)]
+        avatar: u32,
+        /// Two-letter language code, e.g. en.
+        #[arg(long, default_value = "")]
+        language: String,
+        /// Two-letter country code, e.g. DE.
+        #[arg(long, default_value = "")]
+        country: String,
+    },
+    /// Show the profile of a chain you have played, or your own.
+    Profile { chain_id: Option<String> },
+    /// Show the current match.
+    Status,
+    /// Show the rounds of the current match and the archived matches.
+    History,
+    /// Print the JSON replay of an ended match.
+    Export { match_id: String },
+    /// Re-score a JSON replay and check its recorded result.
+    Verify { file: std::path::PathBuf },
+    /// Print round results as they arrive, until the match ends.
+    Watch {
+        /// Seconds between polls.
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
+    },
+}
+
+/// How a hosted match is played.
+#[derive(Args)]
+struct RulesArgs {
+    #[arg(long, default_value_t = 3)]
+    rounds: u32,
+    /// Let both players play each round at once, without seeing each other's word.
+    #[arg(long)]
+    simultaneous: bool,
+    /// Play rush rounds: both players submit as many words as they find at once.
+    #[arg(long, conflicts_with = "chain")]
+    rush: bool,
+    /// Play a word chain: each word starts with the last letter of the one before.
+    #[arg(long)]
+    chain: bool,
+    /// Play on this rack instead of one from a pack.
+    #[arg(long, conflicts_with = "pack")]
+    letters: Option<String>,
+    /// Draw the rack from this letter pack: classic, long, blanks or tricky.
+    #[arg(long)]
+    pack: Option<String>,
+    /// Give rounds a category (animals, countries or food) that words must belong to.
+    #[arg(long, conflicts_with_all = ["rush", "chain"])]
+    categories: bool,
+    /// Award bonuses for pangrams, streaks, speed and longer words.
+    #[arg(long)]
+    bonuses: bool,
+    /// Give the weaker player a handicap derived from the ratings.
+    #[arg(long, conflicts_with_all = ["host_handicap", "guest_handicap"])]
+    handicap_from_rating: bool,
+    /// Your handicap as PERCENT:LETTERS:SECS, e.g. 120:1:30 for 120% points, one extra
+    /// letter and 30 more seconds per turn.
+    #[arg(long, value_parser = parse_handicap)]
+    host_handicap: Option<Handicap>,
+    /// The guest's handicap as PERCENT:LETTERS:SECS.
+    #[arg(long, value_parser = parse_handicap)]
+    guest_handicap: Option<Handicap>,
+}
+
+impl RulesArgs {
+    /// The number of rounds, and the rest of the rules with nobody barred from joining.
+    fn into_settings(self) -> (u32, MatchSettings) {
+        let settings = MatchSettings {
+            turn_order: if self.simultaneous {
+                TurnOrder::Simultaneous
+            } else {
+                TurnOrder::Alternating
+            },
+            mode: if self.rush {
+                GameMode::Rush
+            } else if self.chain {
+                GameMode::Chain
+            } else {
+                GameMode::Classic
+            },
+            letters: match (self.letters, self.pack) {
+                (Some(letters), _) => LetterChoice::Custom(letters),
+                (None, Some(pack)) => LetterChoice::Pack(pack),
+                (None, None) => LetterChoice::default(),
+            },
+            categories: self.categories,
+            bonuses: self.bonuses,
+            handicaps: match (self.host_handicap, self.guest_handicap) {
+                (None, None) if self.handicap_from_rating => HandicapChoice::FromRating,
+                (None, None) => HandicapChoice::Off,
+                (host, guest) => HandicapChoice::Fixed {
+                    host: host.unwrap_or_default(),
+                    guest: guest.unwrap_or_default(),
+                },
+            },
+            ..MatchSettings::default()
+        };
+        (self.rounds, settings)
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let client = Client::new(&cli.url, cli.chain_id, &cli.application_id);
+    if let Err(error) = run(&client, cli.command) {
+        eprintln!("error: {error}");
+        std::process::exit(1);
+    }
+}
+
+fn run(client: &Client, command: Command) -> client::Result<()> {
+    match command {
+        Command::Create {
+            name,
+            allow_list,
+            secret,
+            approve,
+            rules,
+        } => {
+            let (rounds, settings) = rules.into_settings();
+            let settings = MatchSettings {
+                allow_list,
+                invite_secret: secret,
+                require_approval: approve,
+                ..settings
+            };
+            let name = name.unwrap_or_default();
+            let result = client.create_match(&name, rounds, &settings)?;
+            println!("{result}");
+            println!("Share your chain ID: {}", client.chain_id());
+        }
+        Command::Invite { chain_id, rules } => {
+            let (rounds, settings) = rules.into_settings();
+            println!("{}", client.challenge_player(&chain_id, rounds, &settings)?);
+        }
+        Command::Invites => {
+            let invites = client.invites()?;
+            if invites.is_empty() {
+                println!("No invites");
+            }
+            for invite in &invites {
+                println!("{}", invite_line(invite));
+            }
+        }
+        Command::AcceptInvite { chain_id } => println!("{}", client.accept_challenge(&chain_id)?),
+        Command::DeclineInvite { chain_id } => {
+            println!("{}", client.decline_challenge(&chain_id)?)
+        }
+        Command::AddFriend { chain_id, name } => {
+            println!("{}", client.add_friend(&chain_id, &name)?)
+        }
+        Command::RemoveFriend { chain_id } => println!("{}", client.remove_friend(&chain_id)?),
+        Command::Friends => {
+            let (friends, sent_invites) = client.friends()?;
+            if friends.is_empty() {
+                println!("No friends yet");
+            }
+            for friend in &friends {
+                let invited = if sent_invites.contains(&friend.chain_id) {
+                    " (invited)"
+                } else {
+                    ""
+                };
+                println!("{} {}{invited}", friend.chain_id, friend.name);
+            }
+        }
+        Command::Join { host, name, secret } => {
+            let name = name.unwrap_or_default();
+            println!("{}", client.join(&host, &name, secret.as_deref())?);
+        }
+        Command::Play { words } => play(client, &words)?,
+        Command::ClaimTimeout => println!("{}", client.claim_timeout()?),
+        Command::Challenge => println!("{}", client.challenge()?),
+        Command::Leave => println!("{}", client.leave_match()?),
+        Command::Say { words } => println!("{}", client.send_chat(&words.join(" "))?),
+        Command::React { emoji } => println!("{}", client.send_reaction(&emoji)?),
+        Command::Chat { match_id } => {
+            let messages = client.chat(match_id.as_deref())?;
+            if messages.is_empty() {
+                println!("No messages");
+            }
+            for message in &messages {
+                println!("{}", chat_line(message, client.chain_id()));
+            }
+        }
+        Command::SetProfile {
+            name,
+            avatar,
+            language,
+            country,
+        } => {
+            let profile = Profile {
+                display_name: name,
+                avatar_id: avatar,
+                language,
+                country,
+            };
+            println!("{}", client.set_profile(&profile)?);
+        }
+        Command::Profile { chain_id } => {
+            let chain_id = chain_id.as_deref().unwrap_or(client.chain_id());
+            match client.profile(chain_id)? {
+                Some(profile) => println!("{}", profile_text(&profile)),
+                None => println!("No profile"),
+            }
+        }
+        Command::Status => print_status(&client.status()?),
+        Command::History => {
+            let status = client.status()?;
+            if let Some(game) = &status.game {
+                println!("Current match {}:", game.match_id);
+                for record in &game.history {
+                    println!("  {}", round_line(record, status.is_host));
+                }
+            }
+            let archived = client.archived_matches()?;
+            println!("Archived matches: {}", archived.len());
+            for game in &archived {
+                println!("  {}", summary_line(game, client.chain_id()));
+            }
+        }
+        Command::Export { match_id } => match client.export_match(&match_id)? {
+            Some(json) => println!("{json}"),
+            None => println!("No ended match {match_id} on this chain"),
+        },
+        Command::Verify { file } => {
+            let json = std::fs::read_to_string(file).map_err(client::Error::Io)?;
+            match MatchReplay::from_json(&json).and_then(|replay| replay.verify()) {
+                Ok(()) => println!("Replay verified"),
+                Err(error) => {
+                    eprintln!("Replay rejected: {error}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Watch { interval } => watch(client, Duration::from_secs(interval.max(1)))?,
+    }
+    Ok(())
+}
+
+/// Plays `words`; in a simultaneous round, seals them and waits until they can be revealed.
+fn play(client: &Client, words: &[String]) -> client::Result<()> {
+    let status = client.status()?;
+    let Some(game) = status
+        .game
+        .filter(|game| game.round_phase == RoundPhase::BothToPlay)
+    else {
+        match words {
+            [word] => println!("{}", client.submit_word(word)?),
+            _ => println!("{}", client.submit_words(words)?),
+        }
+        return Ok(());
+    };
+    let word = words.join(" ");
+    let salt = client.seal_word(&word)?;
+    println!("Word sealed, waiting to reveal it");
+    loop {
+        thread::sleep(Duration::from_secs(2));
+        let status = client.status()?;
+        if status.game.as_ref().map(|g| g.current_round) != Some(game.current_round) {
+            println!("The round was scored before the word could be revealed");
+            return Ok(());
+        }
+        if status.reveal_due {
+            println!("{}", client.reveal_word(&word, &salt)?);
+            return Ok(());
+        }
+    }
+}
+
+fn watch(client: &Client, interval: Duration) -> client::Result<()> {
+    let mut version = None;
+    let mut rounds_seen = 0;
+    let mut notification = None;
+    loop {
+        let status = client.status()?;
+        if status.last_notification != notification {
+            if let Some(text) = &status.last_notification {
+                println!("* {text}");
+            }
+            notification = status.last_notification.clone();
+        }
+        let Some(game) = &status.game else {
+            thread::sleep(interval);
+            continue;
+        };
+        if version.is_none() {
+            print_status(&status);
+            rounds_seen = game.history.last().map_or(0, |r| r.round);
+        } else if version != Some((game.match_id.clone(), game.version)) {
+            for record in game.history.iter().filter(|r| r.round > rounds_seen) {
+                println!("{}", round_line(record, status.is_host));
+            }
+            rounds_seen = game.history.last().map_or(rounds_seen, |r| r.round);
+            if game.status == MatchStatus::Ended {
+                print_status(&status);
+                return Ok(());
+            }
+            println!("{}", turn_line(&status));
+        }
+        version = Some((game.match_id.clone(), game.version));
+        thread::sleep(interval);
+    }
+}
+
+fn print_status(status: &Status) {
+    let Some(game) = &status.game else {
+        println!("No match. Rating: {}", status.rating);
+        return;
+    };
+    println!(
+        "Match {} (room {}) - {:?}, round {}/{}",
+        game.match_id,
+        game.room_code,
+        game.status,
+        game.current_round.min(game.total_rounds),
+        game.total_rounds
+    );
+    let letters: Vec<String> = game
+        .rack(status.is_host)
+        .chars()
+        .map(String::from)
+        .collect();
+    let source = match (game.letter_source, &game.letter_pack) {
+        (LetterSource::NoRack, _) => None,
+        (LetterSource::Custom, _) => Some("chosen by the host".to_string()),
+        (LetterSource::Pack, Some(pack)) => Some(format!("{pack} pack")),
+        (LetterSource::Pack, None) => Some("pack".to_string()),
+    };
+    match source {
+        Some(source) => println!("Letters: {} ({source})", letters.join(" ")),
+        None => {
+            let next = game
+                .chain_letter()
+                .map_or("any letter".to_string(), String::from);
+            println!(
+                "Chain: {} (next word starts with {next})",
+                game.chain.join(" ")
+            );
+        }
+    }
+    if let Some(category) = game.category {
+        println!("Category: {category:?}");
+    }
+    let (mine, theirs) = if status.is_host {
+        (game.host_score, game.guest_score)
+    } else {
+        (game.guest_score, game.host_score)
+    };
+    let opponent = game
+        .players
+        .iter()
+        .find(|p| (p.chain_id == game.host_chain_id) != status.is_host)
+        .map_or("(waiting)", |p| p.name.as_str());
+    println!("You {mine} : {theirs} {opponent}");
+    if game.handicap_mode != HandicapMode::Off {
+        println!(
+            "Handicaps: you {}, opponent {}",
+            handicap_text(game.handicap(status.is_host)),
+            handicap_text(game.handicap(!status.is_host))
+        );
+    }
+    match status.match_result {
+        Some(result) => match game.end_reason {
+            Some(reason) => println!("Result: {result:?} ({reason:?})"),
+            None => println!("Result: {result:?}"),
+        },
+        None => println!("{}", turn_line(status)),
+    }
+    if let Some(text) = &status.last_notification {
+        println!("Last notification: {text}");
+    }
+}
+
+fn turn_line(status: &Status) -> String {
+    let Some(game) = &status.game else {
+        return String::new();
+    };
+    if game.status != MatchStatus::Active {
+        return "Waiting for a player to join".to_string();
+    }
+    let mut line = if status.my_turn() {
+        "Your turn".to_string()
+    } else {
+        "Opponent's turn".to_string()
+    };
+    if let Some(word) = status.opponent_word.as_deref().filter(|w| !w.is_empty()) {
+        line.push_str(&format!(" (opponent played {word})"));
+    }
+    line
+}
+
+fn round_line(record: &RoundRecord, is_host: bool) -> String {
+    let (my_word, my_points, my_bonuses, their_word, their_points) = if is_host {
+        (
+            &record.host_word,
+            record.host_points,
+            &record.host_bonuses,
+            &record.guest_word,
+            record.guest_points,
+        )
+    } else {
+        (
+            &record.guest_word,
+            record.guest_points,
+            &record.guest_bonuses,
+            &record.host_word,
+            record.host_points,
+        )
+    };
+    let (my_score, their_score, my_handicap) = if is_host {
+        (
+            record.host_score,
+            record.guest_score,
+            record.host_handicap_points,
+        )
+    } else {
+        (
+            record.guest_score,
+            record.host_score,
+            record.guest_handicap_points,
+        )
+    };
+    let category = record
+        .category
+        .map_or(String::new(), |category| format!(" ({category:?})"));
+    let mut bonuses: String = my_bonuses
+        .iter()
+        .map(|bonus| format!(", {:?} +{}", bonus.kind, bonus.points))
+        .collect();
+    if my_handicap != 0 {
+        bonuses.push_str(&format!(", handicap {my_handicap:+}"));
+    }
+    let (my_challenge, their_challenge) = if is_host {
+        (&record.host_challenge, &record.guest_challenge)
+    } else {
+        (&record.guest_challenge, &record.host_challenge)
+    };
+    let challenges: String = [("you", my_challenge), ("opponent", their_challenge)]
+        .into_iter()
+        .filter_map(|(who, challenge)| Some(challenge_text(who, challenge.as_ref()?)))
+        .collect();
+    format!(
+        "Round {}{category}: you {} (+{my_points}{bonuses}), opponent {} (+{their_points}) - \
+         {my_score}:{their_score}{challenges}",
+        record.round,
+        word_or_dash(my_word),
+        word_or_dash(their_word),
+    )
+}
+
+fn challenge_text(who: &str, challenge: &Challenge) -> String {
+    if challenge.upheld {
+        format!(
+            "; {who} challenged and struck {} (-{})",
+            challenge.struck_words.join(" "),
+            challenge.points
+        )
+    } else {
+        format!("; {who} challenged and lost {}", challenge.points)
+    }
+}
+
+fn invite_line(invite: &ReceivedInvite) -> String {
+    let rules = &invite.rules;
+    let letters = match (&rules.custom_letters, &rules.letter_pack) {
+        (Some(letters), _) => format!("letters {letters}"),
+        (None, Some(pack)) => format!("{pack} pack"),
+        (None, None) => "no rack".to_string(),
+    };
+    let mut extras = String::new();
+    if rules.categories {
+        extras.push_str(", categories");
+    }
+    if rules.bonuses {
+        extras.push_str(", bonuses");
+    }
+    if rules.handicap_mode != HandicapMode::Off {
+        extras.push_str(&format!(", {:?} handicaps", rules.handicap_mode));
+    }
+    format!(
+        "{} {} ({}) - {} rounds, {:?} {:?}, {letters}{extras}",
+        invite.chain_id,
+        invite.name,
+        invite.rating,
+        rules.total_rounds,
+        rules.mode,
+        rules.turn_order
+    )
+}
+
+fn chat_line(message: &ChatMessage, chain_id: &str) -> String {
+    let sender = if message.sender_chain_id == chain_id {
+        "you"
+    } else {
+        message.sender_name.as_str()
+    };
+    if message.reaction {
+        format!("{sender} reacted {}", message.text)
+    } else {
+        format!("{sender}: {}", message.text)
+    }
+}
+
+fn summary_line(game: &Game, chain_id: &str) -> String {
+    let result = game
+        .result_for(chain_id)
+        .map_or("-".to_string(), |r| format!("{r:?}"));
+    format!(
+        "{} - {result}, {}:{} after {} rounds",
+        game.match_id,
+        game.host_score,
+        game.guest_score,
+        game.history.len()
+    )
+}
+
+fn profile_text(profile: &Profile) -> String {
+    let mut text = format!("{} (avatar {})", profile.display_name, profile.avatar_id);
+    for code in [&profile.language, &profile.country] {
+        if !code.is_empty() {
+            text.push_str(&format!(", {code}"));
+        }
+    }
+    text
+}
+
+fn handicap_text(handicap: Handicap) -> String {
+    if handicap == Handicap::default() {
+        return "none".to_string();
+    }
+    format!(
+        "{}% points, {} extra letters, {}s more per turn",
+        handicap.score_percent, handicap.extra_letters, handicap.extra_secs
+    )
+}
+
+/// Reads a handicap written as PERCENT:LETTERS:SECS.
+fn parse_handicap(text: &str) -> Result<Handicap, String> {
+    let parts: Vec<&str> = text.split(':').collect();
+    let [percent, letters, secs] = parts[..] else {
+        return Err("expected PERCENT:LETTERS:SECS".to_string());
+    };
+    let number = |part: &str| {
+        part.trim()
+            .parse::<u32>()
+            .map_err(|error| error.to_string())
+    };
+    Ok(Handicap {
+        score_percent: number(percent)?,
+        extra_letters: number(letters)?,
+        extra_secs: number(secs)?,
+    })
+}
+
+fn word_or_dash(word: &str) -> &str {
+    if word.is_empty() {
+        "-"
+    } else {
+        word
+    }
+}
@@ -0,0 +1,28 @@
+//! Feeds arbitrary Unicode racks and words through the word rules and checks they agree.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use word_duel::{blanks_played, letters_for_match, normalize_word, validate_word, word_score};
+
+fuzz_target!(|input: (&str, &str, u64)| {
+    let (rack, word, match_id) = input;
+
+    let score = word_score(rack, word);
+    // Letters played with blank tiles score nothing, so a valid word may score 0.
+    let blanks = blanks_played(rack, word).chars().count();
+    if validate_word(rack, word) {
+        let len = normalize_word(word).chars().count();
+        assert!(len >= 3);
+        assert_eq!(score as usize, len - blanks);
+    } else {
+        assert_eq!((score, blanks), (0, 0));
+    }
+    assert!(score as usize <= rack.to_uppercase().chars().count());
+
+    let letters = letters_for_match(match_id);
+    assert_eq!(
+        word_score(&letters, word),
+        word_score(&letters.to_lowercase(), word)
+    );
+});
@@ -0,0 +1,162 @@
+//! Extra points on top of a round's word points, in matches played with bonuses.
+//!
+//! Each [`BonusKind`] is one rule; a round awards every rule that applies to a player, each as
+//! its own [`Bonus`] in the [`RoundRecord`](crate::RoundRecord). Adding a rule means adding a
+//! variant and its arms in [`BonusKind::points`] and [`BonusKind::value`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::{normalize_word, GameMode, SubmissionScore};
+
+/// Bonus for a word that uses every tile of the rack.
+pub const PANGRAM_BONUS: u32 = 5;
+
+/// Valid rounds in a row it takes to earn [`STREAK_BONUS`].
+pub const STREAK_ROUNDS: u32 = 3;
+
+/// Bonus for every valid round that extends a streak of at least [`STREAK_ROUNDS`].
+pub const STREAK_BONUS: u32 = 2;
+
+/// Bonus for a valid submission made in less time than the opponent took.
+pub const SPEED_BONUS: u32 = 1;
+
+/// Bonus for a valid word longer than any the opponent played.
+pub const LONGER_WORD_BONUS: u32 = 2;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum BonusKind {
+    /// A word uses every tile of the rack.
+    #[serde(alias = "PANGRAM")]
+    Pangram,
+    /// The player has had a valid word for [`STREAK_ROUNDS`] rounds in a row or more.
+    #[serde(alias = "STREAK")]
+    Streak,
+    /// The player took less time than the opponent.
+    #[serde(alias = "SPEED")]
+    Speed,
+    /// The player's longest word beats the opponent's longest.
+    #[serde(alias = "LONGER_WORD")]
+    LongerWord,
+}
+
+impl BonusKind {
+    /// Every rule, in the order bonuses are listed.
+    pub const ALL: [BonusKind; 4] = [
+        BonusKind::Pangram,
+        BonusKind::Streak,
+        BonusKind::Speed,
+        BonusKind::LongerWord,
+    ];
+
+    /// What this rule awards `mine` against `theirs` on `letters`; 0 if it does not apply.
+    pub fn points(self, letters: &str, mine: &Play, theirs: &Play) -> u32 {
+        if mine.valid_words.is_empty() {
+            return 0;
+        }
+        let applies = match self {
+            BonusKind::Pangram => {
+                let rack = letters.chars().count();
+                rack > 0 && mine.valid_words.iter().any(|w| w.chars().count() == rack)
+            }
+            BonusKind::Streak => mine.streak >= STREAK_ROUNDS,
+            BonusKind::Speed => mine.elapsed_micros < theirs.elapsed_micros,
+            BonusKind::LongerWord => mine.longest_word() > theirs.longest_word(),
+        };
+        if applies {
+            self.value()
+        } else {
+            0
+        }
+    }
+
+    /// The points this rule is worth when it applies.
+    pub fn value(self) -> u32 {
+        match self {
+            BonusKind::Pangram => PANGRAM_BONUS,
+            BonusKind::Streak => STREAK_BONUS,
+            BonusKind::Speed => SPEED_BONUS,
+            BonusKind::LongerWord => LONGER_WORD_BONUS,
+        }
+    }
+}
+
+/// One bonus a player earned in a round.
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::SimpleObject,
+)]
+pub struct Bonus {
+    pub kind: BonusKind,
+    pub points: u32,
+}
+
+/// One player's side of a round, as the bonus rules see it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Play {
+    /// The submitted words that scored.
+    pub valid_words: Vec<String>,
+    /// Time from the start of the player's turn until the host had the submission, or until
+    /// the turn ran out.
+    pub elapsed_micros: u64,
+    /// Valid rounds in a row, this one included; 0 if this round's submission scored nothing.
+    pub streak: u32,
+}
+
+impl Play {
+    /// The play of normalized `submission`, scored as `score`, after a streak of
+    /// `previous_streak` valid rounds.
+    pub fn new(
+        mode: GameMode,
+        submission: &str,
+        score: &SubmissionScore,
+        elapsed_micros: u64,
+        previous_streak: u32,
+    ) -> Self {
+        let valid_words = match mode {
+            GameMode::Rush => score
+                .words
+                .iter()
+                .filter(|w| w.points > 0)
+                .map(|w| w.word.clone())
+                .collect(),
+            GameMode::Classic | GameMode::Chain if score.points > 0 => {
+                vec![normalize_word(submission)]
+            }
+            GameMode::Classic | GameMode::Chain => Vec::new(),
+        };
+        let streak = if valid_words.is_empty() {
+            0
+        } else {
+            previous_streak.saturating_add(1)
+        };
+        Play {
+            valid_words,
+            elapsed_micros,
+            streak,
+        }
+    }
+
+    fn longest_word(&self) -> usize {
+        self.valid_words
+            .iter()
+            .map(|w| w.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// The bonuses `mine` earns against `theirs` on `letters`.
+pub fn bonuses(letters: &str, mine: &Play, theirs: &Play) -> Vec<Bonus> {
+    BonusKind::ALL
+        .into_iter()
+        .map(|kind| Bonus {
+            kind,
+            points: kind.points(letters, mine, theirs),
+        })
+        .filter(|bonus| bonus.points > 0)
+        .collect()
+}
+
+/// The points of all `bonuses` together.
+pub fn total(bonuses: &[Bonus]) -> u32 {
+    bonuses.iter().map(|bonus| bonus.points).sum()
+}
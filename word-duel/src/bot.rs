@@ -0,0 +1,140 @@
+//! Computer opponents: pick a word for a rack from a dictionary.
+
+use std::{fmt, str::FromStr};
+
+use crate::{
+    chain_word_score,
+    dictionary::Dictionary,
+    solver::{self, ScoredWord},
+    MAX_RUSH_WORDS,
+};
+
+/// How hard a bot tries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    /// Any playable word, picked at random.
+    Random,
+    /// The longest playable word.
+    #[default]
+    Greedy,
+    /// The playable word scoring the most points under the game's rules.
+    Optimal,
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "random" | "easy" => Ok(Difficulty::Random),
+            "greedy" | "medium" => Ok(Difficulty::Greedy),
+            "optimal" | "hard" => Ok(Difficulty::Optimal),
+            _ => Err(format!("Unknown difficulty '{s}'")),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Random => "random",
+            Difficulty::Greedy => "greedy",
+            Difficulty::Optimal => "optimal",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A player that picks its words from a dictionary.
+#[derive(Debug, Clone)]
+pub struct Bot<'a> {
+    dictionary: &'a Dictionary,
+    difficulty: Difficulty,
+}
+
+impl<'a> Bot<'a> {
+    pub fn new(dictionary: &'a Dictionary, difficulty: Difficulty) -> Self {
+        Bot {
+            dictionary,
+            difficulty,
+        }
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// The bot's word for `letters`, or `None` if the dictionary has no playable word.
+    ///
+    /// `seed` only matters for `Random`, which picks the same word for the same seed; ties are
+    /// otherwise broken alphabetically.
+    pub fn choose_word(&self, letters: &str, seed: u64) -> Option<String> {
+        // Best first, alphabetical among equal scores.
+        self.pick(solver::playable_words(self.dictionary, letters), seed)
+    }
+
+    /// The bot's next word after `chain` in a word-chain match (see [`crate::Game::current_chain`]),
+    /// or `None` if no word can follow it.
+    pub fn choose_chain_word(&self, chain: &[String], seed: u64) -> Option<String> {
+        let mut candidates: Vec<ScoredWord> = self
+            .dictionary
+            .words()
+            .map(|word| ScoredWord {
+                word: word.to_string(),
+                points: chain_word_score(self.dictionary, chain, word),
+                blanks: String::new(),
+            })
+            .filter(|scored| scored.points > 0)
+            .collect();
+        candidates.sort_by(|a, b| b.points.cmp(&a.points).then_with(|| a.word.cmp(&b.word)));
+        self.pick(candidates, seed)
+    }
+
+    /// Picks from `playable`, ordered best first, as this bot's difficulty says.
+    fn pick(&self, mut playable: Vec<ScoredWord>, seed: u64) -> Option<String> {
+        if playable.is_empty() {
+            return None;
+        }
+        let index = match self.difficulty {
+            Difficulty::Random => (mix(seed) % playable.len() as u64) as usize,
+            Difficulty::Greedy => {
+                let longest = playable.iter().map(|w| w.word.chars().count()).max()?;
+                playable
+                    .iter()
+                    .position(|w| w.word.chars().count() == longest)?
+            }
+            Difficulty::Optimal => 0,
+        };
+        Some(playable.swap_remove(index).word)
+    }
+
+    /// The bot's words for a rush round on `letters`, at most [`MAX_RUSH_WORDS`] of them.
+    ///
+    /// `Random` plays about a third of the playable words, chosen by `seed`; `Greedy` plays its
+    /// few best words and `Optimal` every playable word up to the limit.
+    pub fn choose_words(&self, letters: &str, seed: u64) -> Vec<String> {
+        let playable = solver::playable_words(self.dictionary, letters);
+        let words = playable.into_iter().map(|w| w.word);
+        match self.difficulty {
+            Difficulty::Random => words
+                .enumerate()
+                .filter(|(i, _)| mix(seed ^ *i as u64) % 3 == 0)
+                .map(|(_, word)| word)
+                .take(MAX_RUSH_WORDS)
+                .collect(),
+            Difficulty::Greedy => words.take(GREEDY_RUSH_WORDS).collect(),
+            Difficulty::Optimal => words.take(MAX_RUSH_WORDS).collect(),
+        }
+    }
+}
+
+/// Words a greedy bot plays in a rush round.
+const GREEDY_RUSH_WORDS: usize = 5;
+
+/// Spreads consecutive seeds over the whole range (SplitMix64 finaliser).
+fn mix(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,158 @@
+//! Challenges of the words an opponent scored with.
+//!
+//! Classic and rush rounds only check that a word can be made from the rack. Once a round is
+//! scored, either player may challenge the words the opponent scored with in it, until
+//! [`CHALLENGE_WINDOW_SECS`] have passed or the next round is scored; the last round of a
+//! match stays open after the match ends. Words missing from the round's word list lose what
+//! they added to the round, bonuses and handicap included; if none is missing, the challenger
+//! pays [`CHALLENGE_PENALTY`]. The outcome is kept in the challenged round's
+//! [`RoundRecord`](crate::RoundRecord).
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bonus::Play, dictionary::Dictionary, handicap::Handicap, solver::ScoredWord, Category,
+    RoundRecord,
+};
+
+/// Time after a round is scored during which its words may be challenged.
+pub const CHALLENGE_WINDOW_SECS: u64 = 30;
+
+/// Points a failed challenge costs the challenger.
+pub const CHALLENGE_PENALTY: u32 = 5;
+
+/// The outcome of one player's challenge of the opponent's words in a round.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, async_graphql::SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct Challenge {
+    /// Whether any challenged word was missing from the word list.
+    pub upheld: bool,
+    /// The challenged words missing from the word list, which lost their points.
+    pub struck_words: Vec<String>,
+    /// What the challenge took from the challenged player if it was upheld, or from the
+    /// challenger if not.
+    pub points: u32,
+}
+
+/// The word list a round's words are challenged against: its category's, if it had one.
+pub fn word_list(category: Option<Category>) -> &'static Dictionary {
+    match category {
+        Some(category) => Dictionary::category(category),
+        None => Dictionary::english(),
+    }
+}
+
+/// The words the host, or the guest, scored with in `record`; what a challenge of their side
+/// of the round looks up.
+pub fn challenged_words(record: &RoundRecord, host: bool) -> Vec<ScoredWord> {
+    let (word, points, blanks, words) = if host {
+        (
+            &record.host_word,
+            record.host_points,
+            &record.host_blanks,
+            &record.host_words,
+        )
+    } else {
+        (
+            &record.guest_word,
+            record.guest_points,
+            &record.guest_blanks,
+            &record.guest_words,
+        )
+    };
+    if !words.is_empty() {
+        return words.iter().filter(|w| w.points > 0).cloned().collect();
+    }
+    if points == 0 {
+        return Vec::new();
+    }
+    vec![ScoredWord {
+        word: word.clone(),
+        points,
+        blanks: blanks.clone(),
+    }]
+}
+
+/// Judges a challenge of the host's, or the guest's, words in `record`, whose rack is `rack`
+/// and handicap `handicap`. Nobody loses more than their score: `challenger_score` and
+/// `challenged_score` are the players' scores before the challenge.
+pub fn judge(
+    record: &RoundRecord,
+    host: bool,
+    rack: &str,
+    handicap: Handicap,
+    challenger_score: u32,
+    challenged_score: u32,
+) -> Challenge {
+    let dictionary = word_list(record.category);
+    let struck_words: Vec<String> = challenged_words(record, host)
+        .into_iter()
+        .filter(|w| !dictionary.contains(&w.word))
+        .map(|w| w.word)
+        .collect();
+    if struck_words.is_empty() {
+        return Challenge {
+            upheld: false,
+            struck_words,
+            points: CHALLENGE_PENALTY.min(challenger_score),
+        };
+    }
+    let points = struck_points(record, host, &struck_words, rack, handicap);
+    Challenge {
+        upheld: true,
+        struck_words,
+        points: points.min(challenged_score),
+    }
+}
+
+/// What `record` added to the host's, or the guest's, score less what it would have added
+/// without the `struck` words.
+fn struck_points(
+    record: &RoundRecord,
+    host: bool,
+    struck: &[String],
+    rack: &str,
+    handicap: Handicap,
+) -> u32 {
+    let (total, bonuses) = if host {
+        (record.host_total(), &record.host_bonuses)
+    } else {
+        (record.guest_total(), &record.guest_bonuses)
+    };
+    let kept: Vec<ScoredWord> = challenged_words(record, host)
+        .into_iter()
+        .filter(|w| !struck.contains(&w.word))
+        .collect();
+    let mine = play(record, host, &kept);
+    let theirs = play(record, !host, &challenged_words(record, !host));
+    // Fewer words never earn a bonus the full play did not, so only the earned ones are
+    // checked again.
+    let kept_bonuses: u32 = bonuses
+        .iter()
+        .filter(|b| b.kind.points(rack, &mine, &theirs) > 0)
+        .map(|b| b.points)
+        .sum();
+    let kept_points: u32 = kept.iter().map(|w| w.points).sum();
+    total.saturating_sub(handicap.apply(kept_points + kept_bonuses))
+}
+
+/// The host's, or the guest's, side of `record` as the bonus rules see it, with `words` as
+/// the words that scored.
+fn play(record: &RoundRecord, host: bool, words: &[ScoredWord]) -> Play {
+    let (elapsed_micros, streak) = if host {
+        (record.host_elapsed_micros, record.host_streak)
+    } else {
+        (record.guest_elapsed_micros, record.guest_streak)
+    };
+    Play {
+        valid_words: words.iter().map(|w| w.word.clone()).collect(),
+        elapsed_micros,
+        streak: if words.is_empty() { 0 } else { streak },
+    }
+}
+
+/// When the words of `record` can no longer be challenged, in microseconds since the epoch.
+pub fn closes_at(record: &RoundRecord) -> u64 {
+    let scored_at: u64 = record.timestamp.parse().unwrap_or_default();
+    scored_at.saturating_add(CHALLENGE_WINDOW_SECS * 1_000_000)
+}
@@ -0,0 +1,117 @@
+//! Chat between the two players of a match.
+//!
+//! Each chain keeps its own copy of a match's chat: a message is stored by the sender's chain
+//! when sent with `SendChat` or `SendReaction`, then by the opponent's chain when it arrives.
+//! A match holds at most [`MAX_CHAT_MESSAGES`], and a player may send at most
+//! [`CHAT_RATE_LIMIT`] of them in any [`CHAT_RATE_WINDOW_SECS`], as each chain's own clock
+//! measures them.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Longest chat message, in characters.
+pub const MAX_CHAT_LEN: usize = 200;
+
+/// Messages kept per match; once there are this many, the chat is closed.
+pub const MAX_CHAT_MESSAGES: usize = 200;
+
+/// Messages a player may send within [`CHAT_RATE_WINDOW_SECS`].
+pub const CHAT_RATE_LIMIT: usize = 5;
+
+/// Length of the window [`CHAT_RATE_LIMIT`] applies to.
+pub const CHAT_RATE_WINDOW_SECS: u64 = 10;
+
+/// The emoji a player may react with.
+pub const REACTIONS: [&str; 8] = ["👍", "👏", "😂", "😮", "😢", "😡", "🔥", "🤝"];
+
+/// A line of a match's chat, or a reaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, async_graphql::SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct ChatMessage {
+    pub sender_chain_id: String,
+    pub sender_name: String,
+    /// The message, or one of [`REACTIONS`] if `reaction` is set.
+    pub text: String,
+    pub reaction: bool,
+    /// When this chain stored it, in microseconds since the epoch: on sending for the sender,
+    /// on arrival for the opponent.
+    pub timestamp: u64,
+}
+
+/// Why a chat message cannot be sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatError {
+    Empty,
+    TooLong,
+    UnknownReaction,
+    TooFast,
+    Full,
+}
+
+impl fmt::Display for ChatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChatError::Empty => f.write_str("Message must not be empty"),
+            ChatError::TooLong => {
+                write!(f, "Message must be at most {MAX_CHAT_LEN} characters")
+            }
+            ChatError::UnknownReaction => {
+                write!(f, "Reaction must be one of {}", REACTIONS.join(" "))
+            }
+            ChatError::TooFast => write!(
+                f,
+                "At most {CHAT_RATE_LIMIT} messages every {CHAT_RATE_WINDOW_SECS} seconds"
+            ),
+            ChatError::Full => write!(
+                f,
+                "Chat is closed after {MAX_CHAT_MESSAGES} messages in a match"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChatError {}
+
+/// `text` trimmed, with line breaks and other control characters turned into spaces, or why
+/// it cannot be sent.
+pub fn normalized_text(text: &str) -> Result<String, ChatError> {
+    let text: String = text
+        .trim()
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    if text.is_empty() {
+        return Err(ChatError::Empty);
+    }
+    if text.chars().count() > MAX_CHAT_LEN {
+        return Err(ChatError::TooLong);
+    }
+    Ok(text)
+}
+
+/// `emoji` if it is one of [`REACTIONS`].
+pub fn reaction(emoji: &str) -> Result<String, ChatError> {
+    let emoji = emoji.trim();
+    if REACTIONS.contains(&emoji) {
+        Ok(emoji.to_string())
+    } else {
+        Err(ChatError::UnknownReaction)
+    }
+}
+
+/// Whether `sender` may add a message at `now` to a match whose chat so far is `messages`.
+pub fn check_send(messages: &[ChatMessage], sender: &str, now: u64) -> Result<(), ChatError> {
+    if messages.len() >= MAX_CHAT_MESSAGES {
+        return Err(ChatError::Full);
+    }
+    let window = CHAT_RATE_WINDOW_SECS * 1_000_000;
+    let recent = messages
+        .iter()
+        .filter(|m| m.sender_chain_id == sender && m.timestamp.saturating_add(window) > now)
+        .count();
+    if recent >= CHAT_RATE_LIMIT {
+        return Err(ChatError::TooFast);
+    }
+    Ok(())
+}
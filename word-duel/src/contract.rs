@@ -8,8 +8,14 @@ use linera_sdk::{
     Contract, ContractRuntime,
 };
 use word_duel::{
-    CrossChainMessage, Game, InstantiationArgument, MatchStatus, Operation, PlayerInfo,
-    RoundPhase, RoundRecord, WordDuelParameters, word_score,
+    chat::{self, ChatError, ChatMessage},
+    dictionary::Dictionary,
+    engine::{Effect, EngineError, Event, GameEngine},
+    handicap::{Handicap, HandicapChoice},
+    profile, rack, CrossChainMessage, Friend, Game, GameMode, InstantiationArgument, Invite,
+    JoinPolicy, LetterChoice, LetterSource, MatchRules, MatchStatus, Operation, PendingJoin,
+    PlayerInfo, RoomEntry, RoundPhase, TurnOrder, WordDuelParameters, INITIAL_RATING, MAX_ROUNDS,
+    MIN_ROUNDS,
 };
 
 use self::state::WordDuelState;
@@ -26,6 +32,14 @@ impl WithContractAbi for WordDuelContract {
 }
 
 impl WordDuelContract {
+    /// Sends a tracked message, so a rejection bounces back to this chain.
+    fn send(&mut self, destination: ChainId, message: CrossChainMessage) {
+        self.runtime
+            .prepare_message(message)
+            .with_tracking()
+            .send_to(destination);
+    }
+
     fn is_host(&mut self, game: &Game) -> bool {
         game.host_chain_id == self.runtime.chain_id().to_string()
     }
@@ -46,6 +60,534 @@ impl WordDuelContract {
     fn can_play(&self, game: &Game) -> bool {
         game.status == MatchStatus::Active && game.players.len() == 2
     }
+
+    /// Applies `event` to this chain's copy of `game` and carries out the resulting effects.
+    fn apply(&mut self, game: Game, event: Event) -> Result<(), EngineError> {
+        let chain_id = self.runtime.chain_id().to_string();
+        let now = self.runtime.system_time().micros();
+        let mut engine = GameEngine::new(game, chain_id, now);
+        let effects = engine.apply(event)?;
+        let is_host = engine.is_host();
+        let game = engine.into_game();
+        if is_host {
+            // The host's own word is part of its game; the guest's is never kept between rounds.
+            self.state.my_word.set(game.host_word.clone());
+            self.state.opponent_word.set(None);
+        } else {
+            self.state.opponent_word.set(game.host_word.clone());
+        }
+        self.state.game.set(Some(game.clone()));
+        for effect in effects {
+            match effect {
+                Effect::Send { to, message } => self.send(to, *message),
+                Effect::Notify(notification) => {
+                    self.state.last_notification.set(Some(notification))
+                }
+                Effect::MatchEnded => self.finish_match(&game),
+                Effect::ResultRevised => self.revise_match(&game),
+                Effect::RoomReopened => self.open_room(&game),
+            }
+        }
+        Ok(())
+    }
+
+    fn rating(&self) -> u32 {
+        self.state.rating.get().unwrap_or(INITIAL_RATING)
+    }
+
+    /// Archives an ended match and, if it is rated, updates this chain's rating from its result.
+    fn finish_match(&mut self, game: &Game) {
+        self.state.archive.push(game.clone());
+        if !game.rated() {
+            return;
+        }
+        let rating = self.rating();
+        self.state.rating_before_match.set(Some(rating));
+        self.rate_match(game, rating);
+    }
+
+    /// Archives `game` again after a challenge changed its scores and redoes the rating
+    /// update from the rating before the match.
+    fn revise_match(&mut self, game: &Game) {
+        self.state.archive.push(game.clone());
+        if !game.rated() {
+            return;
+        }
+        let rating = self
+            .state
+            .rating_before_match
+            .get()
+            .unwrap_or(INITIAL_RATING);
+        self.rate_match(game, rating);
+    }
+
+    /// Sets this chain's rating to `rating` updated with its result in `game`.
+    fn rate_match(&mut self, game: &Game, rating: u32) {
+        let self_chain = self.runtime.chain_id().to_string();
+        let Some(result) = game.result_for(&self_chain) else {
+            return;
+        };
+        let Some(opponent) = game.other_player(&self_chain) else {
+            return;
+        };
+        let rating = word_duel::updated_rating(rating, opponent.rating, result);
+        self.state.rating.set(Some(rating));
+    }
+
+    fn reject_join(&mut self, player_chain_id: ChainId, reason: &str) {
+        self.send(
+            player_chain_id,
+            CrossChainMessage::JoinRejected {
+                reason: reason.to_string(),
+            },
+        );
+    }
+
+    /// Rejects and forgets every join request still waiting for approval.
+    async fn reject_pending_joins(&mut self, reason: &str) {
+        let pending = self
+            .state
+            .pending_joins
+            .indices()
+            .await
+            .expect("Failed to read pending joins");
+        for chain_id in pending {
+            if let Ok(chain_id) = chain_id.parse() {
+                self.reject_join(chain_id, reason);
+            }
+        }
+        self.state.pending_joins.clear();
+    }
+
+    /// Seats `player` as the guest, starts the match and turns away everyone else.
+    async fn accept_join(&mut self, game: Game, player: PendingJoin) {
+        self.close_room();
+        let player = PlayerInfo {
+            chain_id: player.chain_id,
+            name: player.name,
+            rating: player.rating,
+            handicap: Handicap::default(),
+            profile: player.profile,
+        };
+        if let Err(error) = self.apply(game, Event::Join { player }) {
+            panic!("{error}");
+        }
+        self.reject_pending_joins("Match full").await;
+    }
+
+    /// Opens a new match hosted by this chain, replacing any other, and returns it.
+    async fn create_match(
+        &mut self,
+        host_name: &str,
+        join_policy: JoinPolicy,
+        rules: MatchRules,
+    ) -> Game {
+        let chain_id = self.runtime.chain_id().to_string();
+        let match_id = self.runtime.system_time().micros();
+        let MatchRules {
+            total_rounds,
+            turn_order,
+            mode,
+            letters: letter_choice,
+            categories,
+            bonuses,
+            handicaps,
+        } = rules;
+        let (letters, letter_source, letter_pack) = match mode {
+            GameMode::Chain => (String::new(), LetterSource::NoRack, None),
+            GameMode::Classic | GameMode::Rush => (
+                rack::letters_for(&letter_choice, match_id, Dictionary::english())
+                    .unwrap_or_else(|error| panic!("{error}")),
+                letter_choice.source(),
+                letter_choice.pack_name(),
+            ),
+        };
+        let rating = self.rating();
+        let host_profile = self.state.profile.get().clone();
+        let room_code = word_duel::room_code(&chain_id, &match_id.to_string());
+        self.close_room();
+        let game = Game {
+            match_id: match_id.to_string(),
+            host_chain_id: chain_id.clone(),
+            room_code,
+            status: MatchStatus::WaitingForPlayer,
+            players: vec![PlayerInfo {
+                chain_id: chain_id.clone(),
+                name: profile::player_name(host_name, host_profile.as_ref()),
+                rating,
+                handicap: match handicaps {
+                    HandicapChoice::Fixed { host, .. } => host.clamped(),
+                    HandicapChoice::Off | HandicapChoice::FromRating => Handicap::default(),
+                },
+                profile: host_profile,
+            }],
+            letters,
+            letter_source,
+            letter_pack,
+            mode,
+            turn_order: match mode {
+                GameMode::Rush => TurnOrder::Simultaneous,
+                GameMode::Chain => TurnOrder::Alternating,
+                GameMode::Classic => turn_order,
+            },
+            chain: Vec::new(),
+            categories: categories && mode == GameMode::Classic,
+            category: None,
+            bonuses,
+            handicap_mode: handicaps.mode(),
+            guest_handicap: match handicaps {
+                HandicapChoice::Fixed { guest, .. } => guest.clamped(),
+                HandicapChoice::Off | HandicapChoice::FromRating => Handicap::default(),
+            },
+            total_rounds,
+            current_round: 1,
+            host_score: 0,
+            guest_score: 0,
+            round_phase: RoundPhase::HostToPlay,
+            host_word: None,
+            guest_word: None,
+            host_seal: None,
+            guest_seal: None,
+            turn_deadline: 0,
+            turn_started: 0,
+            host_elapsed_micros: None,
+            guest_elapsed_micros: None,
+            winner_chain_id: None,
+            end_reason: None,
+            history: Vec::new(),
+            version: 0,
+        };
+        self.open_room(&game);
+        self.state.game.set(Some(game.clone()));
+        self.state.join_policy.set(join_policy);
+        self.reject_pending_joins("Match not joinable").await;
+        self.reset_round_words();
+        self.state.last_notification.set(None);
+        game
+    }
+
+    /// Sends `text` to the opponent in the current match's chat, keeping a copy here.
+    async fn send_chat(&mut self, text: String, reaction: bool) {
+        let game = self.state.game.get().clone().expect("Match not found");
+        let opponent = self
+            .opponent_chain_id(&game)
+            .expect("No opponent to chat with");
+        let sender_chain_id = self.runtime.chain_id().to_string();
+        let sender_name = game
+            .players
+            .iter()
+            .find(|p| p.chain_id == sender_chain_id)
+            .map(|p| p.name.clone())
+            .expect("Not a player in this match");
+        let message = ChatMessage {
+            sender_chain_id,
+            sender_name,
+            text,
+            reaction,
+            timestamp: self.runtime.system_time().micros(),
+        };
+        self.post_chat(&game.match_id, message.clone())
+            .await
+            .unwrap_or_else(|error| panic!("{error}"));
+        self.send(
+            opponent,
+            CrossChainMessage::ChatMessage {
+                match_id: game.match_id,
+                message,
+            },
+        );
+    }
+
+    /// Appends `message` to the chat of `match_id`, stamped with this chain's time, if the chat
+    /// limits allow it.
+    async fn post_chat(
+        &mut self,
+        match_id: &str,
+        mut message: ChatMessage,
+    ) -> Result<(), ChatError> {
+        // The sender's stamp can't be trusted to space its messages out.
+        message.timestamp = self.runtime.system_time().micros();
+        let log = self
+            .state
+            .chats
+            .load_entry_mut(match_id)
+            .await
+            .expect("Failed to load chat");
+        let messages = log.read(0..log.count()).await.expect("Failed to read chat");
+        chat::check_send(&messages, &message.sender_chain_id, message.timestamp)?;
+        log.push(message);
+        Ok(())
+    }
+
+    /// Removes and returns the invite from `chain_id`; panics if there is none.
+    async fn take_invite(&mut self, chain_id: &str) -> Invite {
+        let invite = self
+            .state
+            .invites
+            .get(chain_id)
+            .await
+            .expect("Failed to read invites")
+            .expect("Invite not found");
+        self.state
+            .invites
+            .remove(chain_id)
+            .expect("Failed to remove invite");
+        invite
+    }
+
+    /// Host only: takes the guest out of `match_id` and opens the room again, unless a round
+    /// was already played. Returns whether it did.
+    fn reopen_room(&mut self, match_id: &str) -> bool {
+        let Some(game) = self.state.game.get().clone() else {
+            return false;
+        };
+        let match_id = match_id.to_string();
+        self.apply(game, Event::JoinFailed { match_id }).is_ok()
+    }
+
+    /// Clears the accepted invite of `challenger`, whose match arrived or who turned the
+    /// acceptance down.
+    fn forget_accepted_invite(&mut self, challenger: ChainId) {
+        if *self.state.accepted_invite.get() == Some(challenger.to_string()) {
+            self.state.accepted_invite.set(None);
+        }
+    }
+
+    /// Whether this chain asked to join the host of `game`, directly or by its room code.
+    async fn asked_to_join(&self, game: &Game) -> bool {
+        for key in [&game.host_chain_id, &game.room_code] {
+            let asked = self
+                .state
+                .join_requests
+                .contains(key)
+                .await
+                .expect("Failed to read join requests");
+            if asked {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Sends the host's copy of the game to the guest so it can replace its own.
+    fn push_state_sync(&mut self, game: &Game) {
+        if let Some(opponent) = self.opponent_chain_id(game) {
+            let game = game.seen_by(&opponent.to_string());
+            self.send(opponent, CrossChainMessage::StateSync { game });
+        }
+    }
+
+    /// Host only: whether the message being executed comes from the guest of `game`.
+    fn sent_by_guest(&mut self, game: &Game) -> bool {
+        let origin = self
+            .runtime
+            .message_origin_chain_id()
+            .map(|c| c.to_string());
+        origin.is_some()
+            && game.other_player(&game.host_chain_id).map(|p| &p.chain_id) == origin.as_ref()
+    }
+
+    /// Guest only: whether the message being executed comes from the host of `game`.
+    fn sent_by_host(&mut self, game: &Game) -> bool {
+        let origin = self
+            .runtime
+            .message_origin_chain_id()
+            .map(|c| c.to_string());
+        origin.as_ref() == Some(&game.host_chain_id)
+    }
+
+    /// Host only: the current game, if the guest's seal or reveal for `round` from the guest's
+    /// chain may apply to it; a guest behind on the round gets the host's copy instead.
+    fn sealing_game(&mut self, round: u32) -> Option<Game> {
+        let game = self.state.game.get().clone()?;
+        if !self.is_host(&game) || !self.can_play(&game) {
+            return None;
+        }
+        if !self.sent_by_guest(&game) {
+            return None;
+        }
+        if game.current_round != round || game.round_phase != RoundPhase::BothToPlay {
+            // The guest played against an outdated copy of the game.
+            self.push_state_sync(&game);
+            return None;
+        }
+        Some(game)
+    }
+
+    /// Guest only: takes the host's copy of the game if it is newer than this chain's, or, if
+    /// `authoritative`, as new.
+    fn adopt_host_game(&mut self, game: Game, authoritative: bool) {
+        let Some(local) = self.state.game.get().clone() else {
+            return;
+        };
+        if !self.sent_by_host(&local) {
+            return; // only this match's host speaks for it
+        }
+        let event = Event::Sync {
+            game: Box::new(game),
+            authoritative,
+        };
+        if self.apply(local, event).is_ok() {
+            // Only set in a simultaneous round the host already has this chain's word for.
+            let guest_word = self
+                .state
+                .game
+                .get()
+                .as_ref()
+                .and_then(|g| g.guest_word.clone());
+            self.state.my_word.set(guest_word);
+        }
+    }
+
+    /// Host only: tells the guest why its move in `game` was refused.
+    fn reject_move(&mut self, game: &Game, word: Option<String>, error: EngineError) {
+        if let Some(guest) = self.opponent_chain_id(game) {
+            self.send(
+                guest,
+                CrossChainMessage::MoveRejected {
+                    round: game.current_round,
+                    word,
+                    reason: error.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Asks the host for its copy of the game after a gap was detected.
+    fn request_sync(&mut self, game: &Game) {
+        if let Some(host) = self.opponent_chain_id(game) {
+            self.send(
+                host,
+                CrossChainMessage::RequestSync {
+                    version: game.version,
+                },
+            );
+        }
+        self.state
+            .last_notification
+            .set(Some("Out of sync, requesting state".to_string()));
+    }
+
+    /// Applies `event` to the current match, if there is one; returns whether it applied.
+    fn apply_to_match(&mut self, event: Event) -> bool {
+        let Some(game) = self.state.game.get().clone() else {
+            return false;
+        };
+        self.apply(game, event).is_ok()
+    }
+
+    /// Undoes what a message sent from this chain assumed, after the receiver rejected it.
+    fn handle_bounce(&mut self, message: CrossChainMessage) {
+        let notification = match message {
+            CrossChainMessage::JoinRequest {
+                player_chain_id, ..
+            } if player_chain_id != self.runtime.chain_id() => {
+                // The lobby forwarded this request: the player is the one to tell.
+                self.reject_join(player_chain_id, "Join request could not be delivered");
+                return;
+            }
+            CrossChainMessage::JoinRequest { .. } | CrossChainMessage::JoinByCode { .. } => {
+                "Join request could not be delivered"
+            }
+            CrossChainMessage::RegisterRoom { .. } => "Room code could not be registered",
+            CrossChainMessage::Invite { .. } => "Invite could not be delivered",
+            CrossChainMessage::ChatMessage { .. } => "Chat message could not be delivered",
+            CrossChainMessage::InviteAccepted { .. } => {
+                self.state.accepted_invite.set(None);
+                "Challenger could not be reached"
+            }
+            CrossChainMessage::InviteDeclined { .. } => "Challenger could not be reached",
+            CrossChainMessage::InitialStateSync { game: sent } => {
+                // The guest never received the match.
+                if !self.reopen_room(&sent.match_id) {
+                    return;
+                }
+                "Player could not join"
+            }
+            CrossChainMessage::WordSubmitted { round, .. } => {
+                if self.apply_to_match(Event::SubmitBounced { round }) {
+                    self.state.my_word.set(None);
+                }
+                return;
+            }
+            CrossChainMessage::WordSealed { round, .. } => {
+                self.apply_to_match(Event::SealBounced { round });
+                return;
+            }
+            CrossChainMessage::WordRevealed { round, .. } => {
+                if self.apply_to_match(Event::RevealBounced { round }) {
+                    self.state.my_word.set(None);
+                }
+                return;
+            }
+            CrossChainMessage::RoundSync { .. }
+            | CrossChainMessage::StateSync { .. }
+            | CrossChainMessage::ClaimTimeout { .. }
+            | CrossChainMessage::Challenge { .. }
+            | CrossChainMessage::RequestSync { .. }
+            | CrossChainMessage::LeaveNotice { .. } => "Opponent unreachable",
+            CrossChainMessage::UnregisterRoom { .. }
+            | CrossChainMessage::RoomCodeTaken { .. }
+            | CrossChainMessage::MoveRejected { .. }
+            | CrossChainMessage::MatchDeclined { .. }
+            | CrossChainMessage::JoinRejected { .. }
+            | CrossChainMessage::Kicked => return,
+        };
+        self.state
+            .last_notification
+            .set(Some(notification.to_string()));
+    }
+
+    /// The current match; panics unless this chain hosts it.
+    fn hosted_game(&mut self) -> Game {
+        let game = self.state.game.get().clone().expect("Match not found");
+        if !self.is_host(&game) {
+            panic!("Only the host can manage players");
+        }
+        game
+    }
+
+    fn lobby_chain_id(&mut self) -> ChainId {
+        self.runtime.application_creator_chain_id()
+    }
+
+    /// Publishes the room code of a hosted match waiting for a player.
+    fn open_room(&mut self, game: &Game) {
+        let lobby = self.lobby_chain_id();
+        let host_chain_id = self.runtime.chain_id();
+        self.send(
+            lobby,
+            CrossChainMessage::RegisterRoom {
+                code: game.room_code.clone(),
+                host_chain_id,
+                match_id: game.match_id.clone(),
+            },
+        );
+    }
+
+    /// Withdraws the room code of the current match if it was still open.
+    fn close_room(&mut self) {
+        let Some(game) = self.state.game.get().clone() else {
+            return;
+        };
+        if !self.is_host(&game) || game.status != MatchStatus::WaitingForPlayer {
+            return;
+        }
+        let lobby = self.lobby_chain_id();
+        self.send(
+            lobby,
+            CrossChainMessage::UnregisterRoom {
+                code: game.room_code,
+            },
+        );
+    }
+}
+
+/// Panics unless a match can be played by `rules`.
+fn check_rules(rules: &MatchRules) {
+    if !(MIN_ROUNDS..=MAX_ROUNDS).contains(&rules.total_rounds) {
+        panic!("A match has {MIN_ROUNDS} to {MAX_ROUNDS} rounds");
+    }
 }
 
 impl Contract for WordDuelContract {
@@ -71,150 +613,530 @@ impl Contract for WordDuelContract {
         match operation {
             Operation::CreateMatch {
                 host_name,
-                total_rounds,
+                join_policy,
+                rules,
             } => {
-                let chain_id = self.runtime.chain_id().to_string();
-                let match_id = self.runtime.system_time().micros();
-                let letters = word_duel::letters_for_match(match_id);
-                let game = Game {
-                    match_id: match_id.to_string(),
-                    host_chain_id: chain_id.clone(),
-                    status: MatchStatus::WaitingForPlayer,
-                    players: vec![PlayerInfo {
-                        chain_id: chain_id.clone(),
-                        name: host_name,
-                    }],
-                    letters,
-                    total_rounds,
-                    current_round: 1,
-                    host_score: 0,
-                    guest_score: 0,
-                    round_phase: RoundPhase::HostToPlay,
-                    host_word: None,
-                    guest_word: None,
-                    winner_chain_id: None,
-                    history: Vec::new(),
-                };
-                self.state.game.set(Some(game));
-                self.reset_round_words();
-                self.state.last_notification.set(None);
+                check_rules(&rules);
+                self.create_match(&host_name, join_policy, rules).await;
             }
 
             Operation::JoinMatch {
                 host_chain_id,
                 player_name,
+                invite_secret,
             } => {
                 let target_chain: ChainId = host_chain_id.parse().expect("Invalid host chain ID");
                 let player_chain_id = self.runtime.chain_id();
-                self.runtime.send_message(
+                let player_rating = self.rating();
+                let player_profile = self.state.profile.get().clone();
+                let player_name = profile::player_name(&player_name, player_profile.as_ref());
+                self.state
+                    .join_requests
+                    .insert(&target_chain.to_string())
+                    .expect("Failed to store join request");
+                self.send(
                     target_chain,
                     CrossChainMessage::JoinRequest {
                         player_chain_id,
                         player_name,
+                        player_rating,
+                        player_profile,
+                        invite_secret,
+                    },
+                );
+            }
+
+            Operation::JoinByCode {
+                code,
+                player_name,
+                invite_secret,
+            } => {
+                let lobby = self.lobby_chain_id();
+                let player_chain_id = self.runtime.chain_id();
+                let player_rating = self.rating();
+                let player_profile = self.state.profile.get().clone();
+                let player_name = profile::player_name(&player_name, player_profile.as_ref());
+                let code = word_duel::normalize_room_code(&code);
+                self.state
+                    .join_requests
+                    .insert(&code)
+                    .expect("Failed to store join request");
+                self.send(
+                    lobby,
+                    CrossChainMessage::JoinByCode {
+                        code,
+                        player_chain_id,
+                        player_name,
+                        player_rating,
+                        player_profile,
+                        invite_secret,
+                    },
+                );
+            }
+
+            Operation::SetProfile { profile } => {
+                let profile = profile
+                    .normalized()
+                    .unwrap_or_else(|error| panic!("{error}"));
+                self.state.profile.set(Some(profile));
+            }
+
+            Operation::AddFriend { chain_id, name } => {
+                let _: ChainId = chain_id.parse().expect("Invalid chain ID");
+                if chain_id == self.runtime.chain_id().to_string() {
+                    panic!("Cannot add yourself");
+                }
+                let name = name.trim().to_string();
+                if name.is_empty() {
+                    panic!("Friend name required");
+                }
+                self.state
+                    .friends
+                    .insert(&chain_id.clone(), Friend { chain_id, name })
+                    .expect("Failed to store friend");
+            }
+
+            Operation::RemoveFriend { chain_id } => {
+                self.state
+                    .friends
+                    .remove(&chain_id)
+                    .expect("Failed to remove friend");
+            }
+
+            Operation::ChallengePlayer { chain_id, rules } => {
+                let invitee: ChainId = chain_id.parse().expect("Invalid chain ID");
+                if invitee == self.runtime.chain_id() {
+                    panic!("Cannot challenge yourself");
+                }
+                // Refuse the rules now rather than when the invitee accepts.
+                check_rules(&rules);
+                if let LetterChoice::Custom(_) = rules.letters {
+                    rack::letters_for(&rules.letters, 0, Dictionary::english())
+                        .unwrap_or_else(|error| panic!("{error}"));
+                }
+                let challenger_chain_id = self.runtime.chain_id();
+                let challenger_profile = self.state.profile.get().clone();
+                self.state
+                    .sent_invites
+                    .insert(&chain_id, rules.clone())
+                    .expect("Failed to store invite");
+                self.send(
+                    invitee,
+                    CrossChainMessage::Invite {
+                        challenger_chain_id,
+                        challenger_name: profile::player_name("", challenger_profile.as_ref()),
+                        challenger_rating: self.rating(),
+                        challenger_profile,
+                        rules,
+                    },
+                );
+            }
+
+            Operation::AcceptChallenge { chain_id } => {
+                if let Some(challenger) = self.state.accepted_invite.get() {
+                    panic!("Already accepted the challenge of {challenger}");
+                }
+                let invite = self.take_invite(&chain_id).await;
+                if self
+                    .state
+                    .game
+                    .get()
+                    .as_ref()
+                    .is_some_and(|game| game.status == MatchStatus::Active)
+                {
+                    panic!("Finish or leave the current match first");
+                }
+                let challenger: ChainId = invite.chain_id.parse().expect("Invalid chain ID");
+                self.state.accepted_invite.set(Some(invite.chain_id));
+                let player_chain_id = self.runtime.chain_id();
+                let player_profile = self.state.profile.get().clone();
+                self.send(
+                    challenger,
+                    CrossChainMessage::InviteAccepted {
+                        player_chain_id,
+                        player_name: profile::player_name("", player_profile.as_ref()),
+                        player_rating: self.rating(),
+                        player_profile,
+                    },
+                );
+            }
+
+            Operation::DeclineChallenge { chain_id } => {
+                let invite = self.take_invite(&chain_id).await;
+                let challenger: ChainId = invite.chain_id.parse().expect("Invalid chain ID");
+                let player_chain_id = self.runtime.chain_id();
+                let player_profile = self.state.profile.get().clone();
+                self.send(
+                    challenger,
+                    CrossChainMessage::InviteDeclined {
+                        player_chain_id,
+                        player_name: profile::player_name("", player_profile.as_ref()),
                     },
                 );
             }
 
             Operation::SubmitWord { word } => {
-                let mut game = self.state.game.get().clone().expect("Match not found");
-                if !self.can_play(&game) {
-                    panic!("Match not ready");
+                let game = self.state.game.get().clone().expect("Match not found");
+                // A guest's word is only in its game once the host answers; don't send another.
+                if !self.is_host(&game) && self.state.my_word.get().is_some() {
+                    panic!("Already submitted");
                 }
-                let word = word.trim().to_uppercase();
-                if word.len() < 3 {
-                    panic!("Word must be at least 3 letters");
+                let player_chain_id = self.runtime.chain_id().to_string();
+                let submission = game.mode.normalize_submission(&word);
+                let event = Event::Submit {
+                    player_chain_id,
+                    word,
+                };
+                if let Err(error) = self.apply(game, event) {
+                    panic!("{error}");
                 }
+                self.state.my_word.set(Some(submission));
+            }
 
-                if self.is_host(&game) {
-                    if game.round_phase != RoundPhase::HostToPlay {
-                        panic!("Not your turn");
-                    }
-                    if self.state.my_word.get().is_some() {
-                        panic!("Already submitted");
-                    }
-                    self.state.my_word.set(Some(word.clone()));
-                    game.host_word = Some(word.clone());
-                    game.round_phase = RoundPhase::GuestToPlay;
-                    self.state.game.set(Some(game.clone()));
-
-                    if let Some(opponent) = self.opponent_chain_id(&game) {
-                        self.runtime.send_message(
-                            opponent,
-                            CrossChainMessage::WordSubmitted {
-                                round: game.current_round,
-                                word,
-                            },
-                        );
-                    }
-                } else {
-                    if game.round_phase != RoundPhase::GuestToPlay {
-                        panic!("Not your turn");
-                    }
-                    if self.state.my_word.get().is_some() {
-                        panic!("Already submitted");
-                    }
-                    self.state.my_word.set(Some(word.clone()));
-                    if let Some(opponent) = self.opponent_chain_id(&game) {
-                        self.runtime.send_message(
-                            opponent,
-                            CrossChainMessage::WordSubmitted {
-                                round: game.current_round,
-                                word,
-                            },
-                        );
-                    }
+            Operation::SealWord { seal } => {
+                let game = self.state.game.get().clone().expect("Match not found");
+                let player_chain_id = self.runtime.chain_id().to_string();
+                let event = Event::Seal {
+                    player_chain_id,
+                    seal,
+                };
+                if let Err(error) = self.apply(game, event) {
+                    panic!("{error}");
+                }
+            }
+
+            Operation::RevealWord { word, salt } => {
+                let game = self.state.game.get().clone().expect("Match not found");
+                let is_host = self.is_host(&game);
+                let player_chain_id = self.runtime.chain_id().to_string();
+                let submission = game.mode.normalize_submission(&word);
+                let event = Event::Reveal {
+                    player_chain_id,
+                    word,
+                    salt,
+                };
+                if let Err(error) = self.apply(game, event) {
+                    panic!("{error}");
+                }
+                // The host's revealed word is part of its game already.
+                if !is_host {
+                    self.state.my_word.set(Some(submission));
                 }
             }
 
+            Operation::ClaimTimeout => {
+                let game = self.state.game.get().clone().expect("Match not found");
+                let claimant_chain_id = self.runtime.chain_id().to_string();
+                if let Err(error) = self.apply(game, Event::Timeout { claimant_chain_id }) {
+                    panic!("{error}");
+                }
+            }
+
+            Operation::Challenge => {
+                let game = self.state.game.get().clone().expect("Match not found");
+                let round = game.history.last().map_or(0, |record| record.round);
+                let challenger_chain_id = self.runtime.chain_id().to_string();
+                let event = Event::Challenge {
+                    challenger_chain_id,
+                    round,
+                };
+                if let Err(error) = self.apply(game, event) {
+                    panic!("{error}");
+                }
+            }
+
+            Operation::SendChat { text } => {
+                let text = chat::normalized_text(&text).unwrap_or_else(|error| panic!("{error}"));
+                self.send_chat(text, false).await;
+            }
+
+            Operation::SendReaction { emoji } => {
+                let emoji = chat::reaction(&emoji).unwrap_or_else(|error| panic!("{error}"));
+                self.send_chat(emoji, true).await;
+            }
+
             Operation::LeaveMatch => {
-                if let Some(game) = self.state.game.get().clone() {
-                    if let Some(opponent) = self.opponent_chain_id(&game) {
-                        let player_chain_id = self.runtime.chain_id();
-                        self.runtime.send_message(
-                            opponent,
-                            CrossChainMessage::LeaveNotice { player_chain_id },
-                        );
+                let game = self.state.game.get().clone();
+                match game {
+                    Some(game) if game.status == MatchStatus::Active => {
+                        // Leaving a running match is a forfeit; keep the result visible.
+                        let player_chain_id = self.runtime.chain_id().to_string();
+                        if let Err(error) = self.apply(game, Event::Leave { player_chain_id }) {
+                            panic!("{error}");
+                        }
+                    }
+                    _ => {
+                        // Waiting matches are cancelled and ended ones are already archived.
+                        self.close_room();
+                        self.state.game.set(None);
+                        self.state.last_notification.set(None);
                     }
                 }
-                self.state.game.set(None);
+                self.reject_pending_joins("Match not joinable").await;
+                self.state.join_policy.set(JoinPolicy::default());
                 self.reset_round_words();
-                self.state.last_notification.set(None);
+            }
+
+            Operation::RequestSync => {
+                let game = self.state.game.get().clone().expect("Match not found");
+                if self.is_host(&game) {
+                    self.push_state_sync(&game);
+                } else {
+                    self.request_sync(&game);
+                }
+            }
+
+            Operation::AcceptJoin { player_chain_id } => {
+                let game = self.hosted_game();
+                if game.status != MatchStatus::WaitingForPlayer {
+                    panic!("Match not joinable");
+                }
+                let player = self
+                    .state
+                    .pending_joins
+                    .get(&player_chain_id)
+                    .await
+                    .expect("Failed to read pending joins")
+                    .expect("No such join request");
+                self.state
+                    .pending_joins
+                    .remove(&player_chain_id)
+                    .expect("Failed to remove join request");
+                self.accept_join(game, player).await;
+            }
+
+            Operation::DeclineJoin { player_chain_id } => {
+                self.hosted_game();
+                if !self
+                    .state
+                    .pending_joins
+                    .contains_key(&player_chain_id)
+                    .await
+                    .expect("Failed to read pending joins")
+                {
+                    panic!("No such join request");
+                }
+                self.state
+                    .pending_joins
+                    .remove(&player_chain_id)
+                    .expect("Failed to remove join request");
+                let chain_id = player_chain_id.parse().expect("Invalid player chain ID");
+                self.reject_join(chain_id, "Declined by host");
+            }
+
+            Operation::KickPlayer => {
+                let game = self.hosted_game();
+                if let Err(error) = self.apply(game, Event::Kick) {
+                    panic!("{error}");
+                }
             }
         }
     }
 
     async fn execute_message(&mut self, message: Self::Message) {
+        if self.runtime.message_is_bouncing() == Some(true) {
+            self.handle_bounce(message);
+            return;
+        }
         match message {
             CrossChainMessage::JoinRequest {
                 player_chain_id,
                 player_name,
+                player_rating,
+                player_profile,
+                invite_secret,
             } => {
-                let mut game = self.state.game.get().clone().expect("Match not found");
-                if !self.is_host(&game) {
-                    panic!("Only host can accept joins");
-                }
-                if game.status != MatchStatus::WaitingForPlayer {
-                    panic!("Match not joinable");
+                // Players ask for themselves, or through the lobby by room code.
+                let origin = self.runtime.message_origin_chain_id();
+                let lobby = self.lobby_chain_id();
+                if origin != Some(player_chain_id) && origin != Some(lobby) {
+                    return;
                 }
-                if game.players.len() >= 2 {
-                    panic!("Match full");
+                let game = match self.state.game.get().clone() {
+                    Some(game) if self.is_host(&game) => game,
+                    _ => {
+                        self.reject_join(player_chain_id, "Match not found");
+                        return;
+                    }
+                };
+                if game.status != MatchStatus::WaitingForPlayer || game.players.len() >= 2 {
+                    self.reject_join(player_chain_id, "Match full");
+                    return;
                 }
-
-                game.players.push(PlayerInfo {
+                let player = PendingJoin {
                     chain_id: player_chain_id.to_string(),
                     name: player_name,
-                });
-                game.status = MatchStatus::Active;
-                self.state.game.set(Some(game.clone()));
-                self.reset_round_words();
+                    rating: player_rating,
+                    profile: player_profile,
+                };
+                let policy = self.state.join_policy.get().clone();
+                if let Some(reason) =
+                    policy.rejection_reason(&player.chain_id, invite_secret.as_deref())
+                {
+                    self.reject_join(player_chain_id, &reason);
+                    return;
+                }
+
+                if policy.require_approval {
+                    self.state
+                        .last_notification
+                        .set(Some(format!("{} wants to join", player.name)));
+                    self.state
+                        .pending_joins
+                        .insert(&player_chain_id.to_string(), player)
+                        .expect("Failed to store join request");
+                } else {
+                    self.accept_join(game, player).await;
+                }
+            }
+
+            CrossChainMessage::RegisterRoom {
+                code,
+                host_chain_id,
+                match_id,
+            } => {
+                if self.runtime.message_origin_chain_id() != Some(host_chain_id) {
+                    return;
+                }
+                let taken = self
+                    .state
+                    .room_codes
+                    .get(&code)
+                    .await
+                    .expect("Failed to read room codes")
+                    .is_some_and(|entry| entry.host_chain_id != host_chain_id.to_string());
+                if taken {
+                    self.send(
+                        host_chain_id,
+                        CrossChainMessage::RoomCodeTaken { code, match_id },
+                    );
+                    return;
+                }
+                self.state
+                    .room_codes
+                    .insert(
+                        &code,
+                        RoomEntry {
+                            host_chain_id: host_chain_id.to_string(),
+                            match_id,
+                        },
+                    )
+                    .expect("Failed to store room code");
+            }
+
+            CrossChainMessage::UnregisterRoom { code } => {
+                let origin = self
+                    .runtime
+                    .message_origin_chain_id()
+                    .map(|c| c.to_string());
+                let entry = self
+                    .state
+                    .room_codes
+                    .get(&code)
+                    .await
+                    .expect("Failed to read room codes");
+                if entry.is_some_and(|entry| Some(entry.host_chain_id) == origin) {
+                    self.state
+                        .room_codes
+                        .remove(&code)
+                        .expect("Failed to remove room code");
+                }
+            }
+
+            CrossChainMessage::RoomCodeTaken { code, match_id } => {
+                let lobby = self.lobby_chain_id();
+                if self.runtime.message_origin_chain_id() != Some(lobby) {
+                    return;
+                }
+                let Some(mut game) = self.state.game.get().clone() else {
+                    return;
+                };
+                if !self.is_host(&game)
+                    || game.match_id != match_id
+                    || game.room_code != code
+                    || game.status != MatchStatus::WaitingForPlayer
+                {
+                    return;
+                }
+                game.room_code =
+                    word_duel::salted_room_code(&game.host_chain_id, &game.match_id, &code);
+                game.version += 1;
+                self.open_room(&game);
+                self.state.last_notification.set(Some(format!(
+                    "Room code {code} was taken, the room is now {}",
+                    game.room_code
+                )));
+                self.state.game.set(Some(game));
+            }
+
+            CrossChainMessage::JoinByCode {
+                code,
+                player_chain_id,
+                player_name,
+                player_rating,
+                player_profile,
+                invite_secret,
+            } => {
+                let entry = self
+                    .state
+                    .room_codes
+                    .get(&code)
+                    .await
+                    .expect("Failed to read room codes");
+                let Some(host_chain_id) = entry.and_then(|e| e.host_chain_id.parse().ok()) else {
+                    self.reject_join(player_chain_id, "Unknown room code");
+                    return;
+                };
+                self.send(
+                    host_chain_id,
+                    CrossChainMessage::JoinRequest {
+                        player_chain_id,
+                        player_name,
+                        player_rating,
+                        player_profile,
+                        invite_secret,
+                    },
+                );
+            }
+
+            CrossChainMessage::JoinRejected { reason } => {
+                if let Some(origin) = self.runtime.message_origin_chain_id() {
+                    self.forget_accepted_invite(origin);
+                    self.state
+                        .join_requests
+                        .remove(&origin.to_string())
+                        .expect("Failed to remove join request");
+                }
                 self.state
                     .last_notification
-                    .set(Some("Player joined".to_string()));
-                self.runtime
-                    .send_message(player_chain_id, CrossChainMessage::InitialStateSync { game });
+                    .set(Some(format!("Join rejected: {}", reason)));
             }
 
             CrossChainMessage::InitialStateSync { game } => {
+                let Some(origin) = self.runtime.message_origin_chain_id() else {
+                    return;
+                };
+                let invited = *self.state.accepted_invite.get() == Some(origin.to_string());
+                let requested = invited || self.asked_to_join(&game).await;
+                // An active match is never swapped for another host's.
+                let busy = self.state.game.get().as_ref().is_some_and(|local| {
+                    local.status == MatchStatus::Active && local.host_chain_id != game.host_chain_id
+                });
+                self.forget_accepted_invite(origin);
+                if !requested || busy || game.host_chain_id != origin.to_string() {
+                    self.send(
+                        origin,
+                        CrossChainMessage::MatchDeclined {
+                            match_id: game.match_id,
+                        },
+                    );
+                    return;
+                }
+                // A room this chain was still hosting gives way to the match it joined.
+                self.close_room();
+                self.reject_pending_joins("Match not joinable").await;
+                self.state.join_policy.set(JoinPolicy::default());
+                self.state.join_requests.clear();
                 self.state.game.set(Some(game));
                 self.reset_round_words();
                 self.state
@@ -222,103 +1144,324 @@ impl Contract for WordDuelContract {
                     .set(Some("Match ready".to_string()));
             }
 
-            CrossChainMessage::WordSubmitted { round, word } => {
-                let mut game = self.state.game.get().clone().expect("Match not found");
-                if !self.can_play(&game) {
+            CrossChainMessage::WordSubmitted {
+                round,
+                word,
+                version,
+            } => {
+                let Some(game) = self.state.game.get().clone() else {
                     return;
-                }
-                if game.current_round != round {
+                };
+                if !self.can_play(&game) {
                     return;
                 }
 
                 if self.is_host(&game) {
                     // Host receives guest's word; resolve the round
-                    if game.guest_word.is_some() {
+                    if !self.sent_by_guest(&game) || game.guest_word.is_some() {
                         return;
                     }
-                    game.guest_word = Some(word.clone());
-                    self.state.opponent_word.set(Some(word.clone()));
-
-                    let host_word = self
-                        .state
-                        .my_word
-                        .get()
-                        .clone()
-                        .unwrap_or_default();
-                    let host_points = word_score(&game.letters, &host_word);
-                    let guest_points = word_score(&game.letters, &word);
-
-                    game.host_score = game.host_score.saturating_add(host_points);
-                    game.guest_score = game.guest_score.saturating_add(guest_points);
-
-                    let timestamp = self.runtime.system_time().micros().to_string();
-                    game.history.push(RoundRecord {
-                        round: game.current_round,
-                        host_word: host_word.clone(),
-                        guest_word: word,
-                        host_points,
-                        guest_points,
-                        host_score: game.host_score,
-                        guest_score: game.guest_score,
-                        timestamp: timestamp.clone(),
-                    });
-                    if game.history.len() > 50 {
-                        let excess = game.history.len() - 50;
-                        game.history.drain(0..excess);
-                    }
-
-                    game.current_round = game.current_round.saturating_add(1);
-                    game.host_word = None;
-                    game.guest_word = None;
-
-                    if game.current_round > game.total_rounds {
-                        game.status = MatchStatus::Ended;
-                        game.winner_chain_id = Some(if game.host_score > game.guest_score {
-                            game.host_chain_id.clone()
-                        } else if game.guest_score > game.host_score {
-                            game.players
-                                .iter()
-                                .find(|p| p.chain_id != game.host_chain_id)
-                                .map(|p| p.chain_id.clone())
-                                .unwrap_or_default()
-                        } else {
-                            String::new() // draw: no winner
-                        });
-                        game.round_phase = RoundPhase::RoundComplete;
-                    } else {
-                        game.round_phase = RoundPhase::HostToPlay;
+                    let phase = game.round_phase;
+                    if game.current_round != round
+                        || !matches!(phase, RoundPhase::GuestToPlay | RoundPhase::BothToPlay)
+                    {
+                        // The guest played against an outdated copy of the game.
+                        self.push_state_sync(&game);
+                        return;
                     }
-
-                    self.state.game.set(Some(game.clone()));
-                    self.reset_round_words();
-
-                    if let Some(opponent) = self.opponent_chain_id(&game) {
-                        self.runtime
-                            .send_message(opponent, CrossChainMessage::RoundSync { game });
+                    let Some(guest) = game.other_player(&game.host_chain_id) else {
+                        return;
+                    };
+                    let player_chain_id = guest.chain_id.clone();
+                    let event = Event::Submit {
+                        player_chain_id,
+                        word: word.clone(),
+                    };
+                    if let Err(error) = self.apply(game.clone(), event) {
+                        self.reject_move(&game, Some(word), error);
                     }
                 } else {
                     // Guest receives host's word; show it and wait for my submit
-                    if game.host_word.is_some() {
+                    if !self.sent_by_host(&game) {
                         return;
                     }
-                    game.host_word = Some(word.clone());
-                    game.round_phase = RoundPhase::GuestToPlay;
-                    self.state.opponent_word.set(Some(word));
-                    self.state.game.set(Some(game));
+                    let event = Event::HostWord {
+                        round,
+                        word,
+                        version,
+                    };
+                    let _ = self.apply(game, event);
                 }
             }
 
-            CrossChainMessage::RoundSync { game } => {
-                self.state.game.set(Some(game));
-                self.reset_round_words();
+            CrossChainMessage::WordSealed { round, seal } => {
+                let Some(game) = self.sealing_game(round) else {
+                    return;
+                };
+                let Some(guest) = game.other_player(&game.host_chain_id) else {
+                    return;
+                };
+                let player_chain_id = guest.chain_id.clone();
+                let event = Event::Seal {
+                    player_chain_id,
+                    seal,
+                };
+                if let Err(error) = self.apply(game.clone(), event) {
+                    self.reject_move(&game, None, error);
+                    // Replaces the guest's copy, which already holds the refused seal.
+                    self.push_state_sync(&game);
+                }
+            }
+
+            CrossChainMessage::WordRevealed { round, word, salt } => {
+                let Some(game) = self.sealing_game(round) else {
+                    return;
+                };
+                let Some(guest) = game.other_player(&game.host_chain_id) else {
+                    return;
+                };
+                let player_chain_id = guest.chain_id.clone();
+                let submission = game.mode.normalize_submission(&word);
+                let event = Event::Reveal {
+                    player_chain_id,
+                    word,
+                    salt,
+                };
+                if let Err(error) = self.apply(game.clone(), event) {
+                    self.reject_move(&game, Some(submission), error);
+                }
             }
 
-            CrossChainMessage::LeaveNotice { player_chain_id: _ } => {
+            CrossChainMessage::ClaimTimeout { round } => {
+                let Some(game) = self.state.game.get().clone() else {
+                    return;
+                };
+                if !self.is_host(&game) || !self.sent_by_guest(&game) || game.current_round != round
+                {
+                    return;
+                }
+                let Some(guest) = game.other_player(&game.host_chain_id) else {
+                    return;
+                };
+                let claimant_chain_id = guest.chain_id.clone();
+                if let Err(error) = self.apply(game.clone(), Event::Timeout { claimant_chain_id }) {
+                    self.reject_move(&game, None, error);
+                }
+            }
+
+            CrossChainMessage::Challenge { round } => {
+                let Some(game) = self.state.game.get().clone() else {
+                    return;
+                };
+                if !self.is_host(&game) || !self.sent_by_guest(&game) {
+                    return;
+                }
+                let Some(guest) = game.other_player(&game.host_chain_id) else {
+                    return;
+                };
+                let challenger_chain_id = guest.chain_id.clone();
+                let event = Event::Challenge {
+                    challenger_chain_id,
+                    round,
+                };
+                // Too late by the time it arrived, for example: the round stands.
+                if let Err(error) = self.apply(game.clone(), event) {
+                    self.reject_move(&game, None, error);
+                }
+            }
+
+            CrossChainMessage::Invite {
+                challenger_chain_id,
+                challenger_name,
+                challenger_rating,
+                challenger_profile,
+                rules,
+            } => {
+                // Only the challenger itself invites in its name.
+                if self.runtime.message_origin_chain_id() != Some(challenger_chain_id) {
+                    return;
+                }
+                self.state
+                    .last_notification
+                    .set(Some(format!("{challenger_name} challenges you to a match")));
+                let invite = Invite {
+                    chain_id: challenger_chain_id.to_string(),
+                    name: challenger_name,
+                    rating: challenger_rating,
+                    profile: challenger_profile,
+                    rules,
+                };
+                self.state
+                    .invites
+                    .insert(&invite.chain_id.clone(), invite)
+                    .expect("Failed to store invite");
+            }
+
+            CrossChainMessage::InviteAccepted {
+                player_chain_id,
+                player_name,
+                player_rating,
+                player_profile,
+            } => {
+                // Only the invitee itself accepts in its name.
+                if self.runtime.message_origin_chain_id() != Some(player_chain_id) {
+                    return;
+                }
+                let key = player_chain_id.to_string();
+                let rules = self
+                    .state
+                    .sent_invites
+                    .get(&key)
+                    .await
+                    .expect("Failed to read invites");
+                let Some(rules) = rules else {
+                    self.reject_join(player_chain_id, "Invite not found");
+                    return;
+                };
+                self.state
+                    .sent_invites
+                    .remove(&key)
+                    .expect("Failed to remove invite");
+                let busy = self
+                    .state
+                    .game
+                    .get()
+                    .as_ref()
+                    .is_some_and(|game| game.status == MatchStatus::Active);
+                if busy {
+                    self.reject_join(player_chain_id, "Challenger is playing another match");
+                    return;
+                }
+                let join_policy = JoinPolicy {
+                    allow_list: vec![key.clone()],
+                    ..JoinPolicy::default()
+                };
+                let game = self.create_match("", join_policy, rules).await;
+                let player = PendingJoin {
+                    chain_id: key,
+                    name: player_name,
+                    rating: player_rating,
+                    profile: player_profile,
+                };
+                self.accept_join(game, player).await;
+            }
+
+            CrossChainMessage::InviteDeclined {
+                player_chain_id,
+                player_name,
+            } => {
+                if self.runtime.message_origin_chain_id() != Some(player_chain_id) {
+                    return;
+                }
+                self.state
+                    .sent_invites
+                    .remove(&player_chain_id.to_string())
+                    .expect("Failed to remove invite");
+                self.state
+                    .last_notification
+                    .set(Some(format!("{player_name} declined your challenge")));
+            }
+
+            CrossChainMessage::MatchDeclined { match_id } => {
+                let Some(game) = self.state.game.get().clone() else {
+                    return;
+                };
+                let origin = self.runtime.message_origin_chain_id();
+                if !self.is_host(&game) || origin != self.opponent_chain_id(&game) {
+                    return;
+                }
+                if self.reopen_room(&match_id) {
+                    self.state
+                        .last_notification
+                        .set(Some("Player is in another match".to_string()));
+                }
+            }
+
+            CrossChainMessage::RoundSync { game } => self.adopt_host_game(game, false),
+
+            CrossChainMessage::StateSync { game } => self.adopt_host_game(game, true),
+
+            CrossChainMessage::RequestSync { version } => {
+                let Some(game) = self.state.game.get().clone() else {
+                    return;
+                };
+                if !self.is_host(&game) || version > game.version {
+                    return;
+                }
+                self.push_state_sync(&game);
+            }
+
+            CrossChainMessage::LeaveNotice { player_chain_id } => {
+                let Some(game) = self.state.game.get().clone() else {
+                    return;
+                };
+                // Only a player of this match may forfeit it, and only for itself.
+                if self.runtime.message_origin_chain_id() != Some(player_chain_id)
+                    || self.opponent_chain_id(&game) != Some(player_chain_id)
+                {
+                    return;
+                }
+                let player_chain_id = player_chain_id.to_string();
+                if self.apply(game, Event::Leave { player_chain_id }).is_ok() {
+                    self.reset_round_words();
+                }
+            }
+
+            CrossChainMessage::ChatMessage { match_id, message } => {
+                let origin = self
+                    .runtime
+                    .message_origin_chain_id()
+                    .map(|c| c.to_string());
+                let Some(game) = self.state.game.get().clone() else {
+                    return;
+                };
+                // Only the opponent in the current match may add to its chat.
+                let from_opponent = origin.as_ref() == Some(&message.sender_chain_id)
+                    && game
+                        .players
+                        .iter()
+                        .any(|p| p.chain_id == message.sender_chain_id);
+                if game.match_id != match_id || !from_opponent {
+                    return;
+                }
+                let _ = self.post_chat(&match_id, message).await;
+            }
+
+            CrossChainMessage::MoveRejected {
+                round,
+                word,
+                reason,
+            } => {
+                let Some(game) = self.state.game.get().clone() else {
+                    return;
+                };
+                if !self.sent_by_host(&game) {
+                    return;
+                }
+                if word.is_some()
+                    && round == game.current_round
+                    && *self.state.my_word.get() == word
+                {
+                    // The word never counted: let the player submit again.
+                    self.state.my_word.set(None);
+                }
+                self.state
+                    .last_notification
+                    .set(Some(format!("Move rejected: {reason}")));
+            }
+
+            CrossChainMessage::Kicked => {
+                let Some(game) = self.state.game.get().clone() else {
+                    return;
+                };
+                if !self.sent_by_host(&game) || !game.history.is_empty() {
+                    return;
+                }
                 self.state.game.set(None);
                 self.reset_round_words();
                 self.state
                     .last_notification
-                    .set(Some("Opponent left".to_string()));
+                    .set(Some("Removed by host".to_string()));
             }
         }
     }
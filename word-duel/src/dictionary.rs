@@ -0,0 +1,131 @@
+//! Word lists that words can be looked up in.
+
+use std::{borrow::Cow, cmp::Ordering, sync::OnceLock};
+
+use crate::{normalize_word, Category};
+
+/// The bundled English word list (see `data/README.md`), packed into sections by `build.rs`.
+const ENGLISH_SECTIONS: &[(usize, &str)] = include!(concat!(env!("OUT_DIR"), "/english.rs"));
+
+const ANIMAL_WORDS: &str = include_str!("../data/categories/animals.txt");
+const COUNTRY_WORDS: &str = include_str!("../data/categories/countries.txt");
+const FOOD_WORDS: &str = include_str!("../data/categories/food.txt");
+
+/// A set of upper-case words, grouped by length so that lookups need no parsing.
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    /// Shortest words first.
+    sections: Vec<Section>,
+}
+
+/// The words of one length, sorted and written one after the other.
+#[derive(Debug, Clone)]
+struct Section {
+    /// Length of each word in bytes.
+    len: usize,
+    words: Cow<'static, str>,
+}
+
+impl Section {
+    fn count(&self) -> usize {
+        self.words.len() / self.len
+    }
+
+    fn word(&self, index: usize) -> &str {
+        &self.words[index * self.len..(index + 1) * self.len]
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        let (mut low, mut high) = (0, self.count());
+        while low < high {
+            let middle = (low + high) / 2;
+            match self.word(middle).cmp(word) {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+}
+
+impl Dictionary {
+    /// Builds a dictionary from any list of words, in any case and order.
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut words: Vec<String> = words
+            .into_iter()
+            .map(|word| normalize_word(word.as_ref()))
+            .filter(|word| !word.is_empty())
+            .collect();
+        words.sort_unstable_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        words.dedup();
+        let mut sections: Vec<Section> = Vec::new();
+        for word in words {
+            match sections.last_mut() {
+                Some(section) if section.len == word.len() => {
+                    section.words.to_mut().push_str(&word)
+                }
+                _ => sections.push(Section {
+                    len: word.len(),
+                    words: Cow::Owned(word),
+                }),
+            }
+        }
+        Dictionary { sections }
+    }
+
+    /// The bundled English dictionary; it is stored ready to search, so nothing is parsed.
+    pub fn english() -> &'static Dictionary {
+        static ENGLISH: OnceLock<Dictionary> = OnceLock::new();
+        ENGLISH.get_or_init(|| Dictionary {
+            sections: ENGLISH_SECTIONS
+                .iter()
+                .map(|&(len, words)| Section {
+                    len,
+                    words: Cow::Borrowed(words),
+                })
+                .collect(),
+        })
+    }
+
+    /// The bundled word list of a themed round's category, parsed on first use.
+    pub fn category(category: Category) -> &'static Dictionary {
+        static ANIMALS: OnceLock<Dictionary> = OnceLock::new();
+        static COUNTRIES: OnceLock<Dictionary> = OnceLock::new();
+        static FOOD: OnceLock<Dictionary> = OnceLock::new();
+        let (cell, words) = match category {
+            Category::Animals => (&ANIMALS, ANIMAL_WORDS),
+            Category::Countries => (&COUNTRIES, COUNTRY_WORDS),
+            Category::Food => (&FOOD, FOOD_WORDS),
+        };
+        cell.get_or_init(|| Dictionary::new(words.lines()))
+    }
+
+    /// Whether `word` is in the dictionary, ignoring case and surrounding spaces.
+    pub fn contains(&self, word: &str) -> bool {
+        let word = normalize_word(word);
+        self.sections
+            .iter()
+            .find(|section| section.len == word.len())
+            .is_some_and(|section| section.contains(&word))
+    }
+
+    /// All words, shortest first and alphabetical among words of the same length.
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.sections
+            .iter()
+            .flat_map(|section| (0..section.count()).map(move |index| section.word(index)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.sections.iter().map(Section::count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+}
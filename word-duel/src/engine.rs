@@ -0,0 +1,915 @@
+//! The rules of a match as a pure state machine.
+//!
+//! A [`GameEngine`] wraps one chain's copy of a [`Game`] and applies [`Event`]s to it. It never
+//! touches storage or the runtime: the caller passes in the current time and carries out the
+//! returned [`Effect`]s. The host's copy is authoritative, so most rules only change the game
+//! when the engine runs on the host chain; on the guest chain they turn into messages asking
+//! the host to apply them.
+
+use std::fmt;
+
+use linera_sdk::linera_base_types::{ChainId, CryptoHash};
+
+use crate::{
+    bonus::{self, Play},
+    challenge,
+    handicap::{Handicap, HandicapMode},
+    seal, CrossChainMessage, EndReason, Game, GameMode, MatchStatus, PlayerInfo, RoundPhase,
+    RoundRecord, MAX_RUSH_WORDS, MIN_WORD_LEN,
+};
+
+/// Rounds kept in `Game::history`; older ones are dropped.
+pub const MAX_HISTORY: usize = 50;
+
+/// Something a player did.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// `player` takes the free seat of a waiting match.
+    Join { player: PlayerInfo },
+    /// `player_chain_id` plays `word` for the current round.
+    Submit {
+        player_chain_id: String,
+        word: String,
+    },
+    /// `player_chain_id` locks in its word for the current simultaneous round by its `seal`.
+    Seal {
+        player_chain_id: String,
+        seal: CryptoHash,
+    },
+    /// `player_chain_id` reveals the `word` and `salt` of its seal.
+    Reveal {
+        player_chain_id: String,
+        word: String,
+        salt: String,
+    },
+    /// `claimant_chain_id` ends the opponent's turn because its deadline has passed.
+    Timeout { claimant_chain_id: String },
+    /// `challenger_chain_id` challenges the opponent's words in `round`.
+    Challenge {
+        challenger_chain_id: String,
+        round: u32,
+    },
+    /// `player_chain_id` leaves an active match, forfeiting it.
+    Leave { player_chain_id: String },
+    /// The host removes the guest from a match before its first round was played.
+    Kick,
+    /// The guest never received the match `match_id` or turned it down.
+    JoinFailed { match_id: String },
+    /// The guest receives the host's `word` for `round`, played at the host's `version`.
+    HostWord {
+        round: u32,
+        word: String,
+        version: u64,
+    },
+    /// The guest receives the host's copy of the game; an `authoritative` one replaces the
+    /// guest's even at the same version.
+    Sync {
+        game: Box<Game>,
+        authoritative: bool,
+    },
+    /// This chain's word for `round` never reached the opponent.
+    SubmitBounced { round: u32 },
+    /// The guest's seal for `round` never reached the host.
+    SealBounced { round: u32 },
+    /// The guest's revealed word for `round` never reached the host.
+    RevealBounced { round: u32 },
+}
+
+/// What the caller has to do after an event was applied.
+#[derive(Debug, Clone)]
+pub enum Effect {
+    Send {
+        to: ChainId,
+        message: Box<CrossChainMessage>,
+    },
+    Notify(String),
+    /// The match just ended; archive it and update ratings.
+    MatchEnded,
+    /// A challenge of the last round changed the scores of the ended match; archive it again
+    /// and redo the rating update.
+    ResultRevised,
+    /// The host's match is waiting for a player again; publish its room code.
+    RoomReopened,
+}
+
+/// Why an event cannot be applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineError {
+    MatchNotReady,
+    MatchFull,
+    NotAPlayer,
+    NotYourTurn,
+    NotOpponentsTurn,
+    AlreadySubmitted,
+    SealFirst,
+    NotSealed,
+    SealMismatch,
+    TurnExpired,
+    WordTooShort,
+    NoWords,
+    TooManyWords,
+    TurnNotExpired,
+    NothingToChallenge,
+    ChallengeClosed,
+    AlreadyChallenged,
+    NoGuest,
+    RoundsPlayed,
+    OtherMatch,
+    Outdated,
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            EngineError::MatchNotReady => "Match not ready",
+            EngineError::MatchFull => "Match full",
+            EngineError::NotAPlayer => "Not a player in this match",
+            EngineError::NotYourTurn => "Not your turn",
+            EngineError::NotOpponentsTurn => "Not the opponent's turn",
+            EngineError::AlreadySubmitted => "Already submitted",
+            EngineError::SealFirst => "Simultaneous rounds take a sealed word",
+            EngineError::NotSealed => "Words cannot be revealed before both are sealed",
+            EngineError::SealMismatch => "Word and salt do not match the seal",
+            EngineError::TurnExpired => "Turn has timed out",
+            EngineError::WordTooShort => "Word must be at least 3 letters",
+            EngineError::NoWords => "Submit at least one word",
+            EngineError::TooManyWords => "Too many words",
+            EngineError::TurnNotExpired => "Turn has not timed out",
+            EngineError::NothingToChallenge => "No words to challenge",
+            EngineError::ChallengeClosed => "Too late to challenge",
+            EngineError::AlreadyChallenged => "Already challenged",
+            EngineError::NoGuest => "No player to kick",
+            EngineError::RoundsPlayed => "Cannot kick after a round was played",
+            EngineError::OtherMatch => "Not the current match",
+            EngineError::Outdated => "Outdated game state",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+/// One chain's copy of a game, together with who is looking at it and when.
+#[derive(Debug, Clone)]
+pub struct GameEngine {
+    game: Game,
+    chain_id: String,
+    now: u64,
+}
+
+impl GameEngine {
+    /// Runs the rules for `game` on `chain_id` at `now` (microseconds since the epoch).
+    pub fn new(game: Game, chain_id: impl Into<String>, now: u64) -> Self {
+        GameEngine {
+            game,
+            chain_id: chain_id.into(),
+            now,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn into_game(self) -> Game {
+        self.game
+    }
+
+    /// Whether the engine runs on the chain hosting the game.
+    pub fn is_host(&self) -> bool {
+        self.game.host_chain_id == self.chain_id
+    }
+
+    /// Applies `event`; on error the game is left unchanged.
+    pub fn apply(&mut self, event: Event) -> Result<Vec<Effect>, EngineError> {
+        match event {
+            Event::Join { player } => self.join(player),
+            Event::Submit {
+                player_chain_id,
+                word,
+            } => self.submit(&player_chain_id, &word),
+            Event::Seal {
+                player_chain_id,
+                seal,
+            } => self.seal(&player_chain_id, seal),
+            Event::Reveal {
+                player_chain_id,
+                word,
+                salt,
+            } => self.reveal(&player_chain_id, &word, &salt),
+            Event::Timeout { claimant_chain_id } => self.timeout(&claimant_chain_id),
+            Event::Challenge {
+                challenger_chain_id,
+                round,
+            } => self.challenge(&challenger_chain_id, round),
+            Event::Leave { player_chain_id } => self.leave(&player_chain_id),
+            Event::Kick => self.reopen(true),
+            Event::JoinFailed { match_id } => {
+                if match_id != self.game.match_id {
+                    return Err(EngineError::OtherMatch);
+                }
+                self.reopen(false)
+            }
+            Event::HostWord {
+                round,
+                word,
+                version,
+            } => self.host_word(round, word, version),
+            Event::Sync {
+                game,
+                authoritative,
+            } => self.sync(*game, authoritative),
+            Event::SubmitBounced { round } => self.submit_bounced(round),
+            Event::SealBounced { round } => self.seal_bounced(round),
+            Event::RevealBounced { round } => {
+                if self.game.current_round != round {
+                    return Err(EngineError::Outdated);
+                }
+                Ok(vec![Effect::Notify(
+                    "Word not delivered, reveal it again".to_string(),
+                )])
+            }
+        }
+    }
+
+    fn join(&mut self, mut player: PlayerInfo) -> Result<Vec<Effect>, EngineError> {
+        if !self.is_host() {
+            return Err(EngineError::NotAPlayer);
+        }
+        if self.game.status != MatchStatus::WaitingForPlayer || self.game.players.len() >= 2 {
+            return Err(EngineError::MatchFull);
+        }
+        let to = player
+            .chain_id
+            .parse()
+            .map_err(|_| EngineError::NotAPlayer)?;
+        player.handicap = match self.game.handicap_mode {
+            HandicapMode::Off => Handicap::default(),
+            HandicapMode::Fixed => self.game.guest_handicap,
+            HandicapMode::FromRating => {
+                let host_rating = self.game.players.first().map_or(0, |host| host.rating);
+                if let Some(host) = self.game.players.first_mut() {
+                    host.handicap = Handicap::from_ratings(host_rating, player.rating);
+                }
+                Handicap::from_ratings(player.rating, host_rating)
+            }
+        };
+        self.game.players.push(player);
+        self.game.status = MatchStatus::Active;
+        self.game.round_phase = self.game.opening_phase();
+        self.game.category = self.game.round_category();
+        self.game.version += 1;
+        self.start_turn();
+        Ok(vec![
+            Effect::Send {
+                to,
+                message: Box::new(CrossChainMessage::InitialStateSync {
+                    game: self.game.clone(),
+                }),
+            },
+            Effect::Notify("Player joined".to_string()),
+        ])
+    }
+
+    fn submit(&mut self, player_chain_id: &str, word: &str) -> Result<Vec<Effect>, EngineError> {
+        self.check_active()?;
+        let word = self.checked_word(word)?;
+        let by_host = self.mover(player_chain_id)?;
+        if self.game.round_phase == RoundPhase::BothToPlay {
+            return Err(EngineError::SealFirst);
+        }
+
+        if !self.is_host() {
+            // The guest's word only counts once the host has it.
+            return Ok(self.to_opponent(CrossChainMessage::WordSubmitted {
+                round: self.game.current_round,
+                word,
+                version: self.game.version,
+            }));
+        }
+        if by_host && self.game.host_word.is_some() || !by_host && self.game.guest_word.is_some() {
+            return Err(EngineError::AlreadySubmitted);
+        }
+        Ok(if by_host {
+            self.play_host_word(word)
+        } else {
+            self.resolve_round(word)
+        })
+    }
+
+    fn seal(
+        &mut self,
+        player_chain_id: &str,
+        seal: CryptoHash,
+    ) -> Result<Vec<Effect>, EngineError> {
+        self.check_active()?;
+        let by_host = self.mover(player_chain_id)?;
+        if self.game.round_phase != RoundPhase::BothToPlay {
+            return Err(EngineError::NotYourTurn);
+        }
+        // A word sealed in time may be revealed later; the rush window closes here.
+        self.check_deadline(by_host)?;
+        let own_seal = if by_host {
+            &mut self.game.host_seal
+        } else {
+            &mut self.game.guest_seal
+        };
+        if own_seal.is_some() {
+            return Err(EngineError::AlreadySubmitted);
+        }
+        *own_seal = Some(seal);
+
+        if !self.is_host() {
+            // Kept in the guest's copy until the host's next sync, so it is not sent twice.
+            return Ok(self.to_opponent(CrossChainMessage::WordSealed {
+                round: self.game.current_round,
+                seal,
+            }));
+        }
+        let elapsed = Some(self.elapsed());
+        if by_host {
+            self.game.host_elapsed_micros = elapsed;
+        } else {
+            self.game.guest_elapsed_micros = elapsed;
+        }
+        if self.game.host_seal.is_some() && self.game.guest_seal.is_some() {
+            // Both get a full turn to reveal.
+            self.start_turn();
+        }
+        self.game.version += 1;
+        let mut effects = self.sync_opponent();
+        if !by_host {
+            effects.push(Effect::Notify("Opponent sealed their word".to_string()));
+        }
+        Ok(effects)
+    }
+
+    fn reveal(
+        &mut self,
+        player_chain_id: &str,
+        word: &str,
+        salt: &str,
+    ) -> Result<Vec<Effect>, EngineError> {
+        self.check_active()?;
+        let by_host = self.mover(player_chain_id)?;
+        if self.game.round_phase != RoundPhase::BothToPlay {
+            return Err(EngineError::NotYourTurn);
+        }
+        let (own_seal, opponent_seal) = if by_host {
+            (self.game.host_seal, self.game.guest_seal)
+        } else {
+            (self.game.guest_seal, self.game.host_seal)
+        };
+        let own_seal = own_seal.ok_or(EngineError::NotSealed)?;
+        // Without the opponent's seal, only once it can no longer be sent.
+        if opponent_seal.is_none() && self.now <= self.game.deadline(!by_host) {
+            return Err(EngineError::NotSealed);
+        }
+        if seal::seal(word, salt) != own_seal {
+            return Err(EngineError::SealMismatch);
+        }
+        let normalized = self.checked_word(word)?;
+
+        if !self.is_host() {
+            return Ok(self.to_opponent(CrossChainMessage::WordRevealed {
+                round: self.game.current_round,
+                word: word.to_string(),
+                salt: salt.to_string(),
+            }));
+        }
+        if by_host && self.game.host_word.is_some() || !by_host && self.game.guest_word.is_some() {
+            return Err(EngineError::AlreadySubmitted);
+        }
+        let mut effects = self.reveal_word(by_host, normalized);
+        if by_host && opponent_seal.is_none() {
+            // The guest never sealed: the round is already scored.
+            effects.push(Effect::Notify("Opponent's turn timed out".to_string()));
+        } else if !by_host && opponent_seal.is_none() {
+            effects.push(Effect::Notify("Your turn timed out".to_string()));
+        }
+        Ok(effects)
+    }
+
+    fn timeout(&mut self, claimant_chain_id: &str) -> Result<Vec<Effect>, EngineError> {
+        self.check_active()?;
+        if !self.is_player(claimant_chain_id) {
+            return Err(EngineError::NotAPlayer);
+        }
+        let claimant_is_host = claimant_chain_id == self.game.host_chain_id;
+        if self.now < self.game.deadline(!claimant_is_host) {
+            return Err(EngineError::TurnNotExpired);
+        }
+        let phase = self.game.round_phase;
+        if phase == RoundPhase::BothToPlay {
+            // Only the host knows whether the guest's word is in; the guest always asks.
+            let opponent_word = if claimant_is_host {
+                &self.game.guest_word
+            } else {
+                &self.game.host_word
+            };
+            if self.is_host() && opponent_word.is_some() {
+                return Err(EngineError::NotOpponentsTurn);
+            }
+        } else {
+            let expected = if claimant_is_host {
+                RoundPhase::GuestToPlay
+            } else {
+                RoundPhase::HostToPlay
+            };
+            if phase != expected {
+                return Err(EngineError::NotOpponentsTurn);
+            }
+        }
+
+        if !self.is_host() {
+            return Ok(self.to_opponent(CrossChainMessage::ClaimTimeout {
+                round: self.game.current_round,
+            }));
+        }
+        if phase == RoundPhase::BothToPlay {
+            // Whatever word is still missing counts as empty.
+            let host_missed = self.game.host_word.is_none();
+            let guest_word = self.game.guest_word.take().unwrap_or_default();
+            let mut effects = self.resolve_round(guest_word);
+            if host_missed {
+                effects.push(Effect::Notify("Your turn timed out".to_string()));
+            }
+            Ok(effects)
+        } else if claimant_is_host {
+            Ok(self.resolve_round(String::new()))
+        } else {
+            // The host missed its turn: it plays an empty word and the guest moves on.
+            let mut effects = self.play_host_word(String::new());
+            effects.push(Effect::Notify("Your turn timed out".to_string()));
+            Ok(effects)
+        }
+    }
+
+    fn challenge(
+        &mut self,
+        challenger_chain_id: &str,
+        round: u32,
+    ) -> Result<Vec<Effect>, EngineError> {
+        // The last round of a completed match stays open to challenges once it has ended.
+        let completed = self.game.status == MatchStatus::Ended
+            && self.game.end_reason == Some(EndReason::Completed);
+        if !completed {
+            self.check_active()?;
+        }
+        if !self.is_player(challenger_chain_id) {
+            return Err(EngineError::NotAPlayer);
+        }
+        if self.game.mode == GameMode::Chain {
+            // Chain words are looked up when they are played.
+            return Err(EngineError::NothingToChallenge);
+        }
+        let by_host = challenger_chain_id == self.game.host_chain_id;
+        let now = self.now;
+        let record = self
+            .game
+            .history
+            .last()
+            .filter(|r| r.round == round && now <= challenge::closes_at(r))
+            .ok_or(EngineError::ChallengeClosed)?;
+        let own_challenge = if by_host {
+            &record.host_challenge
+        } else {
+            &record.guest_challenge
+        };
+        if own_challenge.is_some() {
+            return Err(EngineError::AlreadyChallenged);
+        }
+        if challenge::challenged_words(record, !by_host).is_empty() {
+            return Err(EngineError::NothingToChallenge);
+        }
+
+        if !self.is_host() {
+            return Ok(self.to_opponent(CrossChainMessage::Challenge { round }));
+        }
+        let (challenger_score, challenged_score) = if by_host {
+            (self.game.host_score, self.game.guest_score)
+        } else {
+            (self.game.guest_score, self.game.host_score)
+        };
+        let outcome = challenge::judge(
+            record,
+            !by_host,
+            &self.game.rack(!by_host),
+            self.game.handicap(!by_host),
+            challenger_score,
+            challenged_score,
+        );
+        let game = &mut self.game;
+        let notification = if outcome.upheld {
+            format!("Challenge upheld: {}", outcome.struck_words.join(", "))
+        } else {
+            "Challenge failed".to_string()
+        };
+        let host_loses = outcome.upheld != by_host;
+        if host_loses {
+            game.host_score -= outcome.points;
+        } else {
+            game.guest_score -= outcome.points;
+        }
+        let (host_score, guest_score) = (game.host_score, game.guest_score);
+        let record = game.history.last_mut().expect("checked above");
+        record.host_score = host_score;
+        record.guest_score = guest_score;
+        let revised = completed && outcome.points > 0;
+        if by_host {
+            record.host_challenge = Some(outcome);
+        } else {
+            record.guest_challenge = Some(outcome);
+        }
+        if revised {
+            game.winner_chain_id = Some(game.winner_by_score());
+        }
+        game.version += 1;
+
+        let mut effects = self.to_opponent(CrossChainMessage::RoundSync {
+            game: self.game.clone(),
+        });
+        if revised {
+            effects.push(Effect::ResultRevised);
+        }
+        effects.push(Effect::Notify(notification));
+        Ok(effects)
+    }
+
+    fn leave(&mut self, player_chain_id: &str) -> Result<Vec<Effect>, EngineError> {
+        self.check_active()?;
+        if !self.is_player(player_chain_id) {
+            return Err(EngineError::NotAPlayer);
+        }
+        self.game.forfeit(player_chain_id);
+        let mut effects = vec![Effect::MatchEnded];
+        if player_chain_id == self.chain_id {
+            if let Ok(player_chain_id) = player_chain_id.parse() {
+                effects
+                    .extend(self.to_opponent(CrossChainMessage::LeaveNotice { player_chain_id }));
+            }
+            effects.push(Effect::Notify("You forfeited".to_string()));
+        } else {
+            effects.push(Effect::Notify("Opponent forfeited".to_string()));
+        }
+        Ok(effects)
+    }
+
+    /// Host only: takes the guest out of a match no round was played in, telling it so if
+    /// `kicked`, and waits for a player again.
+    fn reopen(&mut self, kicked: bool) -> Result<Vec<Effect>, EngineError> {
+        if !self.is_host() {
+            return Err(EngineError::NotAPlayer);
+        }
+        if self.game.status != MatchStatus::Active || self.game.players.len() < 2 {
+            return Err(EngineError::NoGuest);
+        }
+        if !self.game.history.is_empty() {
+            return Err(EngineError::RoundsPlayed);
+        }
+        let mut effects = if kicked {
+            self.to_opponent(CrossChainMessage::Kicked)
+        } else {
+            Vec::new()
+        };
+        let game = &mut self.game;
+        let host_chain_id = game.host_chain_id.clone();
+        game.players.retain(|p| p.chain_id == host_chain_id);
+        game.status = MatchStatus::WaitingForPlayer;
+        game.round_phase = game.opening_phase();
+        game.host_word = None;
+        game.guest_word = None;
+        game.host_seal = None;
+        game.guest_seal = None;
+        game.host_elapsed_micros = None;
+        game.guest_elapsed_micros = None;
+        game.turn_deadline = 0;
+        game.turn_started = 0;
+        game.version += 1;
+        effects.push(Effect::RoomReopened);
+        Ok(effects)
+    }
+
+    /// Guest only: shows the host's word and hands the turn to this chain, unless the word is
+    /// one this chain already has. If something between this copy and the word was lost, asks
+    /// the host for its copy instead.
+    fn host_word(
+        &mut self,
+        round: u32,
+        word: String,
+        version: u64,
+    ) -> Result<Vec<Effect>, EngineError> {
+        if self.is_host() {
+            return Err(EngineError::NotAPlayer);
+        }
+        self.check_active()?;
+        if version <= self.game.version {
+            return Err(EngineError::Outdated); // stale or duplicate
+        }
+        if version > self.game.version + 1 || round != self.game.current_round {
+            let mut effects = self.to_opponent(CrossChainMessage::RequestSync {
+                version: self.game.version,
+            });
+            effects.push(Effect::Notify("Out of sync, requesting state".to_string()));
+            return Ok(effects);
+        }
+        self.game.host_word = Some(word);
+        self.game.round_phase = RoundPhase::GuestToPlay;
+        self.game.version = version;
+        self.start_turn();
+        Ok(Vec::new())
+    }
+
+    /// Guest only: takes the host's copy of the game if it is newer than this chain's, or, if
+    /// `authoritative`, as new.
+    fn sync(&mut self, mut game: Game, authoritative: bool) -> Result<Vec<Effect>, EngineError> {
+        if self.is_host() {
+            return Err(EngineError::NotAPlayer);
+        }
+        if game.host_chain_id != self.game.host_chain_id || game.match_id != self.game.match_id {
+            return Err(EngineError::OtherMatch);
+        }
+        let stale = if authoritative {
+            game.version < self.game.version
+        } else {
+            game.version <= self.game.version
+        };
+        if stale {
+            return Err(EngineError::Outdated);
+        }
+        if !authoritative
+            && game.current_round == self.game.current_round
+            && game.guest_seal.is_none()
+        {
+            // A seal still on its way to the host stays.
+            game.guest_seal = self.game.guest_seal;
+        }
+        let mut effects = Vec::new();
+        if game.status == MatchStatus::Ended && self.game.status != MatchStatus::Ended {
+            effects.push(Effect::MatchEnded);
+        } else if game.status == MatchStatus::Ended
+            && (game.host_score, game.guest_score) != (self.game.host_score, self.game.guest_score)
+        {
+            // A challenge of the last round changed the result.
+            effects.push(Effect::ResultRevised);
+        }
+        self.game = game;
+        Ok(effects)
+    }
+
+    /// Lets this chain play its word for `round` again; on the host, whose word already
+    /// counted, the turn goes back to it.
+    fn submit_bounced(&mut self, round: u32) -> Result<Vec<Effect>, EngineError> {
+        if self.game.status != MatchStatus::Active || self.game.current_round != round {
+            return Err(EngineError::Outdated);
+        }
+        if self.is_host() {
+            if self.game.round_phase != RoundPhase::GuestToPlay {
+                return Err(EngineError::Outdated);
+            }
+            self.game.host_word = None;
+            self.game.host_elapsed_micros = None;
+            self.game.round_phase = RoundPhase::HostToPlay;
+            self.game.version += 1;
+            self.start_turn();
+        }
+        Ok(vec![Effect::Notify(
+            "Word not delivered, submit again".to_string(),
+        )])
+    }
+
+    /// Guest only: drops the seal the host never got, so the word can be sealed again.
+    fn seal_bounced(&mut self, round: u32) -> Result<Vec<Effect>, EngineError> {
+        if self.is_host() {
+            return Err(EngineError::NotAPlayer);
+        }
+        if self.game.current_round != round || self.game.round_phase != RoundPhase::BothToPlay {
+            return Err(EngineError::Outdated);
+        }
+        self.game.guest_seal = None;
+        Ok(vec![Effect::Notify(
+            "Seal not delivered, seal your word again".to_string(),
+        )])
+    }
+
+    /// Host only: records the host's word and hands the turn to the guest.
+    fn play_host_word(&mut self, word: String) -> Vec<Effect> {
+        self.game.host_word = Some(word.clone());
+        self.game.host_elapsed_micros = Some(self.elapsed());
+        self.game.round_phase = RoundPhase::GuestToPlay;
+        self.game.version += 1;
+        self.start_turn();
+        self.to_opponent(CrossChainMessage::WordSubmitted {
+            round: self.game.current_round,
+            word,
+            version: self.game.version,
+        })
+    }
+
+    /// Host only, simultaneous rounds: keeps the first revealed word of the round and scores
+    /// the round once the second is in, or at once if the other player never sealed one.
+    fn reveal_word(&mut self, by_host: bool, word: String) -> Vec<Effect> {
+        let game = &mut self.game;
+        if by_host {
+            game.host_word = Some(word);
+            if game.guest_word.is_some() || game.guest_seal.is_none() {
+                let guest_word = game.guest_word.take().unwrap_or_default();
+                return self.resolve_round(guest_word);
+            }
+            game.version += 1;
+            Vec::new()
+        } else if game.host_word.is_some() || game.host_seal.is_none() {
+            self.resolve_round(word)
+        } else {
+            game.guest_word = Some(word);
+            game.version += 1;
+            vec![Effect::Notify("Opponent revealed their word".to_string())]
+        }
+    }
+
+    /// Host only: scores the round with the guest's `word` and moves on to the next round or
+    /// ends the match.
+    fn resolve_round(&mut self, word: String) -> Vec<Effect> {
+        // A word still missing is played now, so its time runs until now.
+        let elapsed = self.elapsed();
+        let game = &mut self.game;
+        let host_word = game.host_word.take().unwrap_or_default();
+        let host = game.score_submission(true, &host_word);
+        let guest = game.score_submission(false, &word);
+
+        let (host_streak, guest_streak) = game
+            .history
+            .last()
+            .map_or((0, 0), |r| (r.host_streak, r.guest_streak));
+        let host_play = Play::new(
+            game.mode,
+            &host_word,
+            &host,
+            game.host_elapsed_micros.take().unwrap_or(elapsed),
+            host_streak,
+        );
+        let guest_play = Play::new(
+            game.mode,
+            &word,
+            &guest,
+            game.guest_elapsed_micros.take().unwrap_or(elapsed),
+            guest_streak,
+        );
+        let (host_bonuses, guest_bonuses) = if game.bonuses {
+            (
+                bonus::bonuses(&game.rack(true), &host_play, &guest_play),
+                bonus::bonuses(&game.rack(false), &guest_play, &host_play),
+            )
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        let host_total = host.points + bonus::total(&host_bonuses);
+        let guest_total = guest.points + bonus::total(&guest_bonuses);
+        let host_handicapped = game.handicap(true).apply(host_total);
+        let guest_handicapped = game.handicap(false).apply(guest_total);
+
+        game.host_score = game.host_score.saturating_add(host_handicapped);
+        game.guest_score = game.guest_score.saturating_add(guest_handicapped);
+        game.history.push(RoundRecord {
+            round: game.current_round,
+            host_word,
+            guest_word: word,
+            host_points: host.points,
+            guest_points: guest.points,
+            host_score: game.host_score,
+            guest_score: game.guest_score,
+            timestamp: self.now.to_string(),
+            host_words: host.words,
+            guest_words: guest.words,
+            host_blanks: host.blanks,
+            guest_blanks: guest.blanks,
+            category: game.category,
+            host_verdict: host.verdict,
+            guest_verdict: guest.verdict,
+            host_elapsed_micros: host_play.elapsed_micros,
+            guest_elapsed_micros: guest_play.elapsed_micros,
+            host_streak: host_play.streak,
+            guest_streak: guest_play.streak,
+            host_bonuses,
+            guest_bonuses,
+            host_handicap_points: host_handicapped as i32 - host_total as i32,
+            guest_handicap_points: guest_handicapped as i32 - guest_total as i32,
+            host_challenge: None,
+            guest_challenge: None,
+        });
+        if game.history.len() > MAX_HISTORY {
+            let excess = game.history.len() - MAX_HISTORY;
+            game.history.drain(0..excess);
+        }
+
+        game.current_round = game.current_round.saturating_add(1);
+        game.guest_word = None;
+        game.host_seal = None;
+        game.guest_seal = None;
+        game.version += 1;
+
+        let mut effects = Vec::new();
+        if game.current_round > game.total_rounds {
+            game.status = MatchStatus::Ended;
+            game.end_reason = Some(EndReason::Completed);
+            game.winner_chain_id = Some(game.winner_by_score());
+            game.round_phase = RoundPhase::RoundComplete;
+            effects.push(Effect::MatchEnded);
+        } else {
+            game.round_phase = game.opening_phase();
+            game.category = game.round_category();
+            self.start_turn();
+        }
+        effects.extend(self.to_opponent(CrossChainMessage::RoundSync {
+            game: self.game.clone(),
+        }));
+        effects
+    }
+
+    /// Gives the player to move a fresh deadline.
+    fn start_turn(&mut self) {
+        self.game.start_turn(self.now);
+    }
+
+    /// Time since the current turn started.
+    fn elapsed(&self) -> u64 {
+        self.now.saturating_sub(self.game.turn_started)
+    }
+
+    /// `word` as the match plays it, if it is a valid submission.
+    fn checked_word(&self, word: &str) -> Result<String, EngineError> {
+        let word = self.game.mode.normalize_submission(word);
+        match self.game.mode {
+            GameMode::Classic | GameMode::Chain if word.chars().count() < MIN_WORD_LEN => {
+                Err(EngineError::WordTooShort)
+            }
+            GameMode::Rush if word.is_empty() => Err(EngineError::NoWords),
+            GameMode::Rush if word.split(' ').count() > MAX_RUSH_WORDS => {
+                Err(EngineError::TooManyWords)
+            }
+            _ => Ok(word),
+        }
+    }
+
+    /// Whether `player_chain_id` is the host, if it may play in the current phase.
+    fn mover(&self, player_chain_id: &str) -> Result<bool, EngineError> {
+        let by_host = player_chain_id == self.game.host_chain_id;
+        if !by_host && !self.is_player(player_chain_id) {
+            return Err(EngineError::NotAPlayer);
+        }
+        let own_phase = if by_host {
+            RoundPhase::HostToPlay
+        } else {
+            RoundPhase::GuestToPlay
+        };
+        let phase = self.game.round_phase;
+        if phase != own_phase && phase != RoundPhase::BothToPlay {
+            return Err(EngineError::NotYourTurn);
+        }
+        Ok(by_host)
+    }
+
+    /// Host only: refuses plays once the mover's deadline has passed on the host's clock.
+    fn check_deadline(&self, by_host: bool) -> Result<(), EngineError> {
+        if self.is_host() && self.now > self.game.deadline(by_host) {
+            Err(EngineError::TurnExpired)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_active(&self) -> Result<(), EngineError> {
+        if self.game.status == MatchStatus::Active && self.game.players.len() == 2 {
+            Ok(())
+        } else {
+            Err(EngineError::MatchNotReady)
+        }
+    }
+
+    fn is_player(&self, chain_id: &str) -> bool {
+        self.game.players.iter().any(|p| p.chain_id == chain_id)
+    }
+
+    /// The host's game, as the other player may see it.
+    fn sync_opponent(&self) -> Vec<Effect> {
+        let Some(opponent) = self.game.other_player(&self.chain_id) else {
+            return Vec::new();
+        };
+        self.to_opponent(CrossChainMessage::RoundSync {
+            game: self.game.seen_by(&opponent.chain_id),
+        })
+    }
+
+    /// A message to the other player, if it has a valid chain ID.
+    fn to_opponent(&self, message: CrossChainMessage) -> Vec<Effect> {
+        self.game
+            .other_player(&self.chain_id)
+            .and_then(|p| p.chain_id.parse().ok())
+            .map(|to| Effect::Send {
+                to,
+                message: Box::new(message),
+            })
+            .into_iter()
+            .collect()
+    }
+}
@@ -0,0 +1,137 @@
+//! Handicaps that even out matches between players of different strength.
+//!
+//! A [`Handicap`] belongs to one player and is kept in their [`PlayerInfo`](crate::PlayerInfo).
+//! The host either sets both players' handicaps when creating the match or has them derived
+//! from the players' ratings when the guest joins.
+
+use serde::{Deserialize, Serialize};
+
+/// Letters added to the rack of a player with extra letters, in this order.
+pub const EXTRA_LETTERS: &str = "ESA";
+
+/// Lowest score multiplier a handicap may have, in percent.
+pub const MIN_SCORE_PERCENT: u32 = 50;
+
+/// Highest score multiplier a handicap may have, in percent.
+pub const MAX_SCORE_PERCENT: u32 = 200;
+
+/// Most extra seconds a handicap may add to a player's turns.
+pub const MAX_EXTRA_SECS: u32 = 120;
+
+/// Rating difference that earns the weaker player one step of handicap.
+pub const RATING_STEP: u32 = 100;
+
+/// Most steps of handicap a rating difference can earn.
+pub const MAX_RATING_STEPS: u32 = 5;
+
+/// What one player gets to make up for the difference in skill.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    async_graphql::SimpleObject,
+    async_graphql::InputObject,
+)]
+#[graphql(input_name = "HandicapInput", rename_fields = "camelCase")]
+pub struct Handicap {
+    /// The player's round points are multiplied by this, in percent.
+    pub score_percent: u32,
+    /// How many of [`EXTRA_LETTERS`] are added to the player's rack.
+    pub extra_letters: u32,
+    /// Seconds added to each of the player's turns.
+    pub extra_secs: u32,
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Handicap {
+            score_percent: 100,
+            extra_letters: 0,
+            extra_secs: 0,
+        }
+    }
+}
+
+impl Handicap {
+    /// The handicap within the allowed limits.
+    pub fn clamped(self) -> Self {
+        Handicap {
+            score_percent: self
+                .score_percent
+                .clamp(MIN_SCORE_PERCENT, MAX_SCORE_PERCENT),
+            extra_letters: self.extra_letters.min(EXTRA_LETTERS.len() as u32),
+            extra_secs: self.extra_secs.min(MAX_EXTRA_SECS),
+        }
+    }
+
+    /// The handicap of a player rated `rating` against an opponent rated `opponent_rating`:
+    /// every [`RATING_STEP`] the player is behind adds 10% to their points, 15 seconds to their
+    /// turns and, every second step, one extra letter. The stronger player gets none.
+    pub fn from_ratings(rating: u32, opponent_rating: u32) -> Self {
+        let steps = (opponent_rating.saturating_sub(rating) / RATING_STEP).min(MAX_RATING_STEPS);
+        Handicap {
+            score_percent: 100 + 10 * steps,
+            extra_letters: steps / 2,
+            extra_secs: 15 * steps,
+        }
+        .clamped()
+    }
+
+    /// The player's rack: `letters` with the extra letters added. Matches without a rack stay
+    /// without one.
+    pub fn rack(&self, letters: &str) -> String {
+        if letters.is_empty() {
+            return String::new();
+        }
+        let extra = self.extra_letters.min(EXTRA_LETTERS.len() as u32) as usize;
+        format!("{letters}{}", &EXTRA_LETTERS[..extra])
+    }
+
+    /// `points` with the score multiplier applied, rounded down.
+    pub fn apply(&self, points: u32) -> u32 {
+        points.saturating_mul(self.score_percent) / 100
+    }
+}
+
+/// How the players' handicaps are chosen.
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum,
+)]
+pub enum HandicapMode {
+    /// Nobody has a handicap.
+    #[default]
+    #[serde(alias = "OFF")]
+    Off,
+    /// Derived from the players' ratings when the guest joins; see [`Handicap::from_ratings`].
+    #[serde(alias = "FROM_RATING")]
+    FromRating,
+    /// Set by the host when creating the match; such matches are unrated.
+    #[serde(alias = "FIXED")]
+    Fixed,
+}
+
+/// The host's pick of handicaps when creating a match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HandicapChoice {
+    #[default]
+    Off,
+    FromRating,
+    Fixed {
+        host: Handicap,
+        guest: Handicap,
+    },
+}
+
+impl HandicapChoice {
+    pub fn mode(&self) -> HandicapMode {
+        match self {
+            HandicapChoice::Off => HandicapMode::Off,
+            HandicapChoice::FromRating => HandicapMode::FromRating,
+            HandicapChoice::Fixed { .. } => HandicapMode::Fixed,
+        }
+    }
+}
@@ -1,7 +1,31 @@
 use async_graphql::{Request, Response};
-use linera_sdk::linera_base_types::{ChainId, ContractAbi, ServiceAbi};
+use linera_sdk::linera_base_types::{ChainId, ContractAbi, CryptoHash, ServiceAbi};
 use serde::{Deserialize, Serialize};
 
+use self::{
+    bonus::Bonus,
+    challenge::Challenge,
+    chat::ChatMessage,
+    dictionary::Dictionary,
+    handicap::{Handicap, HandicapChoice, HandicapMode},
+    profile::Profile,
+    solver::ScoredWord,
+};
+
+pub mod bonus;
+pub mod bot;
+pub mod challenge;
+pub mod chat;
+pub mod dictionary;
+pub mod engine;
+pub mod handicap;
+pub mod profile;
+pub mod rack;
+pub mod replay;
+pub mod seal;
+pub mod solver;
+pub mod stats;
+
 pub struct WordDuelAbi;
 
 impl ContractAbi for WordDuelAbi {
@@ -20,18 +44,322 @@ pub struct WordDuelParameters;
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct InstantiationArgument;
 
+// The enums below also deserialize from their GraphQL names, so service responses can be read
+// back into these types.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
 pub enum MatchStatus {
+    #[serde(alias = "WAITING_FOR_PLAYER")]
     WaitingForPlayer,
+    #[serde(alias = "ACTIVE")]
     Active,
+    #[serde(alias = "ENDED")]
     Ended,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
 pub enum RoundPhase {
+    #[serde(alias = "HOST_TO_PLAY")]
     HostToPlay,
+    #[serde(alias = "GUEST_TO_PLAY")]
     GuestToPlay,
+    #[serde(alias = "ROUND_COMPLETE")]
     RoundComplete,
+    /// Simultaneous rounds: both players seal a word, in either order, then reveal it; see
+    /// [`seal`].
+    #[serde(alias = "BOTH_TO_PLAY")]
+    BothToPlay,
+}
+
+/// Who may submit a word when.
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum,
+)]
+pub enum TurnOrder {
+    /// The host plays first each round and the guest sees its word before answering.
+    #[default]
+    #[serde(alias = "ALTERNATING")]
+    Alternating,
+    /// Both players play at once; each seals its word before either is revealed, so neither
+    /// sees the other's word before playing.
+    #[serde(alias = "SIMULTANEOUS")]
+    Simultaneous,
+}
+
+/// What a round asks of the players.
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum,
+)]
+pub enum GameMode {
+    /// One word per player and round.
+    #[default]
+    #[serde(alias = "CLASSIC")]
+    Classic,
+    /// Both players submit lists of distinct words at once, within [`RUSH_WINDOW_SECS`];
+    /// every valid word scores.
+    #[serde(alias = "RUSH")]
+    Rush,
+    /// No rack: each word must be a dictionary word starting with the last letter of the word
+    /// before it, and may not repeat an earlier one. Always played in turns.
+    #[serde(alias = "CHAIN")]
+    Chain,
+}
+
+impl GameMode {
+    /// How long a player has to play before the opponent may claim the turn.
+    pub fn turn_timeout_secs(self) -> u64 {
+        match self {
+            GameMode::Classic | GameMode::Chain => TURN_TIMEOUT_SECS,
+            GameMode::Rush => RUSH_WINDOW_SECS,
+        }
+    }
+
+    /// A submission in the form it is stored and sent in: one normalized word, or for rush
+    /// the distinct normalized words separated by single spaces.
+    pub fn normalize_submission(self, submission: &str) -> String {
+        match self {
+            GameMode::Classic | GameMode::Chain => normalize_word(submission),
+            GameMode::Rush => rush_words(submission).join(" "),
+        }
+    }
+
+    /// What a normalized submission scores on `letters` in a round of `category`, or in
+    /// word-chain matches after the words of `chain`.
+    pub fn score(
+        self,
+        letters: &str,
+        category: Option<Category>,
+        chain: &[String],
+        submission: &str,
+    ) -> SubmissionScore {
+        let score = match self {
+            GameMode::Classic => {
+                let off_category =
+                    category.is_some_and(|c| !Dictionary::category(c).contains(submission));
+                if off_category && validate_word(letters, submission) {
+                    return SubmissionScore {
+                        verdict: Verdict::OffCategory,
+                        ..SubmissionScore::default()
+                    };
+                }
+                SubmissionScore {
+                    points: word_score(letters, submission),
+                    blanks: blanks_played(letters, submission),
+                    ..SubmissionScore::default()
+                }
+            }
+            GameMode::Rush => {
+                let words: Vec<ScoredWord> = rush_words(submission)
+                    .into_iter()
+                    .map(|word| ScoredWord::new(letters, word))
+                    .collect();
+                SubmissionScore {
+                    points: words.iter().map(|w| w.points).sum(),
+                    words,
+                    ..SubmissionScore::default()
+                }
+            }
+            GameMode::Chain => SubmissionScore {
+                points: chain_word_score(Dictionary::english(), chain, submission),
+                ..SubmissionScore::default()
+            },
+        };
+        let verdict = match score.points {
+            _ if submission.is_empty() => Verdict::NoWord,
+            0 => Verdict::Invalid,
+            _ => Verdict::Valid,
+        };
+        SubmissionScore { verdict, ..score }
+    }
+}
+
+/// The theme of a category round: only words from its list score.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum Category {
+    #[serde(alias = "ANIMALS")]
+    Animals,
+    #[serde(alias = "COUNTRIES")]
+    Countries,
+    #[serde(alias = "FOOD")]
+    Food,
+}
+
+impl Category {
+    pub const ALL: [Category; 3] = [Category::Animals, Category::Countries, Category::Food];
+
+    /// The category of round `round` of match `match_id` on `letters`: one with at least one
+    /// word that can be made from the rack, or `None` if no category has one.
+    pub fn for_round(letters: &str, match_id: u64, round: u32) -> Option<Category> {
+        let start = (match_id.wrapping_add(round as u64) % Self::ALL.len() as u64) as usize;
+        (0..Self::ALL.len())
+            .map(|i| Self::ALL[(start + i) % Self::ALL.len()])
+            .find(|&category| {
+                Dictionary::category(category)
+                    .words()
+                    .any(|word| validate_word(letters, word))
+            })
+    }
+}
+
+/// How a submission was judged.
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum,
+)]
+pub enum Verdict {
+    /// The submission scored.
+    #[serde(alias = "VALID")]
+    Valid,
+    /// The submission breaks the rules of the mode: letters not on the rack, too short, not a
+    /// dictionary word or not following the chain.
+    #[serde(alias = "INVALID")]
+    Invalid,
+    /// A valid word, but not one of the round's category.
+    #[serde(alias = "OFF_CATEGORY")]
+    OffCategory,
+    /// Nothing was submitted, for example because the turn timed out.
+    #[default]
+    #[serde(alias = "NO_WORD")]
+    NoWord,
+}
+
+/// What one player's submission scored in a round.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubmissionScore {
+    pub points: u32,
+    pub verdict: Verdict,
+    /// Classic rounds: the letters the blank tiles stood for; see [`blank_letters`].
+    pub blanks: String,
+    /// Rush rounds: each word with its points.
+    pub words: Vec<ScoredWord>,
+}
+
+/// How the letters of a match were chosen.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum LetterSource {
+    /// Drawn from a named pack of racks; see [`Game::letter_pack`].
+    #[serde(alias = "PACK")]
+    Pack,
+    /// Typed in by the host.
+    #[serde(alias = "CUSTOM")]
+    Custom,
+    /// Word-chain matches are played without letters.
+    #[serde(alias = "NO_RACK")]
+    NoRack,
+}
+
+/// The host's pick of letters when creating a match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LetterChoice {
+    /// A rack from the pack with this name.
+    Pack(String),
+    /// This exact rack; it must pass [`rack::check_rack`].
+    Custom(String),
+}
+
+impl Default for LetterChoice {
+    fn default() -> Self {
+        LetterChoice::Pack(rack::DEFAULT_LETTER_PACK.to_string())
+    }
+}
+
+impl LetterChoice {
+    pub fn source(&self) -> LetterSource {
+        match self {
+            LetterChoice::Pack(_) => LetterSource::Pack,
+            LetterChoice::Custom(_) => LetterSource::Custom,
+        }
+    }
+
+    /// The name of the chosen pack as listed in [`rack::LETTER_PACKS`], if a known pack.
+    pub fn pack_name(&self) -> Option<String> {
+        match self {
+            LetterChoice::Pack(name) => rack::letter_pack(name).map(|pack| pack.name.to_string()),
+            LetterChoice::Custom(_) => None,
+        }
+    }
+}
+
+/// How a match is played, as chosen by the chain that hosts it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[graphql(complex, rename_fields = "camelCase")]
+pub struct MatchRules {
+    pub total_rounds: u32,
+    /// Rush matches are always played simultaneously and word-chain matches in turns.
+    pub turn_order: TurnOrder,
+    pub mode: GameMode,
+    /// Ignored for word-chain matches.
+    #[graphql(skip)]
+    pub letters: LetterChoice,
+    /// Play category rounds; classic matches only.
+    pub categories: bool,
+    /// Award bonuses on top of word points.
+    pub bonuses: bool,
+    #[graphql(skip)]
+    pub handicaps: HandicapChoice,
+}
+
+#[async_graphql::ComplexObject]
+impl MatchRules {
+    /// The rack the host chose, if it chose one.
+    async fn custom_letters(&self) -> Option<&str> {
+        match &self.letters {
+            LetterChoice::Custom(letters) => Some(letters),
+            LetterChoice::Pack(_) => None,
+        }
+    }
+
+    /// The pack the rack is drawn from, unless the host chose one.
+    async fn letter_pack(&self) -> Option<&str> {
+        match &self.letters {
+            LetterChoice::Pack(name) => Some(name),
+            LetterChoice::Custom(_) => None,
+        }
+    }
+
+    async fn handicap_mode(&self) -> HandicapMode {
+        self.handicaps.mode()
+    }
+}
+
+/// A chain this chain's player plays often, under the name they know it by.
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct Friend {
+    pub chain_id: String,
+    pub name: String,
+}
+
+/// An invite to a match from another chain, waiting for this chain's answer.
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct Invite {
+    /// The challenger, who will host the match.
+    pub chain_id: String,
+    pub name: String,
+    pub rating: u32,
+    pub profile: Option<Profile>,
+    pub rules: MatchRules,
+}
+
+/// How an `Ended` match came to an end.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum EndReason {
+    /// All rounds were played.
+    #[serde(alias = "COMPLETED")]
+    Completed,
+    /// A player left while the match was `Active`; the remaining player wins.
+    #[serde(alias = "FORFEIT")]
+    Forfeit,
+}
+
+/// Result of a finished match from one player's point of view.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum MatchResult {
+    #[serde(alias = "WIN")]
+    Win,
+    #[serde(alias = "LOSS")]
+    Loss,
+    #[serde(alias = "DRAW")]
+    Draw,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
@@ -45,6 +373,86 @@ pub struct RoundRecord {
     pub host_score: u32,
     pub guest_score: u32,
     pub timestamp: String,
+    /// Rush rounds only: each of the host's words with its points.
+    pub host_words: Vec<ScoredWord>,
+    /// Rush rounds only: each of the guest's words with its points.
+    pub guest_words: Vec<ScoredWord>,
+    /// Classic rounds: the letters blank tiles stood for in the host's word, in word order.
+    pub host_blanks: String,
+    /// Classic rounds: the letters blank tiles stood for in the guest's word, in word order.
+    pub guest_blanks: String,
+    /// The round's category, in matches with category rounds.
+    pub category: Option<Category>,
+    pub host_verdict: Verdict,
+    pub guest_verdict: Verdict,
+    /// How long the host took to play, in microseconds.
+    pub host_elapsed_micros: u64,
+    /// How long the guest took to play, in microseconds.
+    pub guest_elapsed_micros: u64,
+    /// Valid rounds in a row the host has played, this one included.
+    pub host_streak: u32,
+    /// Valid rounds in a row the guest has played, this one included.
+    pub guest_streak: u32,
+    /// Matches with bonuses: what the host earned on top of `host_points`.
+    pub host_bonuses: Vec<Bonus>,
+    /// Matches with bonuses: what the guest earned on top of `guest_points`.
+    pub guest_bonuses: Vec<Bonus>,
+    /// What the host's score multiplier added to, or took from, the round's points.
+    pub host_handicap_points: i32,
+    /// What the guest's score multiplier added to, or took from, the round's points.
+    pub guest_handicap_points: i32,
+    /// The host's challenge of the guest's words, if it made one.
+    pub host_challenge: Option<Challenge>,
+    /// The guest's challenge of the host's words, if it made one.
+    pub guest_challenge: Option<Challenge>,
+}
+
+impl RoundRecord {
+    /// Everything the round added to the host's score: word points, bonuses and handicap.
+    pub fn host_total(&self) -> u32 {
+        let total = self.host_points + bonus::total(&self.host_bonuses);
+        total.saturating_add_signed(self.host_handicap_points)
+    }
+
+    /// Everything the round added to the guest's score: word points, bonuses and handicap.
+    pub fn guest_total(&self) -> u32 {
+        let total = self.guest_points + bonus::total(&self.guest_bonuses);
+        total.saturating_add_signed(self.guest_handicap_points)
+    }
+
+    /// What challenges of the round took from the host's score; already deducted from
+    /// `host_score`.
+    pub fn host_challenge_loss(&self) -> u32 {
+        challenge_loss(&self.host_challenge, &self.guest_challenge)
+    }
+
+    /// What challenges of the round took from the guest's score; already deducted from
+    /// `guest_score`.
+    pub fn guest_challenge_loss(&self) -> u32 {
+        challenge_loss(&self.guest_challenge, &self.host_challenge)
+    }
+
+    /// Every word either player played this round.
+    pub fn played_words(&self) -> Vec<&str> {
+        if self.host_words.is_empty() && self.guest_words.is_empty() {
+            return vec![self.host_word.as_str(), self.guest_word.as_str()];
+        }
+        self.host_words
+            .iter()
+            .chain(&self.guest_words)
+            .map(|w| w.word.as_str())
+            .collect()
+    }
+}
+
+/// What a player lost to their own failed challenge and to an upheld challenge of their words.
+fn challenge_loss(own: &Option<Challenge>, opponents: &Option<Challenge>) -> u32 {
+    let failed = own.as_ref().filter(|c| !c.upheld).map_or(0, |c| c.points);
+    let struck = opponents
+        .as_ref()
+        .filter(|c| c.upheld)
+        .map_or(0, |c| c.points);
+    failed + struck
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
@@ -52,6 +460,57 @@ pub struct RoundRecord {
 pub struct PlayerInfo {
     pub chain_id: String,
     pub name: String,
+    /// Rating of the player when the match started.
+    pub rating: u32,
+    pub handicap: Handicap,
+    /// The player's profile when the match started, if they had set one.
+    pub profile: Option<Profile>,
+}
+
+/// Who may join a room; kept on the host chain and never synced to the guest.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JoinPolicy {
+    /// Chains allowed to join; empty means anyone.
+    pub allow_list: Vec<String>,
+    /// Secret a joiner must present, if set.
+    pub invite_secret: Option<String>,
+    /// Whether the host accepts each join request by hand.
+    pub require_approval: bool,
+}
+
+impl JoinPolicy {
+    /// Why `player_chain_id` may not join, or `None` if the policy lets it in.
+    pub fn rejection_reason(
+        &self,
+        player_chain_id: &str,
+        invite_secret: Option<&str>,
+    ) -> Option<String> {
+        if !self.allow_list.is_empty() && !self.allow_list.iter().any(|c| c == player_chain_id) {
+            return Some("Not invited".to_string());
+        }
+        if self.invite_secret.is_some() && self.invite_secret.as_deref() != invite_secret {
+            return Some("Wrong invite secret".to_string());
+        }
+        None
+    }
+}
+
+/// A join request waiting for the host's decision.
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct PendingJoin {
+    pub chain_id: String,
+    pub name: String,
+    pub rating: u32,
+    pub profile: Option<Profile>,
+}
+
+/// Where a room code leads; stored in the lobby registry.
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct RoomEntry {
+    pub host_chain_id: String,
+    pub match_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
@@ -59,9 +518,27 @@ pub struct PlayerInfo {
 pub struct Game {
     pub match_id: String,
     pub host_chain_id: String,
+    /// Short shareable code resolving to this match through the lobby registry.
+    pub room_code: String,
     pub status: MatchStatus,
     pub players: Vec<PlayerInfo>,
     pub letters: String,
+    pub letter_source: LetterSource,
+    /// The pack the letters were drawn from, when `letter_source` is `Pack`.
+    pub letter_pack: Option<String>,
+    pub mode: GameMode,
+    pub turn_order: TurnOrder,
+    /// Word-chain matches: the words accepted so far, oldest first.
+    pub chain: Vec<String>,
+    /// Whether rounds get a category when one fits the rack; see [`Category::for_round`].
+    pub categories: bool,
+    /// The category of the current round, if any.
+    pub category: Option<Category>,
+    /// Whether rounds award bonuses; see [`bonus`].
+    pub bonuses: bool,
+    pub handicap_mode: HandicapMode,
+    /// Fixed handicaps: what the guest gets on joining.
+    pub guest_handicap: Handicap,
     pub total_rounds: u32,
     pub current_round: u32,
     pub host_score: u32,
@@ -69,22 +546,261 @@ pub struct Game {
     pub round_phase: RoundPhase,
     pub host_word: Option<String>,
     pub guest_word: Option<String>,
+    /// Simultaneous rounds: the seal of the host's word, until the round is scored.
+    pub host_seal: Option<CryptoHash>,
+    /// Simultaneous rounds: the seal of the guest's word, until the round is scored.
+    pub guest_seal: Option<CryptoHash>,
+    /// When the player to move forfeits their turn, in microseconds since the epoch.
+    pub turn_deadline: u64,
+    /// When the current turn started, in microseconds since the epoch.
+    pub turn_started: u64,
+    /// How long the host took to play the word it has in this round, in microseconds.
+    pub host_elapsed_micros: Option<u64>,
+    /// How long the guest took to play the word it has in this round, in microseconds.
+    pub guest_elapsed_micros: Option<u64>,
     pub winner_chain_id: Option<String>,
+    pub end_reason: Option<EndReason>,
     pub history: Vec<RoundRecord>,
+    /// Bumped by the host on every change, so copies of the game can be compared.
+    pub version: u64,
+}
+
+impl Game {
+    /// The player on the other side of `chain_id`, once both have joined.
+    pub fn other_player(&self, chain_id: &str) -> Option<&PlayerInfo> {
+        self.players.iter().find(|p| p.chain_id != chain_id)
+    }
+
+    /// Result of an ended match for `chain_id`; `None` while the match is still running.
+    pub fn result_for(&self, chain_id: &str) -> Option<MatchResult> {
+        if self.status != MatchStatus::Ended {
+            return None;
+        }
+        match self.winner_chain_id.as_deref() {
+            None | Some("") => Some(MatchResult::Draw),
+            Some(winner) if winner == chain_id => Some(MatchResult::Win),
+            Some(_) => Some(MatchResult::Loss),
+        }
+    }
+
+    /// The winner of a completed match by its scores; empty for a draw.
+    #[allow(clippy::comparison_chain)]
+    pub fn winner_by_score(&self) -> String {
+        if self.host_score > self.guest_score {
+            self.host_chain_id.clone()
+        } else if self.guest_score > self.host_score {
+            self.other_player(&self.host_chain_id)
+                .map(|p| p.chain_id.clone())
+                .unwrap_or_default()
+        } else {
+            String::new() // draw: no winner
+        }
+    }
+
+    /// The phase every round of this match starts in.
+    pub fn opening_phase(&self) -> RoundPhase {
+        match self.turn_order {
+            TurnOrder::Alternating => RoundPhase::HostToPlay,
+            TurnOrder::Simultaneous => RoundPhase::BothToPlay,
+        }
+    }
+
+    /// The category of the current round, if this match plays category rounds.
+    pub fn round_category(&self) -> Option<Category> {
+        let match_id = self.match_id.parse().unwrap_or_default();
+        self.categories
+            .then(|| Category::for_round(&self.letters, match_id, self.current_round))
+            .flatten()
+    }
+
+    /// Starts the turn of the player to move at `now`, with the mode's time plus any extra
+    /// seconds of their handicap. In simultaneous rounds each player has their own deadline
+    /// (see [`Game::deadline`]) and `turn_deadline` is the later one.
+    pub fn start_turn(&mut self, now: u64) {
+        let (host, guest) = (self.handicap(true), self.handicap(false));
+        let extra_secs = match self.round_phase {
+            RoundPhase::HostToPlay => host.extra_secs,
+            RoundPhase::GuestToPlay => guest.extra_secs,
+            RoundPhase::BothToPlay => host.extra_secs.max(guest.extra_secs),
+            RoundPhase::RoundComplete => 0,
+        };
+        let timeout = self.mode.turn_timeout_secs() + extra_secs as u64;
+        self.turn_started = now;
+        self.turn_deadline = now.saturating_add(timeout * 1_000_000);
+    }
+
+    /// When the host's, or the guest's, time in the current turn runs out.
+    pub fn deadline(&self, host: bool) -> u64 {
+        if self.round_phase != RoundPhase::BothToPlay {
+            return self.turn_deadline;
+        }
+        let timeout = self.mode.turn_timeout_secs() + self.handicap(host).extra_secs as u64;
+        self.turn_started.saturating_add(timeout * 1_000_000)
+    }
+
+    /// Whether the match counts towards the players' ratings. Fixed handicaps are the host's
+    /// pick, so matches with them are unrated.
+    pub fn rated(&self) -> bool {
+        self.handicap_mode != HandicapMode::Fixed
+    }
+
+    /// The chain the next word of a word-chain match must follow: while the guest is to play,
+    /// a valid host word is part of it already.
+    pub fn current_chain(&self) -> Vec<String> {
+        let mut chain = self.chain.clone();
+        if let Some(word) = self
+            .host_word
+            .as_ref()
+            .filter(|_| self.round_phase == RoundPhase::GuestToPlay)
+            .filter(|word| chain_word_score(Dictionary::english(), &chain, word) > 0)
+        {
+            chain.push(word.clone());
+        }
+        chain
+    }
+
+    /// The letter the next word of a word-chain match must start with; `None` before the
+    /// first word.
+    pub fn chain_letter(&self) -> Option<char> {
+        self.current_chain().last()?.chars().last()
+    }
+
+    /// The handicap of the host, or of the guest; none for a guest yet to join.
+    pub fn handicap(&self, host: bool) -> Handicap {
+        self.players
+            .iter()
+            .find(|p| (p.chain_id == self.host_chain_id) == host)
+            .map_or_else(Handicap::default, |p| p.handicap)
+    }
+
+    /// The rack the host, or the guest, plays on: the match's letters plus any extra letters of
+    /// their handicap.
+    pub fn rack(&self, host: bool) -> String {
+        self.handicap(host).rack(&self.letters)
+    }
+
+    /// Scores a normalized submission of the host, or of the guest, in this match, adding it to
+    /// the chain of a word-chain match if it is valid.
+    pub fn score_submission(&mut self, host: bool, submission: &str) -> SubmissionScore {
+        let score = self
+            .mode
+            .score(&self.rack(host), self.category, &self.chain, submission);
+        if self.mode == GameMode::Chain && score.points > 0 {
+            self.chain.push(submission.to_string());
+        }
+        score
+    }
+
+    /// Until when the last scored round may be challenged, also after a completed match ended;
+    /// `None` if no round can be.
+    pub fn challenge_deadline(&self) -> Option<u64> {
+        let open = match self.status {
+            MatchStatus::Active => true,
+            MatchStatus::Ended => self.end_reason == Some(EndReason::Completed),
+            MatchStatus::WaitingForPlayer => false,
+        };
+        if !open || self.mode == GameMode::Chain {
+            return None;
+        }
+        self.history.last().map(challenge::closes_at)
+    }
+
+    /// Simultaneous rounds: whether the host, or the guest, may reveal its sealed word at
+    /// `now`, because both words are sealed or the opponent's time to seal one is up.
+    pub fn can_reveal(&self, host: bool, now: u64) -> bool {
+        let (own_seal, opponent_seal) = if host {
+            (self.host_seal, self.guest_seal)
+        } else {
+            (self.guest_seal, self.host_seal)
+        };
+        self.status == MatchStatus::Active
+            && self.round_phase == RoundPhase::BothToPlay
+            && own_seal.is_some()
+            && (opponent_seal.is_some() || now > self.deadline(!host))
+    }
+
+    /// The game as `chain_id` may see it: in a simultaneous round the opponent's word stays
+    /// hidden until the round is scored.
+    pub fn seen_by(&self, chain_id: &str) -> Game {
+        let mut game = self.clone();
+        if game.round_phase == RoundPhase::BothToPlay {
+            if chain_id == game.host_chain_id {
+                game.guest_word = None;
+            } else {
+                game.host_word = None;
+            }
+        }
+        game
+    }
+
+    /// Ends an active match because `leaver_chain_id` left; the remaining player wins.
+    pub fn forfeit(&mut self, leaver_chain_id: &str) {
+        self.winner_chain_id = Some(
+            self.other_player(leaver_chain_id)
+                .map(|p| p.chain_id.clone())
+                .unwrap_or_default(),
+        );
+        self.status = MatchStatus::Ended;
+        self.end_reason = Some(EndReason::Forfeit);
+        self.round_phase = RoundPhase::RoundComplete;
+        self.host_word = None;
+        self.guest_word = None;
+        self.host_seal = None;
+        self.guest_seal = None;
+        self.version += 1;
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Operation {
     CreateMatch {
+        /// Empty for the display name of this chain's profile.
         host_name: String,
-        total_rounds: u32,
+        join_policy: JoinPolicy,
+        rules: MatchRules,
     },
     JoinMatch {
         host_chain_id: String,
+        /// Empty for the display name of this chain's profile.
         player_name: String,
+        invite_secret: Option<String>,
     },
+    JoinByCode {
+        code: String,
+        /// Empty for the display name of this chain's profile.
+        player_name: String,
+        invite_secret: Option<String>,
+    },
+    /// Sets the profile attached to this chain's player in every match it creates or joins.
+    SetProfile { profile: Profile },
+    AddFriend { chain_id: String, name: String },
+    RemoveFriend { chain_id: String },
+    /// Invites `chain_id` to a match under `rules`, hosted by this chain once accepted.
+    ChallengePlayer { chain_id: String, rules: MatchRules },
+    /// Accepts the invite from `chain_id`: it creates the match and seats this chain.
+    AcceptChallenge { chain_id: String },
+    DeclineChallenge { chain_id: String },
+    /// In rush matches `word` may hold several words separated by spaces. Simultaneous rounds
+    /// take `SealWord` and `RevealWord` instead.
     SubmitWord { word: String },
+    /// Simultaneous rounds: locks in a word by its seal; see [`seal`].
+    SealWord { seal: CryptoHash },
+    /// Simultaneous rounds, once both words are sealed: the word and salt of this chain's seal.
+    RevealWord { word: String, salt: String },
+    /// Ends the opponent's turn with no word once its deadline has passed.
+    ClaimTimeout,
+    /// Challenges the opponent's words in the last scored round; see [`challenge`].
+    Challenge,
+    /// Sends a line to the opponent in the current match's chat; see [`chat`].
+    SendChat { text: String },
+    /// Sends one of [`chat::REACTIONS`] to the opponent in the current match's chat.
+    SendReaction { emoji: String },
     LeaveMatch,
+    /// Re-synchronise this chain's copy of the game with the opponent's.
+    RequestSync,
+    AcceptJoin { player_chain_id: String },
+    DeclineJoin { player_chain_id: String },
+    KickPlayer,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,17 +808,202 @@ pub enum CrossChainMessage {
     JoinRequest {
         player_chain_id: ChainId,
         player_name: String,
+        player_rating: u32,
+        player_profile: Option<Profile>,
+        invite_secret: Option<String>,
+    },
+    JoinRejected { reason: String },
+    /// Host to lobby: make `code` resolve to this match.
+    RegisterRoom {
+        code: String,
+        host_chain_id: ChainId,
+        match_id: String,
+    },
+    /// Host to lobby: the room no longer takes players.
+    UnregisterRoom { code: String },
+    /// Lobby to host: `code` already belongs to another room; pick another one.
+    RoomCodeTaken { code: String, match_id: String },
+    /// Player to lobby: forwarded to the host as a `JoinRequest`.
+    JoinByCode {
+        code: String,
+        player_chain_id: ChainId,
+        player_name: String,
+        player_rating: u32,
+        player_profile: Option<Profile>,
+        invite_secret: Option<String>,
     },
     InitialStateSync { game: Game },
+    /// Guest to host: the guest is already playing another host's match and did not take the
+    /// `InitialStateSync` of `match_id`.
+    MatchDeclined { match_id: String },
     WordSubmitted {
         round: u32,
         word: String,
+        /// Version of the sender's game after the submission.
+        version: u64,
+    },
+    /// Guest to host: the seal of the guest's word in a simultaneous round.
+    WordSealed { round: u32, seal: CryptoHash },
+    /// Guest to host: the word and salt of the guest's seal.
+    WordRevealed {
+        round: u32,
+        word: String,
+        salt: String,
     },
     RoundSync { game: Game },
+    /// Guest to host: the host's turn in `round` is past its deadline.
+    ClaimTimeout { round: u32 },
+    /// Guest to host: the guest challenges the host's words in `round`.
+    Challenge { round: u32 },
+    /// Host to guest: the host's rules refused the guest's move in `round`; `word` is the
+    /// refused word, if the move was one.
+    MoveRejected {
+        round: u32,
+        word: Option<String>,
+        reason: String,
+    },
     LeaveNotice { player_chain_id: ChainId },
+    Kicked,
+    /// Guest to host: send me your copy of the game; I have `version`.
+    RequestSync { version: u64 },
+    /// Host to guest: the authoritative game, replacing the guest's copy.
+    StateSync { game: Game },
+    /// Challenger to invitee: an invite to a match under `rules`.
+    Invite {
+        challenger_chain_id: ChainId,
+        challenger_name: String,
+        challenger_rating: u32,
+        challenger_profile: Option<Profile>,
+        rules: MatchRules,
+    },
+    /// Invitee to challenger: create the match and seat me.
+    InviteAccepted {
+        player_chain_id: ChainId,
+        player_name: String,
+        player_rating: u32,
+        player_profile: Option<Profile>,
+    },
+    /// Invitee to challenger: the invite was turned down.
+    InviteDeclined {
+        player_chain_id: ChainId,
+        player_name: String,
+    },
+    /// Either player to the other: a line of the chat of `match_id`.
+    ChatMessage {
+        match_id: String,
+        message: ChatMessage,
+    },
+}
+
+/// Characters used in room codes; omits I, L, O, 0 and 1 to avoid misreading.
+const ROOM_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+/// Number of characters in a room code.
+pub const ROOM_CODE_LEN: usize = 6;
+
+/// Derives the room code of a match from its host chain and match id (FNV-1a hash).
+pub fn room_code(host_chain_id: &str, match_id: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in host_chain_id.bytes().chain(match_id.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    let base = ROOM_CODE_ALPHABET.len() as u64;
+    (0..ROOM_CODE_LEN)
+        .map(|_| {
+            let c = ROOM_CODE_ALPHABET[(hash % base) as usize] as char;
+            hash /= base;
+            c
+        })
+        .collect()
+}
+
+/// Another room code for a match whose code `taken` was already in use, salting the match id
+/// with it so every retry derives a fresh code.
+pub fn salted_room_code(host_chain_id: &str, match_id: &str, taken: &str) -> String {
+    room_code(host_chain_id, &format!("{match_id}:{taken}"))
+}
+
+/// Canonical form of a user-typed room code: upper case, without spaces or dashes.
+pub fn normalize_room_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Fewest letters a word needs to score.
+pub const MIN_WORD_LEN: usize = 3;
+
+/// Fewest rounds a match can have.
+pub const MIN_ROUNDS: u32 = 1;
+
+/// Most rounds a match can have.
+pub const MAX_ROUNDS: u32 = 20;
+
+/// Time each player has to submit a word before the opponent may claim the turn.
+pub const TURN_TIMEOUT_SECS: u64 = 60;
+
+/// Time both players have to submit their words in a rush round.
+pub const RUSH_WINDOW_SECS: u64 = 120;
+
+/// Most distinct words one player may submit in a rush round.
+pub const MAX_RUSH_WORDS: usize = 50;
+
+/// Rating given to a chain before it has finished any match.
+pub const INITIAL_RATING: u32 = 1200;
+
+/// Maximum rating change for a single match.
+const RATING_K_FACTOR: i64 = 32;
+
+/// Fixed-point scale of the Elo curve: `RATING_SCALE` stands for 1.
+const RATING_SCALE: u128 = 1_000_000_000_000;
+
+/// `10^(1/400)`, the odds gained per rating point, scaled by [`RATING_SCALE`].
+const RATING_STEP: u128 = 1_005_773_063_002;
+
+/// Rating gaps beyond this one count as this one; the expected score is then 99.99%.
+const MAX_RATING_GAP: u32 = 1600;
+
+/// A score of 1 in [`expected_score`] and [`updated_rating`].
+const SCORE_UNIT: i64 = 1_000_000;
+
+/// Expected score of a player rated `rating` against `opponent_rating`, in millionths.
+fn expected_score(rating: u32, opponent_rating: u32) -> i64 {
+    // The stronger player's odds are 10^(gap/400), computed by squaring.
+    let mut exponent = rating.abs_diff(opponent_rating).min(MAX_RATING_GAP);
+    let mut odds = RATING_SCALE;
+    let mut base = RATING_STEP;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            odds = odds * base / RATING_SCALE;
+        }
+        base = base * base / RATING_SCALE;
+        exponent >>= 1;
+    }
+    let stronger = (odds * SCORE_UNIT as u128 / (RATING_SCALE + odds)) as i64;
+    if rating >= opponent_rating {
+        stronger
+    } else {
+        SCORE_UNIT - stronger
+    }
+}
+
+/// Elo rating after a match against an opponent rated `opponent_rating`, in integer
+/// arithmetic so every chain computes the same rating.
+pub fn updated_rating(rating: u32, opponent_rating: u32, result: MatchResult) -> u32 {
+    let score = match result {
+        MatchResult::Win => SCORE_UNIT,
+        MatchResult::Draw => SCORE_UNIT / 2,
+        MatchResult::Loss => 0,
+    };
+    let change = RATING_K_FACTOR * (score - expected_score(rating, opponent_rating));
+    let next = rating as i64 * SCORE_UNIT + change;
+    (next + SCORE_UNIT / 2).div_euclid(SCORE_UNIT).max(0) as u32
 }
 
-/// Predefined letter sets for deterministic generation (no RNG in contract).
+/// Predefined letter sets for deterministic generation (no RNG in contract); the `classic`
+/// letter pack.
 const LETTER_SETS: &[&str] = &[
     "ATRESN",
     "EXAMPL",
@@ -120,36 +1021,94 @@ pub fn letters_for_match(match_id_micros: u64) -> String {
     LETTER_SETS[idx].to_string()
 }
 
-/// Validates that `word` uses only characters from `letters` with correct counts, and len >= 3.
-pub fn validate_word(letters: &str, word: &str) -> bool {
-    let word = word.trim().to_uppercase();
-    if word.len() < 3 {
-        return false;
+/// Trims and upper-cases a submitted word, the form it is validated, scored and stored in.
+pub fn normalize_word(word: &str) -> String {
+    word.trim().to_uppercase()
+}
+
+/// The distinct words of a rush submission, normalized, in the order given.
+pub fn rush_words(submission: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for word in submission.split_whitespace().map(normalize_word) {
+        if !words.contains(&word) {
+            words.push(word);
+        }
     }
+    words
+}
+
+/// A blank tile in a rack; it can stand for any letter.
+pub const BLANK: char = '?';
+
+/// The letters blank tiles stand for when `word` is made from `letters`, in word order, or
+/// `None` if it cannot be made. Rack letters are always used before blanks.
+pub fn blank_letters(letters: &str, word: &str) -> Option<String> {
+    let word = normalize_word(word);
     let letters_upper = letters.to_uppercase();
     let mut letter_counts: std::collections::HashMap<char, u32> = std::collections::HashMap::new();
+    let mut blanks = 0;
     for c in letters_upper.chars() {
         if c.is_alphabetic() {
             *letter_counts.entry(c).or_insert(0) += 1;
+        } else if c == BLANK {
+            blanks += 1;
         }
     }
+    let mut covered = String::new();
     for c in word.chars() {
         if !c.is_alphabetic() {
-            return false;
+            return None;
         }
-        let count = letter_counts.get(&c).copied().unwrap_or(0);
-        if count == 0 {
-            return false;
+        match letter_counts.get_mut(&c) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ if blanks > 0 => {
+                blanks -= 1;
+                covered.push(c);
+            }
+            _ => return None,
         }
-        letter_counts.insert(c, count - 1);
     }
-    true
+    Some(covered)
+}
+
+/// Validates that `word` uses only characters from `letters` with correct counts, blanks
+/// standing in for missing letters, and has at least 3 letters.
+pub fn validate_word(letters: &str, word: &str) -> bool {
+    normalize_word(word).chars().count() >= MIN_WORD_LEN && blank_letters(letters, word).is_some()
+}
+
+/// The letters blank tiles stood for in a valid `word`; empty for invalid words.
+pub fn blanks_played(letters: &str, word: &str) -> String {
+    if validate_word(letters, word) {
+        blank_letters(letters, word).unwrap_or_default()
+    } else {
+        String::new()
+    }
+}
+
+/// Score for a word-chain word: its length if it is a dictionary word of at least 3 letters,
+/// starts with the last letter of the last word of `chain` and is not in `chain` yet; 0
+/// otherwise.
+pub fn chain_word_score(dictionary: &Dictionary, chain: &[String], word: &str) -> u32 {
+    let word = normalize_word(word);
+    let follows = chain
+        .last()
+        .and_then(|last| last.chars().last())
+        .is_none_or(|letter| word.starts_with(letter));
+    let len = word.chars().count();
+    if len >= MIN_WORD_LEN && follows && !chain.contains(&word) && dictionary.contains(&word) {
+        len as u32
+    } else {
+        0
+    }
 }
 
-/// Score for a word: length if valid, 0 otherwise.
+/// Score for a word: number of letters if valid, 0 otherwise. Letters played with blank tiles
+/// score nothing.
 pub fn word_score(letters: &str, word: &str) -> u32 {
     if validate_word(letters, word) {
-        word.trim().len() as u32
+        let blanks = blank_letters(letters, word).unwrap_or_default();
+        (normalize_word(word).chars().count() - blanks.chars().count()) as u32
     } else {
         0
     }
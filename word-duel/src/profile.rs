@@ -0,0 +1,116 @@
+//! Who a chain's player is, kept on their chain and shown to everyone they play.
+//!
+//! A [`Profile`] is set with `SetProfile` and copied into the player's
+//! [`PlayerInfo`](crate::PlayerInfo) whenever they create or join a match.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Longest display name, in characters.
+pub const MAX_DISPLAY_NAME_LEN: usize = 24;
+
+/// Number of avatars to pick from; avatar IDs run from 0 to one less than this.
+pub const AVATAR_COUNT: u32 = 16;
+
+/// Name a player goes by when they give none and have no profile.
+pub const DEFAULT_NAME: &str = "Player";
+
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    async_graphql::SimpleObject,
+    async_graphql::InputObject,
+)]
+#[graphql(input_name = "ProfileInput", rename_fields = "camelCase")]
+pub struct Profile {
+    pub display_name: String,
+    /// One of [`AVATAR_COUNT`] avatars.
+    pub avatar_id: u32,
+    /// ISO 639-1 code in lower case, e.g. `en`; empty if not given.
+    pub language: String,
+    /// ISO 3166-1 alpha-2 code in upper case, e.g. `DE`; empty if not given.
+    pub country: String,
+}
+
+/// Why a profile cannot be stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileError {
+    EmptyName,
+    NameTooLong,
+    UnknownAvatar,
+    InvalidLanguage,
+    InvalidCountry,
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::EmptyName => f.write_str("Display name must not be empty"),
+            ProfileError::NameTooLong => write!(
+                f,
+                "Display name must be at most {MAX_DISPLAY_NAME_LEN} characters"
+            ),
+            ProfileError::UnknownAvatar => {
+                write!(f, "Avatar must be below {AVATAR_COUNT}")
+            }
+            ProfileError::InvalidLanguage => {
+                f.write_str("Language must be a two-letter code such as 'en'")
+            }
+            ProfileError::InvalidCountry => {
+                f.write_str("Country must be a two-letter code such as 'DE'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+impl Profile {
+    /// The profile trimmed, with its codes in their usual case, or why it cannot be stored.
+    pub fn normalized(self) -> Result<Self, ProfileError> {
+        let display_name = self.display_name.trim().to_string();
+        let language = self.language.trim().to_lowercase();
+        let country = self.country.trim().to_uppercase();
+        if display_name.is_empty() {
+            return Err(ProfileError::EmptyName);
+        }
+        if display_name.chars().count() > MAX_DISPLAY_NAME_LEN {
+            return Err(ProfileError::NameTooLong);
+        }
+        if self.avatar_id >= AVATAR_COUNT {
+            return Err(ProfileError::UnknownAvatar);
+        }
+        if !is_code(&language) {
+            return Err(ProfileError::InvalidLanguage);
+        }
+        if !is_code(&country) {
+            return Err(ProfileError::InvalidCountry);
+        }
+        Ok(Profile {
+            display_name,
+            avatar_id: self.avatar_id,
+            language,
+            country,
+        })
+    }
+}
+
+/// Whether `code` is empty or two ASCII letters.
+fn is_code(code: &str) -> bool {
+    code.is_empty() || (code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// The name a player goes by: `name` if they gave one, else their profile's display name.
+pub fn player_name(name: &str, profile: Option<&Profile>) -> String {
+    let name = name.trim();
+    if !name.is_empty() {
+        return name.to_string();
+    }
+    profile.map_or(DEFAULT_NAME.to_string(), |p| p.display_name.clone())
+}
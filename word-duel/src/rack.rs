@@ -0,0 +1,117 @@
+//! Where a match's letters come from: named packs of racks, or a rack chosen by the host.
+
+use std::fmt;
+
+use crate::{dictionary::Dictionary, normalize_word, solver, LetterChoice, BLANK, LETTER_SETS};
+
+/// Fewest letters a custom rack may have.
+pub const MIN_RACK_LEN: usize = 4;
+
+/// Most letters a custom rack may have.
+pub const MAX_RACK_LEN: usize = 12;
+
+/// Most blank tiles a custom rack may have.
+pub const MAX_BLANKS: usize = 2;
+
+/// The pack matches draw from unless the host picks something else.
+pub const DEFAULT_LETTER_PACK: &str = "classic";
+
+/// A named set of racks; a match gets one of them, picked by its id.
+#[derive(Debug, Clone, Copy)]
+pub struct LetterPack {
+    pub name: &'static str,
+    pub racks: &'static [&'static str],
+}
+
+/// Every pack a host can pick by name.
+pub const LETTER_PACKS: &[LetterPack] = &[
+    LetterPack {
+        name: DEFAULT_LETTER_PACK,
+        racks: LETTER_SETS,
+    },
+    LetterPack {
+        name: "long",
+        racks: &[
+            "STRAINED", "NOTARIES", "RELATING", "PAINTERS", "CLARINET", "TRIANGLE",
+        ],
+    },
+    LetterPack {
+        name: "blanks",
+        racks: &[
+            "ATRES?", "CRANE?", "PAINT?", "STREAM?", "WORD?E", "MAST?R", "LET?ER", "?XAMPLE",
+        ],
+    },
+    LetterPack {
+        name: "tricky",
+        racks: &[
+            "QUARTZ", "JUMBLE", "WHISKY", "JACKPOT", "VORTEX", "BANJOS", "OXIDIZE", "FJORDS",
+        ],
+    },
+];
+
+/// The pack called `name`, ignoring case.
+pub fn letter_pack(name: &str) -> Option<&'static LetterPack> {
+    LETTER_PACKS
+        .iter()
+        .find(|pack| pack.name.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Why a letter choice cannot be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RackError {
+    UnknownPack(String),
+    TooShort,
+    TooLong,
+    NotLetters,
+    TooManyBlanks,
+    /// No dictionary word can be made from the rack.
+    Unplayable,
+}
+
+impl fmt::Display for RackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RackError::UnknownPack(name) => write!(f, "Unknown letter pack '{name}'"),
+            RackError::TooShort => write!(f, "Rack must have at least {MIN_RACK_LEN} letters"),
+            RackError::TooLong => write!(f, "Rack must have at most {MAX_RACK_LEN} letters"),
+            RackError::NotLetters => f.write_str("Rack may only use the letters A to Z and ?"),
+            RackError::TooManyBlanks => {
+                write!(f, "Rack may have at most {MAX_BLANKS} blank tiles")
+            }
+            RackError::Unplayable => f.write_str("No word can be made from the rack"),
+        }
+    }
+}
+
+impl std::error::Error for RackError {}
+
+/// A custom rack in its stored form, upper case with `?` for blank tiles, if it is usable.
+pub fn check_rack(dictionary: &Dictionary, letters: &str) -> Result<String, RackError> {
+    let rack = normalize_word(letters);
+    if !rack.chars().all(|c| c.is_ascii_uppercase() || c == BLANK) {
+        return Err(RackError::NotLetters);
+    }
+    let blanks = rack.chars().filter(|&c| c == BLANK).count();
+    match rack.len() {
+        len if len < MIN_RACK_LEN => Err(RackError::TooShort),
+        len if len > MAX_RACK_LEN => Err(RackError::TooLong),
+        _ if blanks > MAX_BLANKS => Err(RackError::TooManyBlanks),
+        _ if solver::playable_words(dictionary, &rack).is_empty() => Err(RackError::Unplayable),
+        _ => Ok(rack),
+    }
+}
+
+/// The rack of match `match_id` for the host's `choice`.
+pub fn letters_for(
+    choice: &LetterChoice,
+    match_id: u64,
+    dictionary: &Dictionary,
+) -> Result<String, RackError> {
+    match choice {
+        LetterChoice::Pack(name) => {
+            let pack = letter_pack(name).ok_or_else(|| RackError::UnknownPack(name.clone()))?;
+            Ok(pack.racks[(match_id % pack.racks.len() as u64) as usize].to_string())
+        }
+        LetterChoice::Custom(letters) => check_rack(dictionary, letters),
+    }
+}
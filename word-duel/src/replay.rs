@@ -0,0 +1,462 @@
+//! A portable record of a finished match, and a check that its recorded result holds.
+//!
+//! A [`MatchReplay`] carries everything needed to re-score a match without the chain: the
+//! rules it was played under, the rack, the players and every kept [`RoundRecord`]. It is
+//! written as JSON or BCS; `format` and `version` say how to read it.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bonus::{self, Play},
+    challenge::{self, Challenge},
+    engine::MAX_HISTORY,
+    handicap::Handicap,
+    Category, EndReason, Game, GameMode, MatchStatus, PlayerInfo, RoundRecord, TurnOrder,
+    MIN_WORD_LEN,
+};
+
+/// Identifies a Word Duel replay.
+pub const REPLAY_FORMAT: &str = "word-duel-replay";
+
+/// Version of the replay layout written by this crate. Raise it only when a released layout
+/// changes, and keep reading the older one.
+pub const REPLAY_VERSION: u32 = 1;
+
+/// The rules a match was played under. Only `mode`, `turn_order`, `categories` and `bonuses`
+/// may differ between matches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rules {
+    pub mode: GameMode,
+    pub turn_order: TurnOrder,
+    /// Whether rounds had categories; see [`Category::for_round`].
+    pub categories: bool,
+    /// Whether rounds awarded bonuses; see [`bonus`].
+    pub bonuses: bool,
+    pub min_word_len: u32,
+    pub turn_timeout_secs: u64,
+    /// Rounds kept in the history; earlier rounds of longer matches are not in the replay.
+    pub max_history: u32,
+}
+
+impl Rules {
+    pub fn new(mode: GameMode, turn_order: TurnOrder, categories: bool, bonuses: bool) -> Self {
+        Rules {
+            mode,
+            turn_order,
+            categories,
+            bonuses,
+            min_word_len: MIN_WORD_LEN as u32,
+            turn_timeout_secs: mode.turn_timeout_secs(),
+            max_history: MAX_HISTORY as u32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchReplay {
+    /// Always [`REPLAY_FORMAT`].
+    pub format: String,
+    pub version: u32,
+    pub rules: Rules,
+    pub match_id: String,
+    pub host_chain_id: String,
+    pub players: Vec<PlayerInfo>,
+    pub letters: String,
+    /// Word-chain matches: every word accepted, including those of rounds not kept.
+    pub chain: Vec<String>,
+    pub total_rounds: u32,
+    pub rounds: Vec<RoundRecord>,
+    pub host_score: u32,
+    pub guest_score: u32,
+    /// Empty for a draw.
+    pub winner_chain_id: String,
+    pub end_reason: EndReason,
+}
+
+/// A replay in both of its encodings, as served by `exportMatch`.
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct ReplayExport {
+    pub match_id: String,
+    pub format: String,
+    pub version: u32,
+    pub json: String,
+    /// The BCS bytes, hex-encoded.
+    pub bcs: String,
+}
+
+/// Why a replay cannot be read or does not add up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    NotEnded,
+    Malformed(String),
+    UnsupportedFormat(String),
+    UnsupportedVersion(u32),
+    UnsupportedRules,
+    MissingRounds,
+    PointsMismatch { round: u32 },
+    ScoreMismatch { round: u32 },
+    FinalScoreMismatch,
+    WinnerMismatch,
+    ChainMismatch,
+    CategoryMismatch { round: u32 },
+    BonusMismatch { round: u32 },
+    ChallengeMismatch { round: u32 },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::NotEnded => f.write_str("Match has not ended"),
+            ReplayError::Malformed(error) => write!(f, "Malformed replay: {error}"),
+            ReplayError::UnsupportedFormat(format) => write!(f, "Not a replay: '{format}'"),
+            ReplayError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported replay version {version}")
+            }
+            ReplayError::UnsupportedRules => f.write_str("Replay uses different rules"),
+            ReplayError::MissingRounds => f.write_str("Replay is missing rounds"),
+            ReplayError::PointsMismatch { round } => {
+                write!(f, "Round {round} points do not match its words")
+            }
+            ReplayError::ScoreMismatch { round } => {
+                write!(f, "Round {round} running score does not add up")
+            }
+            ReplayError::FinalScoreMismatch => f.write_str("Final score does not add up"),
+            ReplayError::WinnerMismatch => f.write_str("Recorded winner does not match the score"),
+            ReplayError::ChainMismatch => f.write_str("Word chain does not match the rounds"),
+            ReplayError::CategoryMismatch { round } => {
+                write!(f, "Round {round} category does not match the match")
+            }
+            ReplayError::BonusMismatch { round } => {
+                write!(f, "Round {round} bonuses do not match its plays")
+            }
+            ReplayError::ChallengeMismatch { round } => {
+                write!(f, "Round {round} challenges do not match its words")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl MatchReplay {
+    /// The replay of an ended match.
+    pub fn from_game(game: &Game) -> Result<Self, ReplayError> {
+        let (Some(winner_chain_id), Some(end_reason)) = (&game.winner_chain_id, game.end_reason)
+        else {
+            return Err(ReplayError::NotEnded);
+        };
+        if game.status != MatchStatus::Ended {
+            return Err(ReplayError::NotEnded);
+        }
+        Ok(MatchReplay {
+            format: REPLAY_FORMAT.to_string(),
+            version: REPLAY_VERSION,
+            rules: Rules::new(game.mode, game.turn_order, game.categories, game.bonuses),
+            match_id: game.match_id.clone(),
+            host_chain_id: game.host_chain_id.clone(),
+            players: game.players.clone(),
+            letters: game.letters.clone(),
+            chain: game.chain.clone(),
+            total_rounds: game.total_rounds,
+            rounds: game.history.clone(),
+            host_score: game.host_score,
+            guest_score: game.guest_score,
+            winner_chain_id: winner_chain_id.clone(),
+            end_reason,
+        })
+    }
+
+    pub fn export(&self) -> ReplayExport {
+        ReplayExport {
+            match_id: self.match_id.clone(),
+            format: self.format.clone(),
+            version: self.version,
+            json: self.to_json(),
+            bcs: hex::encode(self.to_bcs()),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Replays serialize to JSON")
+    }
+
+    pub fn to_bcs(&self) -> Vec<u8> {
+        bcs::to_bytes(self).expect("Replays serialize to BCS")
+    }
+
+    /// Reads a JSON replay; its format and version are checked, its contents are not.
+    pub fn from_json(json: &str) -> Result<Self, ReplayError> {
+        let header: Header = serde_json::from_str(json)
+            .map_err(|error| ReplayError::Malformed(error.to_string()))?;
+        header.check()?;
+        serde_json::from_str(json).map_err(|error| ReplayError::Malformed(error.to_string()))
+    }
+
+    /// Reads a BCS replay; its format and version are checked, its contents are not.
+    pub fn from_bcs(bytes: &[u8]) -> Result<Self, ReplayError> {
+        Header::from_bcs(bytes)?.check()?;
+        bcs::from_bytes(bytes).map_err(|error| ReplayError::Malformed(error.to_string()))
+    }
+
+    /// Re-scores every round and checks the recorded scores and winner against it.
+    ///
+    /// When the match ran longer than the history keeps, the scores and streaks before the first
+    /// kept round are taken from that round's record. A challenge's outcome is checked against
+    /// the word list; only the points it took may be less than the full amount, when the loser
+    /// had fewer.
+    pub fn verify(&self) -> Result<(), ReplayError> {
+        Header {
+            format: self.format.clone(),
+            version: self.version,
+        }
+        .check()?;
+        let rules = &self.rules;
+        if *rules
+            != Rules::new(
+                rules.mode,
+                rules.turn_order,
+                rules.categories,
+                rules.bonuses,
+            )
+        {
+            return Err(ReplayError::UnsupportedRules);
+        }
+        let first = self.rounds.first().map_or(1, |record| record.round);
+        let played = (first + self.rounds.len() as u32).saturating_sub(1);
+        let complete = self.end_reason == EndReason::Completed;
+        if (first > 1 && self.rounds.len() < MAX_HISTORY)
+            || (complete && played != self.total_rounds)
+        {
+            return Err(ReplayError::MissingRounds);
+        }
+        let (mut host_score, mut guest_score) = match self.rounds.first() {
+            Some(record) if record.round > 1 => (
+                (record.host_score + record.host_challenge_loss())
+                    .saturating_sub(record.host_total()),
+                (record.guest_score + record.guest_challenge_loss())
+                    .saturating_sub(record.guest_total()),
+            ),
+            _ => (0, 0),
+        };
+        let (mut host_streak, mut guest_streak) = match self.rounds.first() {
+            Some(record) if record.round > 1 => (
+                record.host_streak.saturating_sub(1),
+                record.guest_streak.saturating_sub(1),
+            ),
+            _ => (0, 0),
+        };
+        let mode = self.rules.mode;
+        // The chain as it stood before the first kept round: the words the kept rounds added
+        // are the last ones.
+        let added = match mode {
+            GameMode::Chain => self
+                .rounds
+                .iter()
+                .map(|r| usize::from(r.host_points > 0) + usize::from(r.guest_points > 0))
+                .sum(),
+            _ => 0,
+        };
+        let kept_from = self
+            .chain
+            .len()
+            .checked_sub(added)
+            .ok_or(ReplayError::ChainMismatch)?;
+        let mut chain = self.chain[..kept_from].to_vec();
+        let match_id = self.match_id.parse().unwrap_or_default();
+        let (host_handicap, guest_handicap) = (self.handicap(true), self.handicap(false));
+        let host_rack = host_handicap.rack(&self.letters);
+        let guest_rack = guest_handicap.rack(&self.letters);
+        for (i, record) in self.rounds.iter().enumerate() {
+            if record.round != first + i as u32 {
+                return Err(ReplayError::MissingRounds);
+            }
+            let category = self
+                .rules
+                .categories
+                .then(|| Category::for_round(&self.letters, match_id, record.round))
+                .flatten();
+            if category != record.category {
+                return Err(ReplayError::CategoryMismatch {
+                    round: record.round,
+                });
+            }
+            let host = mode.score(&host_rack, category, &chain, &record.host_word);
+            if mode == GameMode::Chain && host.points > 0 {
+                chain.push(record.host_word.clone());
+            }
+            let guest = mode.score(&guest_rack, category, &chain, &record.guest_word);
+            if mode == GameMode::Chain && guest.points > 0 {
+                chain.push(record.guest_word.clone());
+            }
+            if (host.points, guest.points) != (record.host_points, record.guest_points)
+                || host.words != record.host_words
+                || guest.words != record.guest_words
+                || (&host.blanks, &guest.blanks) != (&record.host_blanks, &record.guest_blanks)
+                || (host.verdict, guest.verdict) != (record.host_verdict, record.guest_verdict)
+            {
+                return Err(ReplayError::PointsMismatch {
+                    round: record.round,
+                });
+            }
+            let host_play = Play::new(
+                mode,
+                &record.host_word,
+                &host,
+                record.host_elapsed_micros,
+                host_streak,
+            );
+            let guest_play = Play::new(
+                mode,
+                &record.guest_word,
+                &guest,
+                record.guest_elapsed_micros,
+                guest_streak,
+            );
+            let (host_bonuses, guest_bonuses) = if self.rules.bonuses {
+                (
+                    bonus::bonuses(&host_rack, &host_play, &guest_play),
+                    bonus::bonuses(&guest_rack, &guest_play, &host_play),
+                )
+            } else {
+                (Vec::new(), Vec::new())
+            };
+            if (host_play.streak, guest_play.streak) != (record.host_streak, record.guest_streak)
+                || host_bonuses != record.host_bonuses
+                || guest_bonuses != record.guest_bonuses
+            {
+                return Err(ReplayError::BonusMismatch {
+                    round: record.round,
+                });
+            }
+            (host_streak, guest_streak) = (host_play.streak, guest_play.streak);
+            let host_total = host_handicap.apply(host.points + bonus::total(&host_bonuses));
+            let guest_total = guest_handicap.apply(guest.points + bonus::total(&guest_bonuses));
+            if (host_total, guest_total) != (record.host_total(), record.guest_total()) {
+                return Err(ReplayError::PointsMismatch {
+                    round: record.round,
+                });
+            }
+            let challenges = [
+                (&record.host_challenge, false),
+                (&record.guest_challenge, true),
+            ];
+            for (recorded, host_challenged) in challenges {
+                if !recorded
+                    .as_ref()
+                    .is_none_or(|c| self.challenge_holds(c, record, host_challenged))
+                {
+                    return Err(ReplayError::ChallengeMismatch {
+                        round: record.round,
+                    });
+                }
+            }
+            host_score = (host_score + host_total).saturating_sub(record.host_challenge_loss());
+            guest_score = (guest_score + guest_total).saturating_sub(record.guest_challenge_loss());
+            if (host_score, guest_score) != (record.host_score, record.guest_score) {
+                return Err(ReplayError::ScoreMismatch {
+                    round: record.round,
+                });
+            }
+        }
+        if chain != self.chain {
+            return Err(ReplayError::ChainMismatch);
+        }
+        if (host_score, guest_score) != (self.host_score, self.guest_score) {
+            return Err(ReplayError::FinalScoreMismatch);
+        }
+        let guest_chain_id = self
+            .players
+            .iter()
+            .map(|p| p.chain_id.as_str())
+            .find(|chain_id| *chain_id != self.host_chain_id)
+            .unwrap_or_default();
+        let winner_ok = match self.end_reason {
+            EndReason::Completed => {
+                let expected = match host_score.cmp(&guest_score) {
+                    std::cmp::Ordering::Greater => self.host_chain_id.as_str(),
+                    std::cmp::Ordering::Less => guest_chain_id,
+                    std::cmp::Ordering::Equal => "",
+                };
+                self.winner_chain_id == expected
+            }
+            // Who left is not recorded; the winner only has to be one of the players.
+            EndReason::Forfeit => {
+                !self.winner_chain_id.is_empty()
+                    && self
+                        .players
+                        .iter()
+                        .any(|p| p.chain_id == self.winner_chain_id)
+            }
+        };
+        if !winner_ok || guest_chain_id.is_empty() {
+            return Err(ReplayError::WinnerMismatch);
+        }
+        Ok(())
+    }
+
+    fn handicap(&self, host: bool) -> Handicap {
+        self.players
+            .iter()
+            .find(|p| (p.chain_id == self.host_chain_id) == host)
+            .map_or_else(Handicap::default, |p| p.handicap)
+    }
+
+    /// Whether `recorded` is what a challenge of the host's, or the guest's, words in `record`
+    /// comes to.
+    fn challenge_holds(&self, recorded: &Challenge, record: &RoundRecord, host: bool) -> bool {
+        let words = challenge::challenged_words(record, host);
+        let handicap = self.handicap(host);
+        let rack = handicap.rack(&self.letters);
+        let full = challenge::judge(record, host, &rack, handicap, u32::MAX, u32::MAX);
+        self.rules.mode != GameMode::Chain
+            && !words.is_empty()
+            && recorded.upheld == full.upheld
+            && recorded.struck_words == full.struck_words
+            && recorded.points <= full.points
+    }
+}
+
+/// The fields every replay version starts with.
+#[derive(Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+}
+
+impl Header {
+    /// Reads the header off the front of a BCS replay of any version. BCS is not
+    /// self-describing, so this follows its layout: a ULEB128 length and the UTF-8 bytes of
+    /// `format`, then `version` as four little-endian bytes.
+    fn from_bcs(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let malformed = || ReplayError::Malformed("truncated header".to_string());
+        let mut len = 0usize;
+        let mut rest = bytes;
+        for shift in (0..32).step_by(7) {
+            let (&byte, tail) = rest.split_first().ok_or_else(malformed)?;
+            rest = tail;
+            len |= usize::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        if rest.len() < len + 4 {
+            return Err(malformed());
+        }
+        let format = String::from_utf8_lossy(&rest[..len]).into_owned();
+        let version = u32::from_le_bytes(rest[len..len + 4].try_into().expect("four bytes"));
+        Ok(Header { format, version })
+    }
+
+    fn check(&self) -> Result<(), ReplayError> {
+        if self.format != REPLAY_FORMAT {
+            return Err(ReplayError::UnsupportedFormat(self.format.clone()));
+        }
+        if self.version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(self.version));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+//! Sealed words of simultaneous rounds.
+//!
+//! Operations, messages and chain state can all be read by the opponent, so in a simultaneous
+//! round no word travels in the clear before both are locked in. Each player first sends a
+//! seal: the hash of its word and a random salt it keeps to itself. Once the host has both
+//! seals, each player reveals its word and salt, and the host only scores a revealed word that
+//! matches its seal.
+
+use linera_sdk::linera_base_types::{BcsHashable, CryptoHash};
+use serde::{Deserialize, Serialize};
+
+/// Fewest characters a salt should have; with a short one the opponent could find the word by
+/// hashing every word the rack allows.
+pub const MIN_SALT_LEN: usize = 16;
+
+/// What a seal is the hash of.
+#[derive(Serialize, Deserialize)]
+struct SealedWord {
+    word: String,
+    salt: String,
+}
+
+impl BcsHashable<'_> for SealedWord {}
+
+/// The seal of `word`, exactly as it will be revealed, with `salt`.
+pub fn seal(word: &str, salt: &str) -> CryptoHash {
+    CryptoHash::new(&SealedWord {
+        word: word.to_string(),
+        salt: salt.to_string(),
+    })
+}
@@ -4,10 +4,21 @@ mod state;
 
 use std::sync::Arc;
 
-use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
+use async_graphql::{EmptySubscription, InputObject, Object, Request, Response, Schema};
 use linera_sdk::{linera_base_types::WithServiceAbi, views::View, Service, ServiceRuntime};
 use word_duel::{
-    Game, MatchStatus, Operation, RoundPhase, RoundRecord, WordDuelAbi, WordDuelParameters,
+    chat::{self, ChatMessage},
+    dictionary::Dictionary,
+    handicap::{Handicap, HandicapChoice, HandicapMode},
+    profile::Profile,
+    rack,
+    replay::{MatchReplay, ReplayExport},
+    seal,
+    solver::{self, MissedWords, ScoredWord},
+    stats::{self, PlayerStats},
+    Category, Friend, Game, GameMode, Invite, JoinPolicy, LetterChoice, MatchResult, MatchRules,
+    MatchStatus, Operation, PendingJoin, RoomEntry, RoundPhase, RoundRecord, TurnOrder,
+    WordDuelAbi, WordDuelParameters, INITIAL_RATING, MAX_ROUNDS, MIN_ROUNDS,
 };
 
 use self::state::WordDuelState;
@@ -15,7 +26,7 @@ use self::state::WordDuelState;
 linera_sdk::service!(WordDuelService);
 
 pub struct WordDuelService {
-    state: WordDuelState,
+    state: Arc<WordDuelState>,
     runtime: Arc<ServiceRuntime<Self>>,
 }
 
@@ -31,23 +42,31 @@ impl Service for WordDuelService {
             .await
             .expect("Failed to load state");
         WordDuelService {
-            state,
+            state: Arc::new(state),
             runtime: Arc::new(runtime),
         }
     }
 
     async fn handle_query(&self, request: Request) -> Response {
-        let game = self.state.game.get().clone();
+        let chain_id = self.runtime.chain_id().to_string();
+        let now = self.runtime.system_time().micros();
+        let game = self.state.game.get().as_ref().map(|g| g.seen_by(&chain_id));
         let my_word = self.state.my_word.get().clone();
         let opponent_word = self.state.opponent_word.get().clone();
         let last_notification = self.state.last_notification.get().clone();
+        let rating = self.state.rating.get().unwrap_or(INITIAL_RATING);
+        let profile = self.state.profile.get().clone();
         let schema = Schema::build(
             QueryRoot {
                 game,
-                chain_id: self.runtime.chain_id().to_string(),
+                chain_id,
+                now,
                 my_word,
                 opponent_word,
                 last_notification,
+                rating,
+                profile,
+                state: self.state.clone(),
             },
             MutationRoot {
                 runtime: self.runtime.clone(),
@@ -62,9 +81,50 @@ impl Service for WordDuelService {
 struct QueryRoot {
     game: Option<Game>,
     chain_id: String,
+    /// Microseconds since the epoch.
+    now: u64,
     my_word: Option<String>,
     opponent_word: Option<String>,
     last_notification: Option<String>,
+    rating: u32,
+    profile: Option<Profile>,
+    /// For what is too big to copy for every query, such as the archive and the chats; only
+    /// the resolvers that need it read it.
+    state: Arc<WordDuelState>,
+}
+
+impl QueryRoot {
+    /// Every match this chain finished, oldest first; a match archived again after a challenge
+    /// revised it is listed once, as revised.
+    async fn read_archive(&self) -> Vec<Game> {
+        let archive = &self.state.archive;
+        let entries = archive
+            .read(0..archive.count())
+            .await
+            .expect("Failed to read archive");
+        let mut games: Vec<Game> = Vec::with_capacity(entries.len());
+        for game in entries {
+            match games
+                .iter_mut()
+                .find(|g| g.match_id == game.match_id && g.host_chain_id == game.host_chain_id)
+            {
+                Some(earlier) => *earlier = game,
+                None => games.push(game),
+            }
+        }
+        games
+    }
+
+    /// The match `match_id`, whether it is still running or archived.
+    async fn find_match(&self, match_id: &str) -> Option<Game> {
+        if let Some(game) = self.game.as_ref().filter(|g| g.match_id == match_id) {
+            return Some(game.clone());
+        }
+        self.read_archive()
+            .await
+            .into_iter()
+            .find(|g| g.match_id == match_id)
+    }
 }
 
 #[Object]
@@ -81,6 +141,12 @@ impl QueryRoot {
         self.game.as_ref().map(|g| g.letters.clone())
     }
 
+    /// The rack this chain's player plays on, with any extra letters of their handicap.
+    async fn my_letters(&self) -> Option<String> {
+        let game = self.game.as_ref()?;
+        Some(game.rack(game.host_chain_id == self.chain_id))
+    }
+
     async fn round(&self) -> Option<i32> {
         self.game.as_ref().map(|g| g.current_round as i32)
     }
@@ -112,6 +178,14 @@ impl QueryRoot {
         self.opponent_word.clone()
     }
 
+    /// Whether this chain's sealed word should be revealed now with `revealWord`.
+    async fn reveal_due(&self) -> bool {
+        let Some(game) = &self.game else {
+            return false;
+        };
+        self.my_word.is_none() && game.can_reveal(game.host_chain_id == self.chain_id, self.now)
+    }
+
     async fn my_score(&self) -> Option<i32> {
         let game = self.game.as_ref()?;
         if game.host_chain_id == self.chain_id {
@@ -143,9 +217,185 @@ impl QueryRoot {
             .and_then(|g| g.history.last().cloned())
     }
 
+    /// Until when the last scored round may be challenged, in microseconds since the epoch.
+    async fn challenge_deadline(&self) -> Option<u64> {
+        self.game.as_ref()?.challenge_deadline()
+    }
+
     async fn last_notification(&self) -> Option<String> {
         self.last_notification.clone()
     }
+
+    async fn match_result(&self) -> Option<MatchResult> {
+        self.game.as_ref()?.result_for(&self.chain_id)
+    }
+
+    async fn rating(&self) -> i32 {
+        self.rating as i32
+    }
+
+    /// The profile of `chain_id`: this chain's own, or the latest one a match with that chain
+    /// recorded.
+    async fn profile(&self, chain_id: String) -> Option<Profile> {
+        if chain_id == self.chain_id {
+            return self.profile.clone();
+        }
+        let recorded = |game: &Game| {
+            game.players
+                .iter()
+                .filter(|player| player.chain_id == chain_id)
+                .find_map(|player| player.profile.clone())
+        };
+        if let Some(profile) = self.game.as_ref().and_then(recorded) {
+            return Some(profile);
+        }
+        self.read_archive().await.iter().rev().find_map(recorded)
+    }
+
+    async fn archived_matches(&self) -> Vec<Game> {
+        self.read_archive().await
+    }
+
+    async fn pending_joins(&self) -> Vec<PendingJoin> {
+        self.state
+            .pending_joins
+            .index_values()
+            .await
+            .expect("Failed to read pending joins")
+            .into_iter()
+            .map(|(_, join)| join)
+            .collect()
+    }
+
+    async fn friends(&self) -> Vec<Friend> {
+        self.state
+            .friends
+            .index_values()
+            .await
+            .expect("Failed to read friends")
+            .into_iter()
+            .map(|(_, friend)| friend)
+            .collect()
+    }
+
+    /// Challenges from other players awaiting this chain's answer.
+    async fn invites(&self) -> Vec<Invite> {
+        self.state
+            .invites
+            .index_values()
+            .await
+            .expect("Failed to read invites")
+            .into_iter()
+            .map(|(_, invite)| invite)
+            .collect()
+    }
+
+    /// Chains this chain has challenged that have not answered yet.
+    async fn sent_invites(&self) -> Vec<String> {
+        self.state
+            .sent_invites
+            .indices()
+            .await
+            .expect("Failed to read sent invites")
+    }
+
+    async fn room_code(&self) -> Option<String> {
+        self.game.as_ref().map(|g| g.room_code.clone())
+    }
+
+    /// Resolves a room code; only answers on the lobby (application creator) chain.
+    async fn lookup_room(&self, code: String) -> Option<RoomEntry> {
+        let code = word_duel::normalize_room_code(&code);
+        self.state
+            .room_codes
+            .get(&code)
+            .await
+            .expect("Failed to read room codes")
+    }
+
+    /// The best dictionary words for a rack, `limit` (default 10, at most 100) of them.
+    async fn best_possible_words(&self, letters: String, limit: Option<i32>) -> Vec<ScoredWord> {
+        let limit = limit.unwrap_or(10).clamp(0, 100) as usize;
+        solver::best_words(Dictionary::english(), &letters, limit)
+    }
+
+    /// The letter the next word of a word-chain match must start with.
+    async fn chain_letter(&self) -> Option<String> {
+        self.game.as_ref()?.chain_letter().map(String::from)
+    }
+
+    /// The category of the current round, in matches with category rounds.
+    async fn round_category(&self) -> Option<Category> {
+        self.game.as_ref()?.category
+    }
+
+    /// Names of the letter packs `createMatch` can draw from.
+    async fn letter_packs(&self) -> Vec<String> {
+        rack::LETTER_PACKS
+            .iter()
+            .map(|pack| pack.name.to_string())
+            .collect()
+    }
+
+    /// The chat of the current match, or of the archived match `match_id`, oldest first.
+    async fn chat(&self, match_id: Option<String>) -> Vec<ChatMessage> {
+        let Some(match_id) = match_id.or_else(|| self.game.as_ref().map(|g| g.match_id.clone()))
+        else {
+            return Vec::new();
+        };
+        let Some(log) = self
+            .state
+            .chats
+            .try_load_entry(&match_id)
+            .await
+            .expect("Failed to load chat")
+        else {
+            return Vec::new();
+        };
+        log.read(0..log.count()).await.expect("Failed to read chat")
+    }
+
+    /// The emoji `sendReaction` accepts.
+    async fn chat_reactions(&self) -> Vec<String> {
+        chat::REACTIONS.iter().map(|r| r.to_string()).collect()
+    }
+
+    /// Stats of `chain_id` (default: this chain's player) over the matches this chain archived,
+    /// followed by the match still running, if any.
+    async fn player_stats(&self, chain_id: Option<String>) -> PlayerStats {
+        let chain_id = chain_id.unwrap_or_else(|| self.chain_id.clone());
+        let mut games = self.read_archive().await;
+        games.extend(
+            self.game
+                .iter()
+                .filter(|g| g.status != MatchStatus::Ended)
+                .cloned(),
+        );
+        stats::player_stats(&games, &chain_id, Dictionary::english())
+    }
+
+    /// The replay of the ended match `match_id`, as JSON and hex-encoded BCS.
+    async fn export_match(&self, match_id: String) -> Option<ReplayExport> {
+        let game = self.find_match(&match_id).await?;
+        MatchReplay::from_game(&game)
+            .ok()
+            .map(|replay| replay.export())
+    }
+
+    /// For each finished round of the current match, or of the archived match `match_id`, the
+    /// best `limit` (default 3, at most 20) words neither player played, on each player's rack
+    /// and from the round's category if it had one.
+    async fn missed_words(&self, match_id: Option<String>, limit: Option<i32>) -> Vec<MissedWords> {
+        let game = match &match_id {
+            Some(id) => self.find_match(id).await,
+            None => self.game.clone(),
+        };
+        let Some(game) = game else {
+            return Vec::new();
+        };
+        let limit = limit.unwrap_or(3).clamp(0, 20) as usize;
+        solver::missed_by_round(&game, limit)
+    }
 }
 
 struct MutationRoot {
@@ -154,31 +404,249 @@ struct MutationRoot {
 
 #[Object]
 impl MutationRoot {
-    async fn create_match(&self, host_name: String, total_rounds: i32) -> String {
-        let total_rounds = total_rounds.max(1).min(20) as u32;
+    #[allow(clippy::too_many_arguments)]
+    async fn create_match(
+        &self,
+        host_name: Option<String>,
+        total_rounds: i32,
+        allow_list: Option<Vec<String>>,
+        invite_secret: Option<String>,
+        require_approval: Option<bool>,
+        turn_order: Option<TurnOrder>,
+        mode: Option<GameMode>,
+        letters: Option<String>,
+        letter_pack: Option<String>,
+        categories: Option<bool>,
+        bonuses: Option<bool>,
+        handicaps: Option<HandicapMode>,
+        host_handicap: Option<Handicap>,
+        guest_handicap: Option<Handicap>,
+    ) -> String {
+        let host_name = host_name.unwrap_or_default();
+        let rules = MatchRulesInput {
+            total_rounds,
+            turn_order,
+            mode,
+            letters,
+            letter_pack,
+            categories,
+            bonuses,
+            handicaps,
+            host_handicap,
+            guest_handicap,
+        };
         self.runtime.schedule_operation(&Operation::CreateMatch {
             host_name: host_name.clone(),
-            total_rounds,
+            join_policy: JoinPolicy {
+                allow_list: allow_list.unwrap_or_default(),
+                invite_secret,
+                require_approval: require_approval.unwrap_or(false),
+            },
+            rules: rules.into_rules(),
         });
         format!("Match created by '{}'", host_name)
     }
 
-    async fn join_match(&self, host_chain_id: String, player_name: String) -> String {
+    async fn join_match(
+        &self,
+        host_chain_id: String,
+        player_name: Option<String>,
+        invite_secret: Option<String>,
+    ) -> String {
         self.runtime.schedule_operation(&Operation::JoinMatch {
             host_chain_id: host_chain_id.clone(),
-            player_name: player_name.clone(),
+            player_name: player_name.unwrap_or_default(),
+            invite_secret,
         });
         format!("Join request sent to {}", host_chain_id)
     }
 
+    async fn join_by_code(
+        &self,
+        code: String,
+        player_name: Option<String>,
+        invite_secret: Option<String>,
+    ) -> String {
+        self.runtime.schedule_operation(&Operation::JoinByCode {
+            code: code.clone(),
+            player_name: player_name.unwrap_or_default(),
+            invite_secret,
+        });
+        format!("Join request sent for room {}", code)
+    }
+
+    /// Sets this chain's profile, attached to every match it creates or joins from now on.
+    async fn set_profile(&self, profile: Profile) -> String {
+        self.runtime
+            .schedule_operation(&Operation::SetProfile { profile });
+        "Profile saved".to_string()
+    }
+
+    async fn add_friend(&self, chain_id: String, name: String) -> String {
+        self.runtime
+            .schedule_operation(&Operation::AddFriend { chain_id, name });
+        "Friend added".to_string()
+    }
+
+    async fn remove_friend(&self, chain_id: String) -> String {
+        self.runtime
+            .schedule_operation(&Operation::RemoveFriend { chain_id });
+        "Friend removed".to_string()
+    }
+
+    /// Invites the player on `chain_id` to a match played by `rules`, hosted by this chain.
+    async fn challenge_player(&self, chain_id: String, rules: MatchRulesInput) -> String {
+        self.runtime
+            .schedule_operation(&Operation::ChallengePlayer {
+                chain_id: chain_id.clone(),
+                rules: rules.into_rules(),
+            });
+        format!("Challenge sent to {}", chain_id)
+    }
+
+    async fn accept_challenge(&self, chain_id: String) -> String {
+        self.runtime
+            .schedule_operation(&Operation::AcceptChallenge { chain_id });
+        "Challenge accepted".to_string()
+    }
+
+    async fn decline_challenge(&self, chain_id: String) -> String {
+        self.runtime
+            .schedule_operation(&Operation::DeclineChallenge { chain_id });
+        "Challenge declined".to_string()
+    }
+
+    async fn request_sync(&self) -> String {
+        self.runtime.schedule_operation(&Operation::RequestSync);
+        "Sync requested".to_string()
+    }
+
+    async fn accept_join(&self, player_chain_id: String) -> String {
+        self.runtime.schedule_operation(&Operation::AcceptJoin {
+            player_chain_id: player_chain_id.clone(),
+        });
+        format!("Accepted {}", player_chain_id)
+    }
+
+    async fn decline_join(&self, player_chain_id: String) -> String {
+        self.runtime.schedule_operation(&Operation::DeclineJoin {
+            player_chain_id: player_chain_id.clone(),
+        });
+        format!("Declined {}", player_chain_id)
+    }
+
+    async fn kick_player(&self) -> String {
+        self.runtime.schedule_operation(&Operation::KickPlayer);
+        "Kick requested".to_string()
+    }
+
     async fn submit_word(&self, word: String) -> String {
         self.runtime
             .schedule_operation(&Operation::SubmitWord { word });
         "Word submitted".to_string()
     }
 
+    /// Submits several words at once, for rush matches.
+    async fn submit_words(&self, words: Vec<String>) -> String {
+        let word = words.join(" ");
+        self.runtime
+            .schedule_operation(&Operation::SubmitWord { word });
+        "Words submitted".to_string()
+    }
+
+    /// Simultaneous rounds: locks in `word` (in rush rounds, the words separated by spaces)
+    /// without sending it. Keep `word` and `salt`, a random string of at least 16 characters:
+    /// `revealWord` takes both once `revealDue` says so.
+    async fn seal_word(&self, word: String, salt: String) -> async_graphql::Result<String> {
+        if salt.chars().count() < seal::MIN_SALT_LEN {
+            return Err(async_graphql::Error::new(format!(
+                "Salt must have at least {} characters",
+                seal::MIN_SALT_LEN
+            )));
+        }
+        let seal = seal::seal(&word, &salt);
+        self.runtime
+            .schedule_operation(&Operation::SealWord { seal });
+        Ok("Word sealed".to_string())
+    }
+
+    /// Reveals the word sealed with `sealWord`, with the same `word` and `salt`.
+    async fn reveal_word(&self, word: String, salt: String) -> String {
+        self.runtime
+            .schedule_operation(&Operation::RevealWord { word, salt });
+        "Word revealed".to_string()
+    }
+
+    async fn claim_timeout(&self) -> String {
+        self.runtime.schedule_operation(&Operation::ClaimTimeout);
+        "Timeout claimed".to_string()
+    }
+
+    /// Challenges the opponent's words in the last scored round.
+    async fn challenge(&self) -> String {
+        self.runtime.schedule_operation(&Operation::Challenge);
+        "Challenge sent".to_string()
+    }
+
+    async fn send_chat(&self, text: String) -> String {
+        self.runtime
+            .schedule_operation(&Operation::SendChat { text });
+        "Message sent".to_string()
+    }
+
+    async fn send_reaction(&self, emoji: String) -> String {
+        self.runtime
+            .schedule_operation(&Operation::SendReaction { emoji });
+        "Reaction sent".to_string()
+    }
+
     async fn leave_match(&self) -> String {
         self.runtime.schedule_operation(&Operation::LeaveMatch);
         "Leave requested".to_string()
     }
 }
+
+/// The rules of a new match as the `createMatch` arguments give them; a challenge's rules.
+#[derive(InputObject)]
+struct MatchRulesInput {
+    total_rounds: i32,
+    turn_order: Option<TurnOrder>,
+    mode: Option<GameMode>,
+    letters: Option<String>,
+    letter_pack: Option<String>,
+    categories: Option<bool>,
+    bonuses: Option<bool>,
+    handicaps: Option<HandicapMode>,
+    host_handicap: Option<Handicap>,
+    guest_handicap: Option<Handicap>,
+}
+
+impl MatchRulesInput {
+    fn into_rules(self) -> MatchRules {
+        let handicaps = match self.handicaps.unwrap_or_default() {
+            HandicapMode::Off => HandicapChoice::Off,
+            HandicapMode::FromRating => HandicapChoice::FromRating,
+            HandicapMode::Fixed => HandicapChoice::Fixed {
+                host: self.host_handicap.unwrap_or_default(),
+                guest: self.guest_handicap.unwrap_or_default(),
+            },
+        };
+        // A custom rack wins over a pack; with neither, the default pack is used.
+        let letters = match (self.letters, self.letter_pack) {
+            (Some(letters), _) => LetterChoice::Custom(letters),
+            (None, Some(name)) => LetterChoice::Pack(name),
+            (None, None) => LetterChoice::default(),
+        };
+        MatchRules {
+            total_rounds: self
+                .total_rounds
+                .clamp(MIN_ROUNDS as i32, MAX_ROUNDS as i32) as u32,
+            turn_order: self.turn_order.unwrap_or_default(),
+            mode: self.mode.unwrap_or_default(),
+            letters,
+            categories: self.categories.unwrap_or(false),
+            bonuses: self.bonuses.unwrap_or(false),
+            handicaps,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Finds every dictionary word a rack can make.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blanks_played, challenge, dictionary::Dictionary, normalize_word, word_score, Category, Game,
+    BLANK,
+};
+
+/// A playable word and what it scores on the rack it was found for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct ScoredWord {
+    pub word: String,
+    pub points: u32,
+    /// The letters played with blank tiles, in word order.
+    pub blanks: String,
+}
+
+impl ScoredWord {
+    /// `word`, normalized, as scored on `letters`.
+    pub fn new(letters: &str, word: String) -> Self {
+        ScoredWord {
+            points: word_score(letters, &word),
+            blanks: blanks_played(letters, &word),
+            word,
+        }
+    }
+}
+
+/// The best words of a round that neither player played, on each player's rack.
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct MissedWords {
+    pub round: u32,
+    pub host_words: Vec<ScoredWord>,
+    pub guest_words: Vec<ScoredWord>,
+}
+
+/// Every word of `dictionary` that `letters` can make, best first; equal scores are ordered
+/// alphabetically.
+pub fn playable_words(dictionary: &Dictionary, letters: &str) -> Vec<ScoredWord> {
+    let rack = letter_counts(&letters.to_uppercase());
+    let blanks = letters.chars().filter(|&c| c == BLANK).count() as u32;
+    let mut words: Vec<ScoredWord> = dictionary
+        .words()
+        .filter(|word| fits(&rack, blanks, word))
+        .map(|word| ScoredWord::new(letters, word.to_string()))
+        .filter(|scored| scored.points > 0)
+        .collect();
+    words.sort_by(|a, b| b.points.cmp(&a.points).then_with(|| a.word.cmp(&b.word)));
+    words
+}
+
+/// The `limit` best words for `letters`.
+pub fn best_words(dictionary: &Dictionary, letters: &str, limit: usize) -> Vec<ScoredWord> {
+    let mut words = playable_words(dictionary, letters);
+    words.truncate(limit);
+    words
+}
+
+/// The `limit` best words for `letters` other than those in `played`.
+pub fn missed_words(
+    dictionary: &Dictionary,
+    letters: &str,
+    played: &[&str],
+    limit: usize,
+) -> Vec<ScoredWord> {
+    let played: Vec<String> = played.iter().map(|word| normalize_word(word)).collect();
+    playable_words(dictionary, letters)
+        .into_iter()
+        .filter(|scored| !played.contains(&scored.word))
+        .take(limit)
+        .collect()
+}
+
+fn letter_counts(letters: &str) -> HashMap<char, u32> {
+    let mut counts = HashMap::new();
+    for c in letters.chars().filter(|c| c.is_alphabetic()) {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Cheap pre-check that `word` (already upper-case) needs no more of any letter than the rack
+/// has, counting its `blanks`; `word_score` remains the authority.
+fn fits(rack: &HashMap<char, u32>, blanks: u32, word: &str) -> bool {
+    let mut needed = HashMap::new();
+    let mut missing = 0;
+    word.chars().all(|c| {
+        let count = needed.entry(c).or_insert(0);
+        *count += 1;
+        if rack.get(&c).is_some_and(|available| *count <= *available) {
+            return true;
+        }
+        missing += 1;
+        missing <= blanks
+    })
+}
+
+/// For each kept round of `game`, the `limit` best words on the host's and on the guest's rack
+/// that neither player played; in category rounds, only words of the category.
+pub fn missed_by_round(game: &Game, limit: usize) -> Vec<MissedWords> {
+    // Rounds share racks and categories, so each pair is solved once.
+    let mut solved: Vec<(String, Option<Category>, Vec<ScoredWord>)> = Vec::new();
+    let mut missed = |host: bool, category: Option<Category>, played: &[&str]| {
+        let rack = game.rack(host);
+        let index = match solved
+            .iter()
+            .position(|(r, c, _)| *r == rack && *c == category)
+        {
+            Some(index) => index,
+            None => {
+                let words = playable_words(challenge::word_list(category), &rack);
+                solved.push((rack, category, words));
+                solved.len() - 1
+            }
+        };
+        solved[index]
+            .2
+            .iter()
+            .filter(|w| !played.contains(&w.word.as_str()))
+            .take(limit)
+            .cloned()
+            .collect()
+    };
+    game.history
+        .iter()
+        .map(|record| {
+            let played = record.played_words();
+            MissedWords {
+                round: record.round,
+                host_words: missed(true, record.category, &played),
+                guest_words: missed(false, record.category, &played),
+            }
+        })
+        .collect()
+}
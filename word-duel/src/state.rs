@@ -1,8 +1,14 @@
 // All persistent state uses Linera View types (RegisterView). No BTreeMap or plain
 // Rust collections as root state. Game is a value type (serializable struct) stored
 // inside RegisterView<Option<Game>>, not a replacement for Views.
-use linera_sdk::views::{linera_views, RegisterView, RootView, ViewStorageContext};
-use word_duel::Game;
+use linera_sdk::views::{
+    linera_views, CollectionView, LogView, MapView, RegisterView, RootView, SetView,
+    ViewStorageContext,
+};
+use word_duel::{
+    chat::ChatMessage, profile::Profile, Friend, Game, Invite, JoinPolicy, MatchRules, PendingJoin,
+    RoomEntry,
+};
 
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
@@ -11,4 +17,34 @@ pub struct WordDuelState {
     pub my_word: RegisterView<Option<String>>,
     pub opponent_word: RegisterView<Option<String>>,
     pub last_notification: RegisterView<Option<String>>,
+    /// Every match this chain finished, in the order they ended. A match whose scores a
+    /// challenge of its last round changed is pushed again; its last copy is the one that counts.
+    pub archive: LogView<Game>,
+    /// The chat of each match this chain played, by match ID; at most
+    /// [`MAX_CHAT_MESSAGES`](word_duel::chat::MAX_CHAT_MESSAGES) per match.
+    pub chats: CollectionView<String, LogView<ChatMessage>>,
+    /// Elo rating of this chain's player; `None` until the first rated match.
+    pub rating: RegisterView<Option<u32>>,
+    /// The rating before the last rated match, to redo its update if the result is revised.
+    pub rating_before_match: RegisterView<Option<u32>>,
+    /// This chain's player, as set with `SetProfile`.
+    pub profile: RegisterView<Option<Profile>>,
+    /// Host only: who may join the current room.
+    pub join_policy: RegisterView<JoinPolicy>,
+    /// Host only: join requests awaiting approval, keyed by player chain ID.
+    pub pending_joins: MapView<String, PendingJoin>,
+    /// Hosts this chain asked to join, by chain ID, or by the room code it joined with; only
+    /// their matches are taken.
+    pub join_requests: SetView<String>,
+    /// Friends of this chain's player, by chain ID.
+    pub friends: MapView<String, Friend>,
+    /// Invites from other chains awaiting an answer, by challenger chain ID.
+    pub invites: MapView<String, Invite>,
+    /// The challenger whose invite this chain accepted, until its match arrives or the
+    /// challenger turns the acceptance down.
+    pub accepted_invite: RegisterView<Option<String>>,
+    /// Invites this chain sent and not yet had answered, by invitee chain ID.
+    pub sent_invites: MapView<String, MatchRules>,
+    /// Lobby (application creator chain) only: open rooms by room code.
+    pub room_codes: MapView<String, RoomEntry>,
 }
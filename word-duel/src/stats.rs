@@ -0,0 +1,223 @@
+//! Per-player statistics computed from finished matches.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dictionary::Dictionary, solver, Category, Game, GameMode, MatchResult, MAX_RUSH_WORDS,
+};
+
+/// How one player did in one match.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct MatchStats {
+    pub match_id: String,
+    pub letters: String,
+    /// `None` while the match is still running.
+    pub result: Option<MatchResult>,
+    pub rounds: u32,
+    /// Words submitted; timed-out turns are not counted.
+    pub words_played: u32,
+    /// Submitted words that scored nothing.
+    pub invalid_words: u32,
+    pub points: u32,
+    /// What the best play of the player's rack, extra letters included, would have scored
+    /// over the same rounds: its best word, or in rush matches its best words, from the
+    /// round's category in category rounds.
+    pub optimal_points: u32,
+    pub best_word: Option<String>,
+    pub best_word_points: u32,
+    /// Letters in all scoring words together.
+    pub letters_played: u32,
+    pub invalid_word_rate: f64,
+    pub average_word_length: f64,
+    pub efficiency: f64,
+}
+
+/// How one player did across several matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct PlayerStats {
+    pub chain_id: String,
+    pub matches_played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub words_played: u32,
+    pub invalid_words: u32,
+    /// Share of submitted words that scored nothing, from 0 to 1.
+    pub invalid_word_rate: f64,
+    /// Average length of the scoring words.
+    pub average_word_length: f64,
+    pub best_word: Option<String>,
+    pub best_word_points: u32,
+    /// Points scored over the points the best words would have scored, from 0 to 1.
+    pub efficiency: f64,
+    /// Consecutive wins (positive) or losses (negative) up to the latest match.
+    pub current_streak: i32,
+    pub longest_win_streak: u32,
+    pub longest_loss_streak: u32,
+    /// The matches these stats cover, in the order given.
+    pub matches: Vec<MatchStats>,
+}
+
+/// Stats of `chain_id` in `game`, or `None` if it did not play in it.
+pub fn match_stats(game: &Game, chain_id: &str, dictionary: &Dictionary) -> Option<MatchStats> {
+    if !game.players.iter().any(|p| p.chain_id == chain_id) {
+        return None;
+    }
+    let is_host = game.host_chain_id == chain_id;
+    // The player's rack stays the same for the whole match; only categories change, so each
+    // one is solved once.
+    let rack = game.rack(is_host);
+    let mut solved: Vec<(Option<Category>, u32)> = Vec::new();
+    let mut optimal = |category: Option<Category>| {
+        if game.mode == GameMode::Chain {
+            // Word-chain matches have no rack to measure against.
+            return 0;
+        }
+        if let Some(&(_, points)) = solved.iter().find(|(c, _)| *c == category) {
+            return points;
+        }
+        let word_list = match category {
+            Some(category) => Dictionary::category(category),
+            None => dictionary,
+        };
+        let playable = solver::playable_words(word_list, &rack);
+        let points = if game.mode == GameMode::Rush {
+            playable.iter().take(MAX_RUSH_WORDS).map(|w| w.points).sum()
+        } else {
+            playable.first().map_or(0, |best| best.points)
+        };
+        solved.push((category, points));
+        points
+    };
+    let mut stats = MatchStats {
+        match_id: game.match_id.clone(),
+        letters: game.letters.clone(),
+        result: game.result_for(chain_id),
+        rounds: game.history.len() as u32,
+        words_played: 0,
+        invalid_words: 0,
+        points: 0,
+        optimal_points: 0,
+        best_word: None,
+        best_word_points: 0,
+        letters_played: 0,
+        invalid_word_rate: 0.0,
+        average_word_length: 0.0,
+        efficiency: 0.0,
+    };
+    for record in &game.history {
+        let (word, points, words) = if is_host {
+            (&record.host_word, record.host_points, &record.host_words)
+        } else {
+            (&record.guest_word, record.guest_points, &record.guest_words)
+        };
+        stats.points += points;
+        stats.optimal_points += optimal(record.category);
+        // Rush rounds list every word; classic rounds have the one word.
+        let played: Vec<(&String, u32)> = if words.is_empty() {
+            Some((word, points))
+                .filter(|(word, _)| !word.is_empty())
+                .into_iter()
+                .collect()
+        } else {
+            words.iter().map(|w| (&w.word, w.points)).collect()
+        };
+        for (word, points) in played {
+            stats.words_played += 1;
+            if points == 0 {
+                stats.invalid_words += 1;
+                continue;
+            }
+            stats.letters_played += word.chars().count() as u32;
+            if points > stats.best_word_points {
+                stats.best_word = Some(word.clone());
+                stats.best_word_points = points;
+            }
+        }
+    }
+    stats.invalid_word_rate = ratio(stats.invalid_words, stats.words_played);
+    stats.average_word_length = ratio(
+        stats.letters_played,
+        stats.words_played - stats.invalid_words,
+    );
+    stats.efficiency = ratio(stats.points, stats.optimal_points);
+    Some(stats)
+}
+
+/// Stats of `chain_id` over `games`, oldest first; games it did not play in are skipped.
+pub fn player_stats(games: &[Game], chain_id: &str, dictionary: &Dictionary) -> PlayerStats {
+    let matches: Vec<MatchStats> = games
+        .iter()
+        .filter_map(|game| match_stats(game, chain_id, dictionary))
+        .collect();
+    let mut stats = PlayerStats {
+        chain_id: chain_id.to_string(),
+        matches_played: 0,
+        wins: 0,
+        losses: 0,
+        draws: 0,
+        words_played: 0,
+        invalid_words: 0,
+        invalid_word_rate: 0.0,
+        average_word_length: 0.0,
+        best_word: None,
+        best_word_points: 0,
+        efficiency: 0.0,
+        current_streak: 0,
+        longest_win_streak: 0,
+        longest_loss_streak: 0,
+        matches: Vec::new(),
+    };
+    let (mut points, mut optimal_points, mut letters_played) = (0, 0, 0);
+    for game in &matches {
+        points += game.points;
+        optimal_points += game.optimal_points;
+        letters_played += game.letters_played;
+        stats.words_played += game.words_played;
+        stats.invalid_words += game.invalid_words;
+        if game.best_word_points > stats.best_word_points {
+            stats.best_word = game.best_word.clone();
+            stats.best_word_points = game.best_word_points;
+        }
+        let Some(result) = game.result else {
+            continue;
+        };
+        stats.matches_played += 1;
+        stats.current_streak = match result {
+            MatchResult::Win => {
+                stats.wins += 1;
+                stats.current_streak.max(0) + 1
+            }
+            MatchResult::Loss => {
+                stats.losses += 1;
+                stats.current_streak.min(0) - 1
+            }
+            MatchResult::Draw => {
+                stats.draws += 1;
+                0
+            }
+        };
+        let streak = stats.current_streak.unsigned_abs();
+        if stats.current_streak > 0 {
+            stats.longest_win_streak = stats.longest_win_streak.max(streak);
+        } else {
+            stats.longest_loss_streak = stats.longest_loss_streak.max(streak);
+        }
+    }
+    stats.invalid_word_rate = ratio(stats.invalid_words, stats.words_played);
+    stats.average_word_length = ratio(letters_played, stats.words_played - stats.invalid_words);
+    stats.efficiency = ratio(points, optimal_points);
+    stats.matches = matches;
+    stats
+}
+
+/// `part / whole`, or 0 when there is nothing to divide.
+fn ratio(part: u32, whole: u32) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
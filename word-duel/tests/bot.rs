@@ -0,0 +1,113 @@
+//! Bots and the dictionary they pick their words from.
+
+use word_duel::{
+    bot::{Bot, Difficulty},
+    dictionary::Dictionary,
+    letters_for_match, validate_word, word_score,
+};
+
+fn small_dictionary() -> Dictionary {
+    Dictionary::new(["rat", "tar", "star", "stare", "aster", "zebra", "ox"])
+}
+
+#[test]
+fn dictionary_lookups_ignore_case() {
+    let dictionary = small_dictionary();
+    assert!(dictionary.contains("Star"));
+    assert!(dictionary.contains(" STARE "));
+    assert!(!dictionary.contains("stars"));
+    assert_eq!(dictionary.len(), 7);
+}
+
+#[test]
+fn bundled_dictionary_covers_every_rack() {
+    let english = Dictionary::english();
+    assert!(english.len() > 10_000);
+    assert!(english.contains("master"));
+    for match_id in 0..8 {
+        let letters = letters_for_match(match_id);
+        let playable = english
+            .words()
+            .filter(|w| validate_word(&letters, w))
+            .count();
+        assert!(playable > 0, "no word for {letters}");
+    }
+}
+
+#[test]
+fn packed_dictionary_matches_the_word_list() {
+    let english = Dictionary::english();
+    let listed = Dictionary::new(include_str!("../data/words.txt").lines());
+    assert_eq!(english.len(), listed.len());
+    assert!(english.words().eq(listed.words()));
+    assert!(english.words().all(|word| english.contains(word)));
+    for junk in ["aaaaah", "hmmm", "mmmmmm", "ooooooh", "http"] {
+        assert!(!english.contains(junk), "{junk}");
+    }
+}
+
+#[test]
+fn greedy_and_optimal_bots_play_the_longest_word() {
+    let dictionary = small_dictionary();
+    for difficulty in [Difficulty::Greedy, Difficulty::Optimal] {
+        let word = Bot::new(&dictionary, difficulty).choose_word("TRASEN", 0);
+        assert_eq!(word.as_deref(), Some("ASTER"), "{difficulty}");
+    }
+}
+
+#[test]
+fn random_bot_plays_valid_words_depending_on_the_seed() {
+    let dictionary = small_dictionary();
+    let bot = Bot::new(&dictionary, Difficulty::Random);
+    let words: Vec<String> = (0..32)
+        .map(|seed| bot.choose_word("TRASEN", seed).unwrap())
+        .collect();
+    assert!(words.iter().all(|w| word_score("TRASEN", w) > 0));
+    assert_eq!(bot.choose_word("TRASEN", 7), bot.choose_word("TRASEN", 7));
+    assert!(words.iter().any(|w| w != &words[0]));
+}
+
+#[test]
+fn bots_pass_when_nothing_is_playable() {
+    let dictionary = small_dictionary();
+    for difficulty in [Difficulty::Random, Difficulty::Greedy, Difficulty::Optimal] {
+        assert_eq!(
+            Bot::new(&dictionary, difficulty).choose_word("QQQXXX", 1),
+            None
+        );
+    }
+}
+
+#[test]
+fn difficulties_parse_from_their_names() {
+    assert_eq!("optimal".parse(), Ok(Difficulty::Optimal));
+    assert_eq!("Easy".parse(), Ok(Difficulty::Random));
+    assert!("impossible".parse::<Difficulty>().is_err());
+}
+
+#[test]
+fn rush_bots_play_several_valid_words() {
+    let dictionary = small_dictionary();
+    let optimal = Bot::new(&dictionary, Difficulty::Optimal).choose_words("TRASEN", 0);
+    assert_eq!(optimal.len(), 5);
+    assert_eq!(optimal[0], "ASTER");
+    let random = Bot::new(&dictionary, Difficulty::Random);
+    for seed in 0..16 {
+        let words = random.choose_words("TRASEN", seed);
+        assert!(words.iter().all(|w| optimal.contains(w)));
+    }
+    assert!(Bot::new(&dictionary, Difficulty::Greedy)
+        .choose_words("QQQXXX", 0)
+        .is_empty());
+}
+
+#[test]
+fn chain_bots_follow_the_last_letter() {
+    let dictionary = Dictionary::new(["table", "eagle", "easel", "ear", "tar"]);
+    let bot = Bot::new(&dictionary, Difficulty::Greedy);
+    let mut chain = vec!["TABLE".to_string(), "EAGLE".to_string()];
+    assert_eq!(bot.choose_chain_word(&chain, 0).as_deref(), Some("EASEL"));
+    chain.push("EASEL".to_string());
+    assert_eq!(bot.choose_chain_word(&chain, 0), None);
+    assert_eq!(bot.choose_chain_word(&[], 0).as_deref(), Some("EAGLE"));
+}
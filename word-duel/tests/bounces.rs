@@ -0,0 +1,426 @@
+//! Every message is sent with tracking; these tests reject each kind on the receiving chain
+//! and check what the sender does with the bounce.
+
+mod common;
+
+use linera_sdk::test::MessageAction;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_join_request_notifies_the_joiner() {
+    let players = common::setup().await;
+    players.create_match(1).await;
+
+    let join = players.join().await;
+    players
+        .host
+        .add_block(|block| {
+            block.with_messages_from_by_action(&join, MessageAction::Reject);
+        })
+        .await;
+    players.guest.handle_received_messages().await;
+
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Join request could not be delivered"
+    );
+    let guest = players
+        .query(&players.guest, "query { game { matchId } }")
+        .await;
+    assert!(guest["game"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_initial_sync_reopens_the_room() {
+    let players = common::setup().await;
+    players.create_match(1).await;
+
+    let join = players.join().await;
+    let accept = players
+        .host
+        .add_block(|block| {
+            block.with_messages_from(&join);
+        })
+        .await;
+    players
+        .guest
+        .add_block(|block| {
+            block.with_messages_from_by_action(&accept, MessageAction::Reject);
+        })
+        .await;
+    players.host.handle_received_messages().await;
+
+    let host = players
+        .query(
+            &players.host,
+            "query { game { status players { chainId } } }",
+        )
+        .await;
+    assert_eq!(host["game"]["status"], "WAITING_FOR_PLAYER");
+    assert_eq!(host["game"]["players"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        players.notification(&players.host).await,
+        "Player could not join"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_host_word_gives_the_turn_back() {
+    let players = common::setup().await;
+    players.start_match(1).await;
+
+    let submit = players.submit(&players.host, "TEA").await;
+    players
+        .guest
+        .add_block(|block| {
+            block.with_messages_from_by_action(&submit, MessageAction::Reject);
+        })
+        .await;
+    players.host.handle_received_messages().await;
+
+    let host = players
+        .query(
+            &players.host,
+            "query { roundPhase myWord game { hostWord } }",
+        )
+        .await;
+    assert_eq!(host["roundPhase"], "HOST_TO_PLAY");
+    assert!(host["myWord"].is_null());
+    assert!(host["game"]["hostWord"].is_null());
+    assert_eq!(
+        players.notification(&players.host).await,
+        "Word not delivered, submit again"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_guest_word_can_be_submitted_again() {
+    let players = common::setup().await;
+    players.start_match(1).await;
+    players.submit(&players.host, "TEA").await;
+    players.guest.handle_received_messages().await;
+
+    let submit = players.submit(&players.guest, "EAT").await;
+    players
+        .host
+        .add_block(|block| {
+            block.with_messages_from_by_action(&submit, MessageAction::Reject);
+        })
+        .await;
+    players.guest.handle_received_messages().await;
+
+    let guest = players
+        .query(&players.guest, "query { myWord roundPhase }")
+        .await;
+    assert!(guest["myWord"].is_null());
+    assert_eq!(guest["roundPhase"], "GUEST_TO_PLAY");
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Word not delivered, submit again"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_round_sync_reports_unreachable_opponent() {
+    let players = common::setup().await;
+    players.start_match(2).await;
+    players.submit(&players.host, "TEA").await;
+    players.guest.handle_received_messages().await;
+    let submit = players.submit(&players.guest, "EAT").await;
+
+    let resolve = players
+        .host
+        .add_block(|block| {
+            block.with_messages_from(&submit);
+        })
+        .await;
+    players
+        .guest
+        .add_block(|block| {
+            block.with_messages_from_by_action(&resolve, MessageAction::Reject);
+        })
+        .await;
+    players.host.handle_received_messages().await;
+
+    let host = players.query(&players.host, "query { round }").await;
+    assert_eq!(host["round"], 2);
+    assert_eq!(
+        players.notification(&players.host).await,
+        "Opponent unreachable"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_leave_notice_keeps_the_forfeit() {
+    let players = common::setup().await;
+    players.start_match(1).await;
+
+    let leave = players
+        .mutate(&players.host, "mutation { leaveMatch }")
+        .await;
+    players
+        .guest
+        .add_block(|block| {
+            block.with_messages_from_by_action(&leave, MessageAction::Reject);
+        })
+        .await;
+    players.host.handle_received_messages().await;
+
+    let host = players
+        .query(&players.host, "query { matchResult game { endReason } }")
+        .await;
+    assert_eq!(host["matchResult"], "LOSS");
+    assert_eq!(host["game"]["endReason"], "FORFEIT");
+    assert_eq!(
+        players.notification(&players.host).await,
+        "Opponent unreachable"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_forwarded_join_request_notifies_the_joiner() {
+    let players = common::setup().await;
+    let room_host = players.validator.new_chain().await;
+    players
+        .mutate(
+            &room_host,
+            r#"mutation { createMatch(hostName: "Room", totalRounds: 1) }"#,
+        )
+        .await;
+    players.host.handle_received_messages().await;
+    let code = players.query(&room_host, "query { roomCode }").await["roomCode"].clone();
+
+    let join = players
+        .mutate(
+            &players.guest,
+            &format!(r#"mutation {{ joinByCode(code: {code}, playerName: "Guest") }}"#),
+        )
+        .await;
+    let forward = players
+        .host
+        .add_block(|block| {
+            block.with_messages_from(&join);
+        })
+        .await;
+    room_host
+        .add_block(|block| {
+            block.with_messages_from_by_action(&forward, MessageAction::Reject);
+        })
+        .await;
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Join rejected: Join request could not be delivered"
+    );
+    assert!(players.notification(&players.host).await.is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_join_by_code_notifies_the_joiner() {
+    let players = common::setup().await;
+
+    let join = players
+        .mutate(
+            &players.guest,
+            r#"mutation { joinByCode(code: "ABCDEF", playerName: "Guest") }"#,
+        )
+        .await;
+    players
+        .host
+        .add_block(|block| {
+            block.with_messages_from_by_action(&join, MessageAction::Reject);
+        })
+        .await;
+    players.guest.handle_received_messages().await;
+
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Join request could not be delivered"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_room_registration_is_reported() {
+    let players = common::setup().await;
+
+    let create = players
+        .mutate(
+            &players.guest,
+            r#"mutation { createMatch(hostName: "Guest", totalRounds: 1) }"#,
+        )
+        .await;
+    players
+        .host
+        .add_block(|block| {
+            block.with_messages_from_by_action(&create, MessageAction::Reject);
+        })
+        .await;
+    players.guest.handle_received_messages().await;
+
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Room code could not be registered"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_seal_can_be_sealed_again() {
+    let players = common::setup().await;
+    players.start_match_with(1, "turnOrder: SIMULTANEOUS").await;
+    let letters = players.letters().await;
+
+    let seal = players.seal(&players.guest, &letters[..3]).await;
+    players
+        .host
+        .add_block(|block| {
+            block.with_messages_from_by_action(&seal, MessageAction::Reject);
+        })
+        .await;
+    players.guest.handle_received_messages().await;
+
+    let guest = players
+        .query(&players.guest, "query { game { guestSeal } }")
+        .await;
+    assert!(guest["game"]["guestSeal"].is_null());
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Seal not delivered, seal your word again"
+    );
+    players.seal(&players.guest, &letters[..3]).await;
+    players.host.handle_received_messages().await;
+    let host = players
+        .query(&players.host, "query { game { guestSeal } }")
+        .await;
+    assert!(host["game"]["guestSeal"].is_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_reveal_can_be_revealed_again() {
+    let players = common::setup().await;
+    players.start_match_with(1, "turnOrder: SIMULTANEOUS").await;
+    let letters = players.letters().await;
+    players.seal(&players.guest, &letters[..3]).await;
+    players.host.handle_received_messages().await;
+    players.seal(&players.host, &letters[..4]).await;
+    players.guest.handle_received_messages().await;
+
+    let reveal = players.reveal(&players.guest, &letters[..3]).await;
+    players
+        .host
+        .add_block(|block| {
+            block.with_messages_from_by_action(&reveal, MessageAction::Reject);
+        })
+        .await;
+    players.guest.handle_received_messages().await;
+
+    let guest = players.query(&players.guest, "query { myWord }").await;
+    assert!(guest["myWord"].is_null());
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Word not delivered, reveal it again"
+    );
+    players.reveal(&players.guest, &letters[..3]).await;
+    players.host.handle_received_messages().await;
+    players.reveal(&players.host, &letters[..4]).await;
+    let host = players
+        .query(
+            &players.host,
+            "query { game { status history { guestWord } } }",
+        )
+        .await;
+    assert_eq!(host["game"]["status"], "ENDED");
+    assert_eq!(host["game"]["history"][0]["guestWord"], &letters[..3]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_invite_is_reported() {
+    let players = common::setup().await;
+
+    let invite = players
+        .mutate(
+            &players.host,
+            &format!(
+                r#"mutation {{ challengePlayer(chainId: "{}", rules: {{ totalRounds: 1 }}) }}"#,
+                players.guest.id()
+            ),
+        )
+        .await;
+    players
+        .guest
+        .add_block(|block| {
+            block.with_messages_from_by_action(&invite, MessageAction::Reject);
+        })
+        .await;
+    players.host.handle_received_messages().await;
+
+    assert_eq!(
+        players.notification(&players.host).await,
+        "Invite could not be delivered"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_acceptance_frees_the_invitee_to_accept_again() {
+    let players = common::setup().await;
+    let invite = format!(
+        r#"mutation {{ challengePlayer(chainId: "{}", rules: {{ totalRounds: 1 }}) }}"#,
+        players.guest.id()
+    );
+    let accept = format!(
+        r#"mutation {{ acceptChallenge(chainId: "{}") }}"#,
+        players.host.id()
+    );
+    players.mutate(&players.host, &invite).await;
+    players.guest.handle_received_messages().await;
+
+    let accepted = players.mutate(&players.guest, &accept).await;
+    players
+        .host
+        .add_block(|block| {
+            block.with_messages_from_by_action(&accepted, MessageAction::Reject);
+        })
+        .await;
+    players.guest.handle_received_messages().await;
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Challenger could not be reached"
+    );
+
+    players.mutate(&players.host, &invite).await;
+    players.guest.handle_received_messages().await;
+    players.mutate(&players.guest, &accept).await;
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+    let guest = players
+        .query(&players.guest, "query { game { hostChainId status } }")
+        .await;
+    assert_eq!(guest["game"]["hostChainId"], players.host.id().to_string());
+    assert_eq!(guest["game"]["status"], "ACTIVE");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_chat_message_is_reported() {
+    let players = common::setup().await;
+    players.start_match(1).await;
+
+    let chat = players
+        .mutate(&players.host, r#"mutation { sendChat(text: "hello") }"#)
+        .await;
+    players
+        .guest
+        .add_block(|block| {
+            block.with_messages_from_by_action(&chat, MessageAction::Reject);
+        })
+        .await;
+    players.host.handle_received_messages().await;
+
+    assert_eq!(
+        players.notification(&players.host).await,
+        "Chat message could not be delivered"
+    );
+    let guest = players
+        .query(&players.guest, "query { chat { text } }")
+        .await;
+    assert_eq!(guest["chat"], serde_json::json!([]));
+}
@@ -0,0 +1,54 @@
+//! Chat messages and the limits on sending them.
+
+use word_duel::chat::{
+    self, ChatError, ChatMessage, CHAT_RATE_LIMIT, CHAT_RATE_WINDOW_SECS, MAX_CHAT_LEN,
+    MAX_CHAT_MESSAGES,
+};
+
+fn message(sender: &str, timestamp: u64) -> ChatMessage {
+    ChatMessage {
+        sender_chain_id: sender.to_string(),
+        sender_name: sender.to_string(),
+        text: "gg".to_string(),
+        reaction: false,
+        timestamp,
+    }
+}
+
+#[test]
+fn messages_are_trimmed_and_kept_to_one_line() {
+    assert_eq!(
+        chat::normalized_text("  well\nplayed "),
+        Ok("well played".to_string())
+    );
+    assert_eq!(chat::normalized_text(" \n "), Err(ChatError::Empty));
+    let longest = "é".repeat(MAX_CHAT_LEN);
+    assert_eq!(chat::normalized_text(&longest), Ok(longest.clone()));
+    assert_eq!(
+        chat::normalized_text(&format!("{longest}!")),
+        Err(ChatError::TooLong)
+    );
+    assert_eq!(chat::reaction(" 🔥"), Ok("🔥".to_string()));
+    assert_eq!(chat::reaction("🐢"), Err(ChatError::UnknownReaction));
+}
+
+#[test]
+fn senders_are_rate_limited_and_chats_bounded() {
+    let window = CHAT_RATE_WINDOW_SECS * 1_000_000;
+    let mut messages: Vec<_> = (0..CHAT_RATE_LIMIT as u64)
+        .map(|n| message("a", n))
+        .collect();
+    assert_eq!(
+        chat::check_send(&messages, "a", 10),
+        Err(ChatError::TooFast)
+    );
+    // The opponent's messages do not count against a player.
+    assert_eq!(chat::check_send(&messages, "b", 10), Ok(()));
+    assert_eq!(chat::check_send(&messages, "a", window + 1), Ok(()));
+
+    messages.resize(MAX_CHAT_MESSAGES, message("b", 0));
+    assert_eq!(
+        chat::check_send(&messages, "a", 10 * window),
+        Err(ChatError::Full)
+    );
+}
@@ -0,0 +1,176 @@
+//! Shared setup for the integration tests: one validator, the application created on the
+//! host chain (which is also the lobby) and a second chain for the guest.
+
+#![allow(dead_code)]
+
+use linera_chain::types::ConfirmedBlockCertificate;
+use linera_sdk::{
+    linera_base_types::{ApplicationId, TimeDelta, Timestamp},
+    test::{ActiveChain, TestValidator, WorkerError},
+};
+use word_duel::{
+    InstantiationArgument, Operation, WordDuelAbi, WordDuelParameters, TURN_TIMEOUT_SECS,
+};
+
+/// The salt every test seals its words with.
+const SALT: &str = "0123456789abcdef";
+
+pub struct Players {
+    pub validator: TestValidator,
+    pub app: ApplicationId<WordDuelAbi>,
+    pub host: ActiveChain,
+    pub guest: ActiveChain,
+}
+
+pub async fn setup() -> Players {
+    let (validator, app, host) = TestValidator::with_current_application::<WordDuelAbi, _, _>(
+        WordDuelParameters,
+        InstantiationArgument,
+    )
+    .await;
+    let guest = validator.new_chain().await;
+    Players {
+        validator,
+        app,
+        host,
+        guest,
+    }
+}
+
+impl Players {
+    pub async fn query(&self, chain: &ActiveChain, query: &str) -> serde_json::Value {
+        chain.graphql_query(self.app, query).await.response
+    }
+
+    pub async fn mutate(&self, chain: &ActiveChain, mutation: &str) -> ConfirmedBlockCertificate {
+        chain.graphql_mutation(self.app, mutation).await
+    }
+
+    pub async fn notification(&self, chain: &ActiveChain) -> serde_json::Value {
+        self.query(chain, "query { lastNotification }").await["lastNotification"].clone()
+    }
+
+    /// Creates a match on the host chain; returns the certificate of the creating block.
+    pub async fn create_match(&self, total_rounds: u32) -> ConfirmedBlockCertificate {
+        self.create_match_with(total_rounds, "").await
+    }
+
+    /// Like `create_match`, passing `options` as extra `createMatch` arguments.
+    pub async fn create_match_with(
+        &self,
+        total_rounds: u32,
+        options: &str,
+    ) -> ConfirmedBlockCertificate {
+        let certificate = self
+            .mutate(
+                &self.host,
+                &format!(
+                    r#"mutation {{ createMatch(hostName: "Host", totalRounds: {total_rounds} {options}) }}"#
+                ),
+            )
+            .await;
+        self.host.handle_received_messages().await;
+        certificate
+    }
+
+    /// Sends the guest's join request; returns the certificate of the requesting block.
+    pub async fn join(&self) -> ConfirmedBlockCertificate {
+        self.mutate(
+            &self.guest,
+            &format!(
+                r#"mutation {{ joinMatch(hostChainId: "{}", playerName: "Guest") }}"#,
+                self.host.id()
+            ),
+        )
+        .await
+    }
+
+    /// Creates a match and lets the guest join it, delivering every message.
+    pub async fn start_match(&self, total_rounds: u32) {
+        self.start_match_with(total_rounds, "").await;
+    }
+
+    /// Like `start_match`, passing `options` as extra `createMatch` arguments.
+    pub async fn start_match_with(&self, total_rounds: u32, options: &str) {
+        self.create_match_with(total_rounds, options).await;
+        self.join().await;
+        self.host.handle_received_messages().await;
+        self.guest.handle_received_messages().await;
+    }
+
+    pub async fn submit(&self, chain: &ActiveChain, word: &str) -> ConfirmedBlockCertificate {
+        self.mutate(
+            chain,
+            &format!(r#"mutation {{ submitWord(word: "{word}") }}"#),
+        )
+        .await
+    }
+
+    /// Seals `word` for a simultaneous round; `reveal` sends it once both seals are in.
+    pub async fn seal(&self, chain: &ActiveChain, word: &str) -> ConfirmedBlockCertificate {
+        self.mutate(
+            chain,
+            &format!(r#"mutation {{ sealWord(word: "{word}", salt: "{SALT}") }}"#),
+        )
+        .await
+    }
+
+    pub async fn reveal(&self, chain: &ActiveChain, word: &str) -> ConfirmedBlockCertificate {
+        self.mutate(
+            chain,
+            &format!(r#"mutation {{ revealWord(word: "{word}", salt: "{SALT}") }}"#),
+        )
+        .await
+    }
+
+    /// Plays a full round: the host's word, then the guest's, with every message delivered.
+    pub async fn play_round(&self, host_word: &str, guest_word: &str) {
+        self.submit(&self.host, host_word).await;
+        self.guest.handle_received_messages().await;
+        self.submit(&self.guest, guest_word).await;
+        self.host.handle_received_messages().await;
+        self.guest.handle_received_messages().await;
+    }
+
+    pub async fn letters(&self) -> String {
+        self.query(&self.host, "query { letters }").await["letters"]
+            .as_str()
+            .expect("No letters")
+            .to_string()
+    }
+
+    /// Moves the validator clock past the current turn's deadline and returns the new time.
+    pub fn time_out(&self) -> Timestamp {
+        let clock = self.validator.clock();
+        clock.add(TimeDelta::from_secs(TURN_TIMEOUT_SECS + 1));
+        clock.current_time()
+    }
+
+    /// Executes `operation` in a block stamped `at`; blocks default to time zero otherwise.
+    pub async fn operate_at(
+        &self,
+        chain: &ActiveChain,
+        at: Timestamp,
+        operation: Operation,
+    ) -> Result<ConfirmedBlockCertificate, WorkerError> {
+        chain
+            .try_add_block(|block| {
+                block.with_timestamp(at).with_operation(self.app, operation);
+            })
+            .await
+    }
+
+    /// Delivers the messages of `certificate` to `chain` in a block stamped `at`.
+    pub async fn deliver_at(
+        &self,
+        chain: &ActiveChain,
+        at: Timestamp,
+        certificate: &ConfirmedBlockCertificate,
+    ) -> ConfirmedBlockCertificate {
+        chain
+            .add_block(|block| {
+                block.with_timestamp(at).with_messages_from(certificate);
+            })
+            .await
+    }
+}
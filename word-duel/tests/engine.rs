@@ -0,0 +1,1215 @@
+//! The game rules on their own, without chains or storage.
+
+use word_duel::{
+    bonus::{Bonus, BonusKind},
+    challenge::{Challenge, CHALLENGE_PENALTY, CHALLENGE_WINDOW_SECS},
+    engine::{Effect, EngineError, Event, GameEngine, MAX_HISTORY},
+    handicap::{Handicap, HandicapMode},
+    seal, solver, validate_word, Category, CrossChainMessage, EndReason, Game, GameMode,
+    LetterSource, MatchStatus, PlayerInfo, RoundPhase, TurnOrder, Verdict, MAX_RUSH_WORDS,
+    RUSH_WINDOW_SECS, TURN_TIMEOUT_SECS,
+};
+
+const START: u64 = 1_000_000;
+const TIMEOUT: u64 = TURN_TIMEOUT_SECS * 1_000_000;
+
+fn chain_id(n: u8) -> String {
+    format!("{n:064x}")
+}
+
+fn host() -> String {
+    chain_id(1)
+}
+
+fn guest() -> String {
+    chain_id(2)
+}
+
+fn player(chain_id: String) -> PlayerInfo {
+    PlayerInfo {
+        chain_id,
+        name: "player".to_string(),
+        rating: word_duel::INITIAL_RATING,
+        handicap: Handicap::default(),
+        profile: None,
+    }
+}
+
+fn waiting_game(total_rounds: u32) -> Game {
+    Game {
+        match_id: START.to_string(),
+        host_chain_id: host(),
+        room_code: "ABCDEF".to_string(),
+        status: MatchStatus::WaitingForPlayer,
+        players: vec![player(host())],
+        letters: "ATRESN".to_string(),
+        letter_source: LetterSource::Pack,
+        letter_pack: Some("classic".to_string()),
+        mode: GameMode::Classic,
+        turn_order: TurnOrder::Alternating,
+        chain: Vec::new(),
+        categories: false,
+        category: None,
+        bonuses: false,
+        handicap_mode: HandicapMode::Off,
+        guest_handicap: Handicap::default(),
+        total_rounds,
+        current_round: 1,
+        host_score: 0,
+        guest_score: 0,
+        round_phase: RoundPhase::HostToPlay,
+        host_word: None,
+        guest_word: None,
+        host_seal: None,
+        guest_seal: None,
+        turn_deadline: 0,
+        turn_started: 0,
+        host_elapsed_micros: None,
+        guest_elapsed_micros: None,
+        winner_chain_id: None,
+        end_reason: None,
+        history: Vec::new(),
+        version: 0,
+    }
+}
+
+/// The host's engine for a match the guest has just joined.
+fn started(total_rounds: u32) -> GameEngine {
+    let mut engine = GameEngine::new(waiting_game(total_rounds), host(), START);
+    engine
+        .apply(Event::Join {
+            player: player(guest()),
+        })
+        .unwrap();
+    engine
+}
+
+fn submit(engine: &mut GameEngine, chain_id: String, word: &str) -> Vec<Effect> {
+    engine
+        .apply(Event::Submit {
+            player_chain_id: chain_id,
+            word: word.to_string(),
+        })
+        .unwrap()
+}
+
+fn sent(effects: &[Effect]) -> Vec<&CrossChainMessage> {
+    effects
+        .iter()
+        .filter_map(|effect| match effect {
+            Effect::Send { message, .. } => Some(message.as_ref()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn ended(effects: &[Effect]) -> bool {
+    effects.iter().any(|e| matches!(e, Effect::MatchEnded))
+}
+
+#[test]
+fn join_starts_the_match_and_syncs_the_guest() {
+    let mut engine = GameEngine::new(waiting_game(3), host(), START);
+    let effects = engine
+        .apply(Event::Join {
+            player: player(guest()),
+        })
+        .unwrap();
+
+    let game = engine.game();
+    assert_eq!(game.status, MatchStatus::Active);
+    assert_eq!(game.players.len(), 2);
+    assert_eq!(game.version, 1);
+    assert_eq!(game.turn_deadline, START + TIMEOUT);
+    assert!(matches!(
+        sent(&effects)[..],
+        [CrossChainMessage::InitialStateSync { .. }]
+    ));
+
+    let again = engine.apply(Event::Join {
+        player: player(chain_id(3)),
+    });
+    assert_eq!(again.unwrap_err(), EngineError::MatchFull);
+}
+
+#[test]
+fn a_round_is_scored_once_both_words_are_in() {
+    let mut engine = started(2);
+
+    let effects = submit(&mut engine, host(), " rate ");
+    assert_eq!(engine.game().host_word.as_deref(), Some("RATE"));
+    assert_eq!(engine.game().round_phase, RoundPhase::GuestToPlay);
+    assert!(matches!(
+        sent(&effects)[..],
+        [CrossChainMessage::WordSubmitted { round: 1, .. }]
+    ));
+
+    let effects = submit(&mut engine, guest(), "STERN");
+    let game = engine.game();
+    assert_eq!(game.current_round, 2);
+    assert_eq!(game.round_phase, RoundPhase::HostToPlay);
+    assert_eq!((game.host_score, game.guest_score), (4, 5));
+    assert_eq!(game.history[0].host_word, "RATE");
+    assert_eq!(game.history[0].guest_word, "STERN");
+    assert!(game.host_word.is_none());
+    assert!(!ended(&effects));
+    assert!(matches!(
+        sent(&effects)[..],
+        [CrossChainMessage::RoundSync { .. }]
+    ));
+}
+
+#[test]
+fn the_last_round_picks_the_winner() {
+    let mut engine = started(1);
+    submit(&mut engine, host(), "STERN");
+    let effects = submit(&mut engine, guest(), "QQQQ");
+
+    let game = engine.game();
+    assert!(ended(&effects));
+    assert_eq!(game.status, MatchStatus::Ended);
+    assert_eq!(game.end_reason, Some(EndReason::Completed));
+    assert_eq!(game.winner_chain_id, Some(host()));
+    assert_eq!(game.history[0].guest_points, 0);
+}
+
+#[test]
+fn equal_scores_are_a_draw() {
+    let mut engine = started(1);
+    submit(&mut engine, host(), "RAT");
+    submit(&mut engine, guest(), "TAR");
+    assert_eq!(engine.game().winner_chain_id.as_deref(), Some(""));
+}
+
+#[test]
+fn words_out_of_turn_or_too_short_are_rejected() {
+    let mut engine = started(1);
+    let before = engine.game().version;
+
+    let guest_first = engine.apply(Event::Submit {
+        player_chain_id: guest(),
+        word: "RAT".to_string(),
+    });
+    assert_eq!(guest_first.unwrap_err(), EngineError::NotYourTurn);
+
+    let too_short = engine.apply(Event::Submit {
+        player_chain_id: host(),
+        word: "AT".to_string(),
+    });
+    assert_eq!(too_short.unwrap_err(), EngineError::WordTooShort);
+
+    let stranger = engine.apply(Event::Submit {
+        player_chain_id: chain_id(3),
+        word: "RAT".to_string(),
+    });
+    assert_eq!(stranger.unwrap_err(), EngineError::NotAPlayer);
+    assert_eq!(engine.game().version, before);
+}
+
+#[test]
+fn the_guest_only_forwards_its_word_to_the_host() {
+    let mut host_engine = started(1);
+    submit(&mut host_engine, host(), "RAT");
+    let mut engine = GameEngine::new(host_engine.into_game(), guest(), START);
+    let before = engine.game().clone();
+
+    let effects = submit(&mut engine, guest(), "tar");
+
+    assert_eq!(engine.game().version, before.version);
+    assert!(engine.game().guest_word.is_none());
+    match sent(&effects)[..] {
+        [CrossChainMessage::WordSubmitted {
+            round,
+            word,
+            version,
+        }] => {
+            assert_eq!(
+                (*round, word.as_str(), *version),
+                (1, "TAR", before.version)
+            );
+        }
+        _ => panic!("expected a WordSubmitted message, got {effects:?}"),
+    }
+}
+
+#[test]
+fn the_guest_takes_the_hosts_word_once_and_asks_for_a_sync_after_a_gap() {
+    let mut engine = GameEngine::new(started(2).into_game(), guest(), START);
+    let version = engine.game().version;
+    let host_word = |version| Event::HostWord {
+        round: 1,
+        word: "RAT".to_string(),
+        version,
+    };
+
+    let effects = engine.apply(host_word(version + 2)).unwrap();
+    assert!(matches!(
+        sent(&effects)[..],
+        [CrossChainMessage::RequestSync { version: v }] if *v == version
+    ));
+    assert_eq!(engine.game().round_phase, RoundPhase::HostToPlay);
+
+    let effects = engine.apply(host_word(version + 1)).unwrap();
+    assert!(effects.is_empty());
+    let game = engine.game();
+    assert_eq!(game.round_phase, RoundPhase::GuestToPlay);
+    assert_eq!(game.host_word.as_deref(), Some("RAT"));
+    assert_eq!(game.version, version + 1);
+    assert_eq!(game.turn_deadline, START + TIMEOUT);
+
+    let again = engine.apply(host_word(version + 1));
+    assert_eq!(again.unwrap_err(), EngineError::Outdated);
+}
+
+#[test]
+fn the_guest_adopts_newer_host_copies_and_reports_the_result() {
+    let mut host_engine = started(1);
+    let mut engine = GameEngine::new(host_engine.game().clone(), guest(), START);
+    let sync = |game: &Game, authoritative| Event::Sync {
+        game: Box::new(game.seen_by(&guest())),
+        authoritative,
+    };
+
+    let same = engine.apply(sync(host_engine.game(), false));
+    assert_eq!(same.unwrap_err(), EngineError::Outdated);
+    assert!(engine
+        .apply(sync(host_engine.game(), true))
+        .unwrap()
+        .is_empty());
+    let mut other = host_engine.game().clone();
+    other.match_id = "another".to_string();
+    other.version += 1;
+    let other = engine.apply(sync(&other, false));
+    assert_eq!(other.unwrap_err(), EngineError::OtherMatch);
+
+    submit(&mut host_engine, host(), "RATE");
+    submit(&mut host_engine, guest(), "SATRN");
+    let effects = engine.apply(sync(host_engine.game(), false)).unwrap();
+    assert!(ended(&effects));
+    assert_eq!(engine.game().winner_chain_id, Some(guest()));
+
+    challenge(&mut host_engine, host(), 1).unwrap();
+    let effects = engine.apply(sync(host_engine.game(), false)).unwrap();
+    assert!(effects.iter().any(|e| matches!(e, Effect::ResultRevised)));
+    assert_eq!(engine.game().winner_chain_id, Some(host()));
+}
+
+#[test]
+fn a_newer_copy_keeps_a_seal_still_on_its_way_to_the_host() {
+    let host_engine = started_simultaneous(2);
+    let mut engine = GameEngine::new(host_engine.game().clone(), guest(), START);
+    seal_word(&mut engine, guest(), "STAR");
+    let seal = engine.game().guest_seal;
+    assert!(seal.is_some());
+
+    let mut newer = host_engine.game().clone();
+    newer.version += 1;
+    engine
+        .apply(Event::Sync {
+            game: Box::new(newer.clone()),
+            authoritative: false,
+        })
+        .unwrap();
+    assert_eq!(engine.game().guest_seal, seal);
+
+    newer.version += 1;
+    engine
+        .apply(Event::Sync {
+            game: Box::new(newer),
+            authoritative: true,
+        })
+        .unwrap();
+    assert_eq!(engine.game().guest_seal, None);
+}
+
+#[test]
+fn timeouts_need_an_expired_deadline() {
+    let mut engine = started(1);
+    submit(&mut engine, host(), "RAT");
+    let early = engine.apply(Event::Timeout {
+        claimant_chain_id: host(),
+    });
+    assert_eq!(early.unwrap_err(), EngineError::TurnNotExpired);
+
+    let game = engine.into_game();
+    let mut engine = GameEngine::new(game, host(), START + TIMEOUT);
+    let effects = engine
+        .apply(Event::Timeout {
+            claimant_chain_id: host(),
+        })
+        .unwrap();
+    assert!(ended(&effects));
+    assert_eq!(engine.game().history[0].guest_word, "");
+    assert_eq!(engine.game().winner_chain_id, Some(host()));
+}
+
+#[test]
+fn a_timed_out_host_plays_an_empty_word() {
+    let game = started(1).into_game();
+    let mut engine = GameEngine::new(game, host(), START + TIMEOUT);
+
+    let wrong_side = engine.apply(Event::Timeout {
+        claimant_chain_id: host(),
+    });
+    assert_eq!(wrong_side.unwrap_err(), EngineError::NotOpponentsTurn);
+
+    let effects = engine
+        .apply(Event::Timeout {
+            claimant_chain_id: guest(),
+        })
+        .unwrap();
+    assert_eq!(engine.game().host_word.as_deref(), Some(""));
+    assert_eq!(engine.game().round_phase, RoundPhase::GuestToPlay);
+    assert_eq!(engine.game().turn_deadline, START + 2 * TIMEOUT);
+    assert!(effects
+        .iter()
+        .any(|e| matches!(e, Effect::Notify(n) if n == "Your turn timed out")));
+}
+
+#[test]
+fn leaving_forfeits_and_only_the_leaver_sends_the_notice() {
+    let game = started(3).into_game();
+
+    let mut leaver = GameEngine::new(game.clone(), guest(), START);
+    let effects = leaver
+        .apply(Event::Leave {
+            player_chain_id: guest(),
+        })
+        .unwrap();
+    assert!(ended(&effects));
+    assert!(matches!(
+        sent(&effects)[..],
+        [CrossChainMessage::LeaveNotice { .. }]
+    ));
+
+    let mut remaining = GameEngine::new(game, host(), START);
+    let effects = remaining
+        .apply(Event::Leave {
+            player_chain_id: guest(),
+        })
+        .unwrap();
+    assert!(sent(&effects).is_empty());
+    assert_eq!(remaining.game().end_reason, Some(EndReason::Forfeit));
+    assert_eq!(remaining.game().winner_chain_id, Some(host()));
+    assert_eq!(
+        serde_json::to_value(remaining.game()).unwrap(),
+        serde_json::to_value(leaver.game()).unwrap()
+    );
+
+    let twice = remaining.apply(Event::Leave {
+        player_chain_id: guest(),
+    });
+    assert_eq!(twice.unwrap_err(), EngineError::MatchNotReady);
+}
+
+#[test]
+fn kicking_before_the_first_round_clears_the_seals_for_the_next_guest() {
+    let mut engine = started_simultaneous(2);
+    seal_word(&mut engine, host(), "RATE");
+    seal_word(&mut engine, guest(), "STAR");
+
+    let effects = engine.apply(Event::Kick).unwrap();
+    assert!(matches!(sent(&effects)[..], [CrossChainMessage::Kicked]));
+    assert!(effects.iter().any(|e| matches!(e, Effect::RoomReopened)));
+    let game = engine.game();
+    assert_eq!(game.status, MatchStatus::WaitingForPlayer);
+    assert_eq!(game.players.len(), 1);
+    assert_eq!(game.round_phase, RoundPhase::BothToPlay);
+    assert_eq!((game.host_seal, game.guest_seal), (None, None));
+    assert_eq!(game.turn_deadline, 0);
+
+    engine
+        .apply(Event::Join {
+            player: player(chain_id(3)),
+        })
+        .unwrap();
+    seal_word(&mut engine, chain_id(3), "STAR");
+    seal_word(&mut engine, host(), "RATE");
+    reveal(&mut engine, host(), "RATE");
+    reveal(&mut engine, chain_id(3), "STAR");
+    assert_eq!(engine.game().history.len(), 1);
+}
+
+#[test]
+fn only_a_guest_who_has_not_played_a_round_can_be_removed() {
+    let mut waiting = GameEngine::new(waiting_game(2), host(), START);
+    assert_eq!(
+        waiting.apply(Event::Kick).unwrap_err(),
+        EngineError::NoGuest
+    );
+
+    let mut engine = started(2);
+    let other = engine.apply(Event::JoinFailed {
+        match_id: "another".to_string(),
+    });
+    assert_eq!(other.unwrap_err(), EngineError::OtherMatch);
+
+    submit(&mut engine, host(), "RATE");
+    submit(&mut engine, guest(), "STAR");
+    assert_eq!(
+        engine.apply(Event::Kick).unwrap_err(),
+        EngineError::RoundsPlayed
+    );
+    let match_id = engine.game().match_id.clone();
+    assert_eq!(
+        engine.apply(Event::JoinFailed { match_id }).unwrap_err(),
+        EngineError::RoundsPlayed
+    );
+}
+
+#[test]
+fn an_undelivered_match_waits_for_a_player_without_kicking_anyone() {
+    let mut engine = started(2);
+    let match_id = engine.game().match_id.clone();
+
+    let effects = engine.apply(Event::JoinFailed { match_id }).unwrap();
+    assert!(sent(&effects).is_empty());
+    assert!(effects.iter().any(|e| matches!(e, Effect::RoomReopened)));
+    assert_eq!(engine.game().status, MatchStatus::WaitingForPlayer);
+    assert_eq!(engine.game().round_phase, RoundPhase::HostToPlay);
+}
+
+#[test]
+fn an_undelivered_host_word_hands_the_turn_back_to_the_host() {
+    let mut engine = started(2);
+    submit(&mut engine, host(), "RAT");
+    let version = engine.game().version;
+    let mut later = GameEngine::new(engine.into_game(), host(), START + 5_000_000);
+
+    let stale = later.apply(Event::SubmitBounced { round: 2 });
+    assert_eq!(stale.unwrap_err(), EngineError::Outdated);
+    let effects = later.apply(Event::SubmitBounced { round: 1 }).unwrap();
+    assert!(matches!(effects[..], [Effect::Notify(_)]));
+    let game = later.game();
+    assert_eq!(game.round_phase, RoundPhase::HostToPlay);
+    assert_eq!(
+        (game.host_word.as_ref(), game.host_elapsed_micros),
+        (None, None)
+    );
+    assert_eq!(game.version, version + 1);
+    assert_eq!(game.turn_deadline, START + 5_000_000 + TIMEOUT);
+
+    let again = later.apply(Event::SubmitBounced { round: 1 });
+    assert_eq!(again.unwrap_err(), EngineError::Outdated);
+    submit(&mut later, host(), "TAR");
+}
+
+#[test]
+fn an_undelivered_seal_or_reveal_can_be_played_again() {
+    let mut engine = GameEngine::new(started_simultaneous(2).into_game(), guest(), START);
+    seal_word(&mut engine, guest(), "STAR");
+    let version = engine.game().version;
+
+    let stale = engine.apply(Event::SealBounced { round: 2 });
+    assert_eq!(stale.unwrap_err(), EngineError::Outdated);
+    let effects = engine.apply(Event::SealBounced { round: 1 }).unwrap();
+    assert!(matches!(effects[..], [Effect::Notify(_)]));
+    assert_eq!(engine.game().guest_seal, None);
+    assert_eq!(engine.game().version, version);
+    seal_word(&mut engine, guest(), "STAR");
+
+    let stale = engine.apply(Event::RevealBounced { round: 2 });
+    assert_eq!(stale.unwrap_err(), EngineError::Outdated);
+    let effects = engine.apply(Event::RevealBounced { round: 1 }).unwrap();
+    assert!(matches!(effects[..], [Effect::Notify(_)]));
+}
+
+#[test]
+fn history_keeps_the_latest_rounds() {
+    let rounds = MAX_HISTORY as u32 + 5;
+    let mut engine = started(rounds);
+    for _ in 0..rounds {
+        submit(&mut engine, host(), "RAT");
+        submit(&mut engine, guest(), "STAR");
+    }
+    let game = engine.game();
+    assert_eq!(game.status, MatchStatus::Ended);
+    assert_eq!(game.history.len(), MAX_HISTORY);
+    assert_eq!(game.history[0].round, 6);
+    assert_eq!(game.guest_score, 4 * rounds);
+}
+
+/// The host's engine for a simultaneous match the guest has just joined.
+fn started_simultaneous(total_rounds: u32) -> GameEngine {
+    let mut game = waiting_game(total_rounds);
+    game.turn_order = TurnOrder::Simultaneous;
+    let mut engine = GameEngine::new(game, host(), START);
+    engine
+        .apply(Event::Join {
+            player: player(guest()),
+        })
+        .unwrap();
+    engine
+}
+
+const SALT: &str = "0123456789abcdef";
+
+fn seal_word(engine: &mut GameEngine, chain_id: String, word: &str) -> Vec<Effect> {
+    engine
+        .apply(Event::Seal {
+            player_chain_id: chain_id,
+            seal: seal::seal(word, SALT),
+        })
+        .unwrap()
+}
+
+fn reveal(engine: &mut GameEngine, chain_id: String, word: &str) -> Vec<Effect> {
+    engine
+        .apply(Event::Reveal {
+            player_chain_id: chain_id,
+            word: word.to_string(),
+            salt: SALT.to_string(),
+        })
+        .unwrap()
+}
+
+#[test]
+fn simultaneous_rounds_take_both_words_in_either_order() {
+    let mut engine = started_simultaneous(2);
+    assert_eq!(engine.game().round_phase, RoundPhase::BothToPlay);
+    let plain = engine.apply(Event::Submit {
+        player_chain_id: guest(),
+        word: "STERN".to_string(),
+    });
+    assert_eq!(plain.unwrap_err(), EngineError::SealFirst);
+
+    let effects = seal_word(&mut engine, guest(), "STERN");
+    assert!(matches!(
+        sent(&effects)[..],
+        [CrossChainMessage::RoundSync { .. }]
+    ));
+    assert!(engine.game().guest_seal.is_some());
+    assert!(engine.game().guest_word.is_none());
+    let again = engine.apply(Event::Seal {
+        player_chain_id: guest(),
+        seal: seal::seal("RAT", SALT),
+    });
+    assert_eq!(again.unwrap_err(), EngineError::AlreadySubmitted);
+    let early = engine.apply(Event::Reveal {
+        player_chain_id: guest(),
+        word: "STERN".to_string(),
+        salt: SALT.to_string(),
+    });
+    assert_eq!(early.unwrap_err(), EngineError::NotSealed);
+
+    seal_word(&mut engine, host(), "RATE");
+    reveal(&mut engine, guest(), "STERN");
+    assert_eq!(engine.game().guest_word.as_deref(), Some("STERN"));
+    assert_eq!(engine.game().round_phase, RoundPhase::BothToPlay);
+
+    let effects = reveal(&mut engine, host(), "RATE");
+    let game = engine.game();
+    assert_eq!((game.host_score, game.guest_score), (4, 5));
+    assert_eq!(game.round_phase, RoundPhase::BothToPlay);
+    assert!(game.guest_word.is_none() && game.host_word.is_none());
+    assert!(game.guest_seal.is_none() && game.host_seal.is_none());
+    assert!(matches!(
+        sent(&effects)[..],
+        [CrossChainMessage::RoundSync { .. }]
+    ));
+
+    seal_word(&mut engine, host(), "STAR");
+    seal_word(&mut engine, guest(), "TAR");
+    let effects = reveal(&mut engine, host(), "STAR");
+    assert!(sent(&effects).is_empty());
+    let effects = reveal(&mut engine, guest(), "TAR");
+    assert!(ended(&effects));
+    assert_eq!(engine.game().history[1].host_word, "STAR");
+    assert_eq!(engine.game().history[1].guest_word, "TAR");
+}
+
+#[test]
+fn simultaneous_words_stay_hidden_from_the_opponent() {
+    let mut engine = started_simultaneous(1);
+    seal_word(&mut engine, host(), "STAR");
+
+    let seen = engine.game().seen_by(&guest());
+    assert!(seen.host_word.is_none());
+    assert_eq!(seen.host_seal, Some(seal::seal("STAR", SALT)));
+
+    // The guest seals against its copy and sends only the hash; the word follows once both
+    // seals are in.
+    let mut guest_engine = GameEngine::new(seen, guest(), START);
+    let effects = seal_word(&mut guest_engine, guest(), "RAT");
+    assert!(matches!(
+        sent(&effects)[..],
+        [CrossChainMessage::WordSealed { round: 1, .. }]
+    ));
+    let effects = reveal(&mut guest_engine, guest(), "RAT");
+    assert!(matches!(
+        sent(&effects)[..],
+        [CrossChainMessage::WordRevealed { round: 1, .. }]
+    ));
+}
+
+#[test]
+fn revealed_words_must_match_their_seal() {
+    let mut engine = started_simultaneous(1);
+    seal_word(&mut engine, host(), "STAR");
+    seal_word(&mut engine, guest(), "RAT");
+
+    let other_word = engine.apply(Event::Reveal {
+        player_chain_id: guest(),
+        word: "STERN".to_string(),
+        salt: SALT.to_string(),
+    });
+    assert_eq!(other_word.unwrap_err(), EngineError::SealMismatch);
+    let other_salt = engine.apply(Event::Reveal {
+        player_chain_id: guest(),
+        word: "RAT".to_string(),
+        salt: "fedcba9876543210".to_string(),
+    });
+    assert_eq!(other_salt.unwrap_err(), EngineError::SealMismatch);
+    assert!(engine.game().guest_word.is_none());
+
+    reveal(&mut engine, guest(), "RAT");
+    reveal(&mut engine, host(), "STAR");
+    assert_eq!(engine.game().history[0].guest_word, "RAT");
+}
+
+#[test]
+fn a_lone_seal_is_revealed_and_scored_after_the_deadline() {
+    let mut engine = started_simultaneous(1);
+    seal_word(&mut engine, host(), "STAR");
+    let mut engine = GameEngine::new(engine.into_game(), host(), START + TIMEOUT + 1);
+
+    let late = engine.apply(Event::Seal {
+        player_chain_id: guest(),
+        seal: seal::seal("RAT", SALT),
+    });
+    assert_eq!(late.unwrap_err(), EngineError::TurnExpired);
+
+    let effects = reveal(&mut engine, host(), "STAR");
+    assert!(ended(&effects));
+    assert_eq!(engine.game().history[0].host_word, "STAR");
+    assert_eq!(engine.game().history[0].guest_word, "");
+    assert_eq!(engine.game().winner_chain_id, Some(host()));
+}
+
+#[test]
+fn simultaneous_timeouts_score_missing_words_as_empty() {
+    let mut engine = started_simultaneous(1);
+    seal_word(&mut engine, host(), "STAR");
+    seal_word(&mut engine, guest(), "RAT");
+    reveal(&mut engine, host(), "STAR");
+    let deadline = engine.game().turn_deadline;
+    let mut engine = GameEngine::new(engine.into_game(), host(), deadline);
+
+    let host_waiting = engine.apply(Event::Timeout {
+        claimant_chain_id: guest(),
+    });
+    assert_eq!(host_waiting.unwrap_err(), EngineError::NotOpponentsTurn);
+
+    let effects = engine
+        .apply(Event::Timeout {
+            claimant_chain_id: host(),
+        })
+        .unwrap();
+    assert!(ended(&effects));
+    assert_eq!(engine.game().history[0].guest_word, "");
+    assert_eq!(engine.game().winner_chain_id, Some(host()));
+}
+
+/// The host's engine for a one-round rush match the guest has just joined.
+fn started_rush() -> GameEngine {
+    let mut game = waiting_game(1);
+    game.mode = GameMode::Rush;
+    game.turn_order = TurnOrder::Simultaneous;
+    let mut engine = GameEngine::new(game, host(), START);
+    engine
+        .apply(Event::Join {
+            player: player(guest()),
+        })
+        .unwrap();
+    engine
+}
+
+#[test]
+fn rush_rounds_score_every_distinct_word() {
+    let flood = (0..=MAX_RUSH_WORDS)
+        .map(|i| format!("W{i}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    for (words, error) in [
+        ("  ", EngineError::NoWords),
+        (&flood[..], EngineError::TooManyWords),
+    ] {
+        let mut engine = started_rush();
+        seal_word(&mut engine, host(), words);
+        seal_word(&mut engine, guest(), "stern");
+        let revealed = engine.apply(Event::Reveal {
+            player_chain_id: host(),
+            word: words.to_string(),
+            salt: SALT.to_string(),
+        });
+        assert_eq!(revealed.unwrap_err(), error);
+    }
+
+    let mut engine = started_rush();
+    assert_eq!(
+        engine.game().turn_deadline,
+        START + RUSH_WINDOW_SECS * 1_000_000
+    );
+    seal_word(&mut engine, host(), "rat tar RAT qqq at");
+    seal_word(&mut engine, guest(), "stern");
+    reveal(&mut engine, host(), "rat tar RAT qqq at");
+    assert_eq!(engine.game().host_word.as_deref(), Some("RAT TAR QQQ AT"));
+    reveal(&mut engine, guest(), "stern");
+
+    let record = &engine.game().history[0];
+    assert_eq!(record.host_points, 6);
+    assert_eq!(record.guest_points, 5);
+    let host_words: Vec<(&str, u32)> = record
+        .host_words
+        .iter()
+        .map(|w| (w.word.as_str(), w.points))
+        .collect();
+    assert_eq!(host_words, [("RAT", 3), ("TAR", 3), ("QQQ", 0), ("AT", 0)]);
+    assert_eq!(record.played_words(), ["RAT", "TAR", "QQQ", "AT", "STERN"]);
+    assert_eq!(engine.game().winner_chain_id, Some(host()));
+}
+
+#[test]
+fn late_rush_words_are_refused() {
+    let mut engine = started_rush();
+    seal_word(&mut engine, host(), "rat tar");
+    let deadline = engine.game().turn_deadline;
+    let mut engine = GameEngine::new(engine.into_game(), host(), deadline + 1);
+
+    // Rush words are only ever sealed, and the window closes on seals.
+    let submitted = engine.apply(Event::Submit {
+        player_chain_id: guest(),
+        word: "stern".to_string(),
+    });
+    assert_eq!(submitted.unwrap_err(), EngineError::SealFirst);
+    let sealed = engine.apply(Event::Seal {
+        player_chain_id: guest(),
+        seal: seal::seal("stern", SALT),
+    });
+    assert_eq!(sealed.unwrap_err(), EngineError::TurnExpired);
+    assert!(engine.game().guest_seal.is_none());
+
+    // Words sealed in time can still be revealed; the late player's round scores nothing.
+    reveal(&mut engine, host(), "rat tar");
+    let record = &engine.game().history[0];
+    assert_eq!((record.host_points, record.guest_points), (6, 0));
+
+    // Right at the deadline the window is still open.
+    let engine = started_rush();
+    let deadline = engine.game().turn_deadline;
+    let mut engine = GameEngine::new(engine.into_game(), host(), deadline);
+    seal_word(&mut engine, guest(), "stern");
+    let mut engine = GameEngine::new(engine.into_game(), host(), deadline + 1);
+    let sealed = engine.apply(Event::Seal {
+        player_chain_id: host(),
+        seal: seal::seal("rat tar", SALT),
+    });
+    assert_eq!(sealed.unwrap_err(), EngineError::TurnExpired);
+}
+
+#[test]
+fn blank_tiles_are_recorded_and_score_nothing() {
+    let mut game = waiting_game(1);
+    game.letters = "ATR?SN".to_string();
+    let mut engine = GameEngine::new(game, host(), START);
+    engine
+        .apply(Event::Join {
+            player: player(guest()),
+        })
+        .unwrap();
+    submit(&mut engine, host(), "stare");
+    submit(&mut engine, guest(), "rants");
+
+    let record = &engine.game().history[0];
+    assert_eq!((record.host_points, record.host_blanks.as_str()), (4, "E"));
+    assert_eq!((record.guest_points, record.guest_blanks.as_str()), (5, ""));
+}
+
+#[test]
+fn word_chain_words_follow_the_last_letter_without_repeats() {
+    let mut game = waiting_game(2);
+    game.mode = GameMode::Chain;
+    game.letters = String::new();
+    let mut engine = GameEngine::new(game, host(), START);
+    engine
+        .apply(Event::Join {
+            player: player(guest()),
+        })
+        .unwrap();
+    assert_eq!(engine.game().chain_letter(), None);
+    submit(&mut engine, host(), "table");
+    assert_eq!(engine.game().chain_letter(), Some('E'));
+    submit(&mut engine, guest(), "eagle");
+
+    submit(&mut engine, host(), "table");
+    assert_eq!(engine.game().chain_letter(), Some('E'));
+    submit(&mut engine, guest(), "elephant");
+
+    let game = engine.game();
+    assert_eq!(game.chain, ["TABLE", "EAGLE", "ELEPHANT"]);
+    let points: Vec<(u32, u32)> = game
+        .history
+        .iter()
+        .map(|r| (r.host_points, r.guest_points))
+        .collect();
+    assert_eq!(points, [(5, 5), (0, 8)]);
+}
+
+#[test]
+fn category_rounds_reject_words_outside_the_category() {
+    let mut game = waiting_game(2);
+    game.categories = true;
+    let mut engine = GameEngine::new(game, host(), START);
+    engine
+        .apply(Event::Join {
+            player: player(guest()),
+        })
+        .unwrap();
+    assert_eq!(engine.game().category, Some(Category::Food));
+    submit(&mut engine, host(), "tea");
+    submit(&mut engine, guest(), "rat");
+    assert_eq!(engine.game().category, Some(Category::Animals));
+    submit(&mut engine, host(), "tern");
+    submit(&mut engine, guest(), "qqq");
+
+    let verdicts: Vec<_> = engine
+        .game()
+        .history
+        .iter()
+        .map(|r| {
+            (
+                r.category,
+                r.host_points,
+                r.host_verdict,
+                r.guest_points,
+                r.guest_verdict,
+            )
+        })
+        .collect();
+    assert_eq!(
+        verdicts,
+        [
+            (
+                Some(Category::Food),
+                3,
+                Verdict::Valid,
+                0,
+                Verdict::OffCategory
+            ),
+            (
+                Some(Category::Animals),
+                4,
+                Verdict::Valid,
+                0,
+                Verdict::Invalid
+            ),
+        ]
+    );
+}
+
+#[test]
+fn the_last_round_stays_open_to_challenges_after_the_match() {
+    let mut engine = started(1);
+    submit(&mut engine, host(), "RATE");
+    let effects = submit(&mut engine, guest(), "SATRN");
+    assert!(ended(&effects));
+    assert_eq!(engine.game().winner_chain_id, Some(guest()));
+    assert!(engine.game().challenge_deadline().is_some());
+
+    let effects = challenge(&mut engine, host(), 1).unwrap();
+    assert!(effects.iter().any(|e| matches!(e, Effect::ResultRevised)));
+    let game = engine.game();
+    assert_eq!(game.status, MatchStatus::Ended);
+    assert_eq!((game.host_score, game.guest_score), (4, 0));
+    assert_eq!(game.winner_chain_id, Some(host()));
+
+    let mut late = GameEngine::new(engine.into_game(), host(), START + 31_000_000);
+    let closed = challenge(&mut late, guest(), 1);
+    assert_eq!(closed.unwrap_err(), EngineError::ChallengeClosed);
+
+    let mut engine = started(2);
+    submit(&mut engine, host(), "RATE");
+    submit(&mut engine, guest(), "SATRN");
+    engine
+        .apply(Event::Leave {
+            player_chain_id: guest(),
+        })
+        .unwrap();
+    let forfeited = challenge(&mut engine, host(), 1);
+    assert_eq!(forfeited.unwrap_err(), EngineError::MatchNotReady);
+}
+
+#[test]
+fn upheld_challenges_take_back_bonuses_and_handicap() {
+    let mut game = waiting_game(2);
+    game.bonuses = true;
+    game.handicap_mode = HandicapMode::Fixed;
+    game.guest_handicap = Handicap {
+        score_percent: 200,
+        ..Handicap::default()
+    };
+    let mut engine = GameEngine::new(game, host(), START);
+    engine
+        .apply(Event::Join {
+            player: player(guest()),
+        })
+        .unwrap();
+    submit(&mut engine, host(), "rat");
+    submit(&mut engine, guest(), "stern");
+    submit(&mut engine, host(), "rat");
+    submit(&mut engine, guest(), "nstrae");
+    let record = engine.game().history[1].clone();
+    assert_eq!((record.guest_points, record.guest_total()), (6, 26));
+    assert_eq!(engine.game().guest_score, 40);
+
+    challenge(&mut engine, host(), 2).unwrap();
+    let game = engine.game();
+    let outcome = game.history[1].host_challenge.as_ref().unwrap();
+    assert_eq!((outcome.upheld, outcome.points), (true, 26));
+    assert_eq!(game.guest_score, 14);
+}
+
+#[test]
+fn bonuses_are_itemised_per_player() {
+    let mut game = waiting_game(3);
+    game.bonuses = true;
+    let mut engine = GameEngine::new(game, host(), START);
+    engine
+        .apply(Event::Join {
+            player: player(guest()),
+        })
+        .unwrap();
+    let secs = |s: u64| START + s * 1_000_000;
+    for (now, chain_id, word) in [
+        (secs(3), host(), "astern"),
+        (secs(4), guest(), "rat"),
+        (secs(5), host(), "star"),
+        (secs(7), guest(), "nest"),
+        (secs(8), host(), "rat"),
+        (secs(8), guest(), "qqq"),
+    ] {
+        engine = GameEngine::new(engine.into_game(), host(), now);
+        submit(&mut engine, chain_id, word);
+    }
+
+    let game = engine.game();
+    let bonuses = |bonuses: &[Bonus]| -> Vec<(BonusKind, u32)> {
+        bonuses.iter().map(|b| (b.kind, b.points)).collect()
+    };
+    let rounds: Vec<_> = game
+        .history
+        .iter()
+        .map(|r| (bonuses(&r.host_bonuses), bonuses(&r.guest_bonuses)))
+        .collect();
+    assert_eq!(
+        rounds,
+        [
+            (
+                vec![(BonusKind::Pangram, 5), (BonusKind::LongerWord, 2)],
+                vec![(BonusKind::Speed, 1)]
+            ),
+            (vec![(BonusKind::Speed, 1)], vec![]),
+            (
+                vec![(BonusKind::Streak, 2), (BonusKind::LongerWord, 2)],
+                vec![]
+            ),
+        ]
+    );
+    let streaks: Vec<_> = game
+        .history
+        .iter()
+        .map(|r| (r.host_streak, r.guest_streak))
+        .collect();
+    assert_eq!(streaks, [(1, 1), (2, 2), (3, 0)]);
+    assert_eq!(
+        (
+            game.history[1].host_elapsed_micros,
+            game.history[1].guest_elapsed_micros
+        ),
+        (1_000_000, 2_000_000)
+    );
+    assert_eq!((game.host_score, game.guest_score), (25, 8));
+}
+
+#[test]
+fn rating_handicaps_help_the_weaker_player() {
+    let mut game = waiting_game(1);
+    game.handicap_mode = HandicapMode::FromRating;
+    let mut engine = GameEngine::new(game, host(), START);
+    let mut weaker = player(guest());
+    weaker.rating = word_duel::INITIAL_RATING - 250;
+    engine.apply(Event::Join { player: weaker }).unwrap();
+
+    let game = engine.game();
+    assert_eq!(game.handicap(true), Handicap::default());
+    assert_eq!(
+        game.handicap(false),
+        Handicap {
+            score_percent: 120,
+            extra_letters: 1,
+            extra_secs: 30,
+        }
+    );
+    assert_eq!(game.rack(false), "ATRESNE");
+
+    submit(&mut engine, host(), "stern");
+    assert_eq!(engine.game().turn_deadline, START + TIMEOUT + 30_000_000);
+    submit(&mut engine, guest(), "eastern");
+
+    let game = engine.game();
+    let record = &game.history[0];
+    assert_eq!((record.guest_points, record.guest_handicap_points), (7, 1));
+    assert_eq!((game.host_score, game.guest_score), (5, 8));
+}
+
+#[test]
+fn missed_words_use_each_players_rack_and_the_rounds_category() {
+    let mut game = waiting_game(2);
+    game.handicap_mode = HandicapMode::Fixed;
+    game.guest_handicap = Handicap {
+        extra_letters: 1,
+        ..Handicap::default()
+    };
+    let mut engine = GameEngine::new(game, host(), START);
+    engine
+        .apply(Event::Join {
+            player: player(guest()),
+        })
+        .unwrap();
+    for _ in 0..2 {
+        submit(&mut engine, host(), "stern");
+        submit(&mut engine, guest(), "eastern");
+    }
+    let mut game = engine.into_game();
+    game.history[1].category = Some(Category::Animals);
+
+    let missed = solver::missed_by_round(&game, 20);
+    let words = |scored: &[solver::ScoredWord]| -> Vec<String> {
+        scored.iter().map(|w| w.word.clone()).collect()
+    };
+    assert!(missed[0]
+        .host_words
+        .iter()
+        .all(|w| validate_word(&game.letters, &w.word)));
+    assert!(missed[0]
+        .guest_words
+        .iter()
+        .any(|w| !validate_word(&game.letters, &w.word)));
+    assert!(!words(&missed[0].guest_words).contains(&"EASTERN".to_string()));
+    assert_eq!(words(&missed[1].host_words), ["TERN", "ANT", "RAT"]);
+}
+
+#[test]
+fn simultaneous_deadlines_follow_each_players_handicap() {
+    let mut game = waiting_game(1);
+    game.turn_order = TurnOrder::Simultaneous;
+    game.handicap_mode = HandicapMode::Fixed;
+    game.guest_handicap = Handicap {
+        extra_secs: 60,
+        ..Handicap::default()
+    };
+    let mut engine = GameEngine::new(game, host(), START);
+    engine
+        .apply(Event::Join {
+            player: player(guest()),
+        })
+        .unwrap();
+    let game = engine.game();
+    assert!(!game.rated());
+    assert_eq!(game.deadline(true), START + TIMEOUT);
+    assert_eq!(game.deadline(false), START + TIMEOUT + 60_000_000);
+    assert_eq!(game.turn_deadline, game.deadline(false));
+
+    let mut engine = GameEngine::new(engine.into_game(), host(), START + TIMEOUT + 1);
+    let late = engine.apply(Event::Seal {
+        player_chain_id: host(),
+        seal: seal::seal("STAR", SALT),
+    });
+    assert_eq!(late.unwrap_err(), EngineError::TurnExpired);
+    seal_word(&mut engine, guest(), "STERN");
+    let early = engine.apply(Event::Timeout {
+        claimant_chain_id: host(),
+    });
+    assert_eq!(early.unwrap_err(), EngineError::TurnNotExpired);
+
+    let effects = reveal(&mut engine, guest(), "STERN");
+    assert!(ended(&effects));
+    assert_eq!(engine.game().history[0].host_word, "");
+    assert_eq!(engine.game().winner_chain_id, Some(guest()));
+}
+
+fn challenge(
+    engine: &mut GameEngine,
+    chain_id: String,
+    round: u32,
+) -> Result<Vec<Effect>, EngineError> {
+    engine.apply(Event::Challenge {
+        challenger_chain_id: chain_id,
+        round,
+    })
+}
+
+#[test]
+fn challenges_strike_missing_words_or_cost_the_challenger() {
+    let mut engine = started(3);
+    submit(&mut engine, host(), "RATE");
+    submit(&mut engine, guest(), "SATRN");
+    assert_eq!(
+        engine.game().challenge_deadline(),
+        Some(START + CHALLENGE_WINDOW_SECS * 1_000_000)
+    );
+
+    let mut guest_engine = GameEngine::new(engine.game().clone(), guest(), START);
+    let effects = challenge(&mut guest_engine, guest(), 1).unwrap();
+    assert!(matches!(
+        sent(&effects)[..],
+        [CrossChainMessage::Challenge { round: 1 }]
+    ));
+
+    let effects = challenge(&mut engine, host(), 1).unwrap();
+    assert!(matches!(
+        sent(&effects)[..],
+        [CrossChainMessage::RoundSync { .. }]
+    ));
+    let again = challenge(&mut engine, host(), 1);
+    assert_eq!(again.unwrap_err(), EngineError::AlreadyChallenged);
+    let game = engine.game();
+    let record = &game.history[0];
+    assert_eq!(
+        record.host_challenge,
+        Some(Challenge {
+            upheld: true,
+            struck_words: vec!["SATRN".to_string()],
+            points: 5,
+        })
+    );
+    assert_eq!((game.host_score, game.guest_score), (4, 0));
+    assert_eq!((record.host_score, record.guest_score), (4, 0));
+
+    // RATE holds up, but the guest has nothing left to lose.
+    challenge(&mut engine, guest(), 1).unwrap();
+    let record = &engine.game().history[0];
+    assert_eq!(
+        record
+            .guest_challenge
+            .as_ref()
+            .map(|c| (c.upheld, c.points)),
+        Some((false, 0))
+    );
+
+    submit(&mut engine, host(), "STERN");
+    submit(&mut engine, guest(), "RATES");
+    let mut late = GameEngine::new(engine.game().clone(), host(), START + 31_000_000);
+    let closed = challenge(&mut late, guest(), 2);
+    assert_eq!(closed.unwrap_err(), EngineError::ChallengeClosed);
+    let stale = challenge(&mut engine, guest(), 1);
+    assert_eq!(stale.unwrap_err(), EngineError::ChallengeClosed);
+
+    challenge(&mut engine, guest(), 2).unwrap();
+    let game = engine.game();
+    assert_eq!(
+        game.history[1].guest_challenge.as_ref().map(|c| c.points),
+        Some(CHALLENGE_PENALTY)
+    );
+    assert_eq!((game.host_score, game.guest_score), (9, 0));
+}
@@ -0,0 +1,1044 @@
+//! End-to-end matches between two chains, checked through each chain's GraphQL service.
+
+mod common;
+
+use std::collections::HashMap;
+
+use linera_sdk::{
+    linera_base_types::{ChainId, Timestamp},
+    test::{ActiveChain, MessageAction},
+};
+use word_duel::{
+    handicap::HandicapChoice, GameMode, JoinPolicy, LetterChoice, MatchRules, Operation, TurnOrder,
+};
+
+const GAME_FIELDS: &str = "game { matchId status currentRound hostScore guestScore \
+    roundPhase winnerChainId endReason history { round hostWord guestWord hostPoints \
+    guestPoints hostScore guestScore } }";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn full_match_ends_with_the_same_result_on_both_chains() {
+    let players = common::setup().await;
+    players.start_match(2).await;
+    let letters = players.letters().await;
+
+    players.play_round(&letters[..4], &letters[..3]).await;
+    let host = players
+        .query(&players.host, "query { round myScore opponentScore }")
+        .await;
+    assert_eq!(host["round"], 2);
+    assert_eq!(host["myScore"], 4);
+    assert_eq!(host["opponentScore"], 3);
+
+    players.play_round(&letters[..3], &letters[..5]).await;
+
+    let query =
+        format!("query {{ {GAME_FIELDS} matchResult archivedMatches {{ matchId }} rating }}");
+    let host = players.query(&players.host, &query).await;
+    let guest = players.query(&players.guest, &query).await;
+    assert_eq!(host["game"], guest["game"]);
+    assert_eq!(host["game"]["status"], "ENDED");
+    assert_eq!(host["game"]["endReason"], "COMPLETED");
+    assert_eq!(host["game"]["hostScore"], 7);
+    assert_eq!(host["game"]["guestScore"], 8);
+    assert_eq!(host["game"]["history"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        host["game"]["winnerChainId"],
+        players.guest.id().to_string()
+    );
+    assert_eq!(host["matchResult"], "LOSS");
+    assert_eq!(guest["matchResult"], "WIN");
+    assert_eq!(host["archivedMatches"].as_array().unwrap().len(), 1);
+    assert_eq!(guest["archivedMatches"].as_array().unwrap().len(), 1);
+    assert!(host["rating"].as_i64().unwrap() < word_duel::INITIAL_RATING as i64);
+    assert!(guest["rating"].as_i64().unwrap() > word_duel::INITIAL_RATING as i64);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_word_scores_zero() {
+    let players = common::setup().await;
+    players.start_match(1).await;
+    let letters = players.letters().await;
+
+    players.play_round(&letters[..3], "QQQQ").await;
+
+    let guest = players
+        .query(
+            &players.guest,
+            "query { lastRoundRecord { guestWord guestPoints hostPoints } matchResult }",
+        )
+        .await;
+    assert_eq!(guest["lastRoundRecord"]["guestWord"], "QQQQ");
+    assert_eq!(guest["lastRoundRecord"]["guestPoints"], 0);
+    assert_eq!(guest["lastRoundRecord"]["hostPoints"], 3);
+    assert_eq!(guest["matchResult"], "LOSS");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn short_and_out_of_turn_words_are_rejected() {
+    let players = common::setup().await;
+    players.start_match(1).await;
+    let app = players.app;
+
+    let out_of_turn = players
+        .guest
+        .try_graphql_mutation(app, r#"mutation { submitWord(word: "TEA") }"#)
+        .await;
+    assert!(out_of_turn.is_err());
+
+    let too_short = players
+        .host
+        .try_graphql_mutation(app, r#"mutation { submitWord(word: "AT") }"#)
+        .await;
+    assert!(too_short.is_err());
+
+    let host = players
+        .query(&players.host, "query { roundPhase myWord }")
+        .await;
+    assert_eq!(host["roundPhase"], "HOST_TO_PLAY");
+    assert!(host["myWord"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn host_claims_the_round_when_the_guest_times_out() {
+    let players = common::setup().await;
+    players.start_match(1).await;
+    let letters = players.letters().await;
+    players.submit(&players.host, &letters[..3]).await;
+    players.guest.handle_received_messages().await;
+
+    let at = players.time_out();
+    let claim = players
+        .operate_at(&players.host, at, Operation::ClaimTimeout)
+        .await
+        .expect("Timeout claim rejected");
+    players.deliver_at(&players.guest, at, &claim).await;
+
+    let query = format!("query {{ {GAME_FIELDS} }}");
+    let host = players.query(&players.host, &query).await;
+    let guest = players.query(&players.guest, &query).await;
+    assert_eq!(host["game"], guest["game"]);
+    assert_eq!(host["game"]["status"], "ENDED");
+    assert_eq!(host["game"]["history"][0]["guestWord"], "");
+    assert_eq!(host["game"]["history"][0]["guestPoints"], 0);
+    assert_eq!(host["game"]["winnerChainId"], players.host.id().to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn guest_claims_the_turn_when_the_host_times_out() {
+    let players = common::setup().await;
+    players.start_match(1).await;
+    let letters = players.letters().await;
+
+    let at = players.time_out();
+    let claim = players
+        .operate_at(&players.guest, at, Operation::ClaimTimeout)
+        .await
+        .expect("Timeout claim rejected");
+    let forfeit = players.deliver_at(&players.host, at, &claim).await;
+    players.deliver_at(&players.guest, at, &forfeit).await;
+
+    let guest = players
+        .query(&players.guest, "query { roundPhase opponentWord }")
+        .await;
+    assert_eq!(guest["roundPhase"], "GUEST_TO_PLAY");
+    assert_eq!(guest["opponentWord"], "");
+
+    let submit = players
+        .operate_at(
+            &players.guest,
+            at,
+            Operation::SubmitWord {
+                word: letters[..3].to_string(),
+            },
+        )
+        .await
+        .expect("Guest word rejected");
+    let resolve = players.deliver_at(&players.host, at, &submit).await;
+    players.deliver_at(&players.guest, at, &resolve).await;
+
+    let guest = players
+        .query(
+            &players.guest,
+            "query { matchResult lastRoundRecord { hostPoints guestPoints } }",
+        )
+        .await;
+    assert_eq!(guest["lastRoundRecord"]["hostPoints"], 0);
+    assert_eq!(guest["lastRoundRecord"]["guestPoints"], 3);
+    assert_eq!(guest["matchResult"], "WIN");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn timeout_cannot_be_claimed_before_the_deadline() {
+    let players = common::setup().await;
+    players.start_match(1).await;
+    players.submit(&players.host, "TEA").await;
+
+    let early = players
+        .host
+        .try_graphql_mutation(players.app, "mutation { claimTimeout }")
+        .await;
+    assert!(early.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_guest_that_missed_a_round_sync_catches_up_from_the_host() {
+    let players = common::setup().await;
+    players.start_match(2).await;
+    let letters = players.letters().await;
+    players.submit(&players.host, &letters[..3]).await;
+    players.guest.handle_received_messages().await;
+    let guest_word = players.submit(&players.guest, &letters[..4]).await;
+    let resolved = players
+        .host
+        .add_block(|block| {
+            block.with_messages_from(&guest_word);
+        })
+        .await;
+    // The sync of the scored round never reaches the guest.
+    players
+        .guest
+        .add_block(|block| {
+            block.with_messages_from_by_action(&resolved, MessageAction::Reject);
+        })
+        .await;
+    players.host.handle_received_messages().await;
+
+    players.submit(&players.host, &letters[..5]).await;
+    players.guest.handle_received_messages().await;
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Out of sync, requesting state"
+    );
+    let guest = players.query(&players.guest, "query { round }").await;
+    assert_eq!(guest["round"], 1);
+
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+    let query = format!("query {{ {GAME_FIELDS} opponentWord }}");
+    let host = players.query(&players.host, &query).await;
+    let guest = players.query(&players.guest, &query).await;
+    assert_eq!(host["game"], guest["game"]);
+    assert_eq!(guest["game"]["currentRound"], 2);
+    assert_eq!(guest["game"]["roundPhase"], "GUEST_TO_PLAY");
+    assert_eq!(guest["opponentWord"], &letters[..5]);
+
+    players.submit(&players.guest, &letters[..3]).await;
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+    let host = players.query(&players.host, &query).await;
+    let guest = players.query(&players.guest, &query).await;
+    assert_eq!(host["game"], guest["game"]);
+    assert_eq!(guest["game"]["status"], "ENDED");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn matches_without_rounds_are_refused() {
+    let players = common::setup().await;
+    let rules = MatchRules {
+        total_rounds: 0,
+        turn_order: TurnOrder::default(),
+        mode: GameMode::default(),
+        letters: LetterChoice::default(),
+        categories: false,
+        bonuses: false,
+        handicaps: HandicapChoice::Off,
+    };
+    let create = Operation::CreateMatch {
+        host_name: "Host".to_string(),
+        join_policy: JoinPolicy::default(),
+        rules,
+    };
+    let at = players.validator.clock().current_time();
+    assert!(players.operate_at(&players.host, at, create).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn host_tells_the_guest_why_its_move_was_refused() {
+    let players = common::setup().await;
+    players.start_match(2).await;
+    let letters = players.letters().await;
+    players.play_round(&letters[..3], &letters[..3]).await;
+
+    // The challenge is made in time but only reaches the host after the window closed.
+    let challenge = players
+        .mutate(&players.guest, "mutation { challenge }")
+        .await;
+    let at = players.time_out();
+    let rejection = players.deliver_at(&players.host, at, &challenge).await;
+    players.deliver_at(&players.guest, at, &rejection).await;
+
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Move rejected: Too late to challenge"
+    );
+    let host = players
+        .query(
+            &players.host,
+            "query { lastRoundRecord { guestChallenge { upheld } } }",
+        )
+        .await;
+    assert!(host["lastRoundRecord"]["guestChallenge"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn challenging_the_last_round_revises_the_result_and_ratings() {
+    let players = common::setup().await;
+    players.start_match_with(1, r#"letters: "gardens""#).await;
+    players.play_round("GARDEN", "SNEDRAG").await;
+    let query = "query { matchResult rating archivedMatches { winnerChainId } }";
+    let host = players.query(&players.host, query).await;
+    assert_eq!(host["matchResult"], "LOSS");
+
+    players
+        .mutate(&players.host, "mutation { challenge }")
+        .await;
+    players.guest.handle_received_messages().await;
+
+    let host = players.query(&players.host, query).await;
+    let guest = players.query(&players.guest, query).await;
+    assert_eq!(host["matchResult"], "WIN");
+    assert_eq!(guest["matchResult"], "LOSS");
+    assert!(host["rating"].as_i64().unwrap() > word_duel::INITIAL_RATING as i64);
+    assert!(guest["rating"].as_i64().unwrap() < word_duel::INITIAL_RATING as i64);
+    for archived in [&host["archivedMatches"], &guest["archivedMatches"]] {
+        assert_eq!(archived.as_array().unwrap().len(), 1);
+        assert_eq!(archived[0]["winnerChainId"], players.host.id().to_string());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn an_invitee_plays_only_the_first_invite_it_accepts() {
+    let players = common::setup().await;
+    let rival = players.validator.new_chain().await;
+    let guest_id = players.guest.id();
+    let invite = format!(
+        r#"mutation {{ challengePlayer(chainId: "{guest_id}", rules: {{ totalRounds: 1 }}) }}"#
+    );
+    players.mutate(&players.host, &invite).await;
+    players.mutate(&rival, &invite).await;
+    // The guest also asked to join a room the rival opens meanwhile.
+    players
+        .mutate(
+            &rival,
+            r#"mutation { createMatch(hostName: "Rival", totalRounds: 1) }"#,
+        )
+        .await;
+    players
+        .mutate(
+            &players.guest,
+            &format!(r#"mutation {{ joinMatch(hostChainId: "{}") }}"#, rival.id()),
+        )
+        .await;
+    players.guest.handle_received_messages().await;
+
+    let accept = |challenger: &ActiveChain| {
+        format!(
+            r#"mutation {{ acceptChallenge(chainId: "{}") }}"#,
+            challenger.id()
+        )
+    };
+    players.mutate(&players.guest, &accept(&players.host)).await;
+    let second = players
+        .guest
+        .try_graphql_mutation(players.app, &accept(&rival))
+        .await;
+    assert!(second.is_err());
+
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+    rival.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+    rival.handle_received_messages().await;
+
+    let query = "query { game { hostChainId status players { chainId } } }";
+    let guest = players.query(&players.guest, query).await;
+    assert_eq!(guest["game"]["hostChainId"], players.host.id().to_string());
+    assert_eq!(guest["game"]["status"], "ACTIVE");
+    let rival_game = players.query(&rival, query).await;
+    assert_eq!(rival_game["game"]["status"], "WAITING_FOR_PLAYER");
+    assert_eq!(rival_game["game"]["players"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn accepting_an_invite_closes_the_room_the_invitee_hosts() {
+    let players = common::setup().await;
+    players
+        .mutate(
+            &players.guest,
+            r#"mutation { createMatch(hostName: "Guest", totalRounds: 1) }"#,
+        )
+        .await;
+    players.host.handle_received_messages().await;
+    let code = players.query(&players.guest, "query { roomCode }").await["roomCode"].clone();
+    let lookup = format!("query {{ lookupRoom(code: {code}) {{ hostChainId }} }}");
+    let lobby = players.query(&players.host, &lookup).await;
+    assert_eq!(
+        lobby["lookupRoom"]["hostChainId"],
+        players.guest.id().to_string()
+    );
+
+    let invite = format!(
+        r#"mutation {{ challengePlayer(chainId: "{}", rules: {{ totalRounds: 1 }}) }}"#,
+        players.guest.id()
+    );
+    players.mutate(&players.host, &invite).await;
+    players.guest.handle_received_messages().await;
+    players
+        .mutate(
+            &players.guest,
+            &format!(
+                r#"mutation {{ acceptChallenge(chainId: "{}") }}"#,
+                players.host.id()
+            ),
+        )
+        .await;
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+    players.host.handle_received_messages().await;
+
+    let guest = players
+        .query(&players.guest, "query { game { hostChainId } }")
+        .await;
+    assert_eq!(guest["game"]["hostChainId"], players.host.id().to_string());
+    let lobby = players.query(&players.host, &lookup).await;
+    assert!(lobby["lookupRoom"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn leaving_an_active_match_forfeits_it() {
+    let players = common::setup().await;
+    players.start_match(3).await;
+
+    players
+        .mutate(&players.guest, "mutation { leaveMatch }")
+        .await;
+    players.host.handle_received_messages().await;
+
+    let query =
+        "query { matchResult game { status endReason winnerChainId } archivedMatches { matchId } }";
+    let host = players.query(&players.host, query).await;
+    let guest = players.query(&players.guest, query).await;
+    assert_eq!(host["matchResult"], "WIN");
+    assert_eq!(guest["matchResult"], "LOSS");
+    assert_eq!(host["game"], guest["game"]);
+    assert_eq!(host["game"]["endReason"], "FORFEIT");
+    assert_eq!(host["archivedMatches"].as_array().unwrap().len(), 1);
+    assert_eq!(guest["archivedMatches"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        players.notification(&players.host).await,
+        "Opponent forfeited"
+    );
+
+    players
+        .mutate(&players.guest, "mutation { leaveMatch }")
+        .await;
+    let guest = players
+        .query(&players.guest, "query { game { matchId } }")
+        .await;
+    assert!(guest["game"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancelling_a_waiting_match_is_not_recorded() {
+    let players = common::setup().await;
+    players.create_match(1).await;
+
+    players
+        .mutate(&players.host, "mutation { leaveMatch }")
+        .await;
+    players.host.handle_received_messages().await;
+
+    let host = players
+        .query(
+            &players.host,
+            "query { game { matchId } archivedMatches { matchId } rating }",
+        )
+        .await;
+    assert!(host["game"].is_null());
+    assert!(host["archivedMatches"].as_array().unwrap().is_empty());
+    assert_eq!(host["rating"], word_duel::INITIAL_RATING);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn hosts_approve_join_requests_before_the_match_starts() {
+    let players = common::setup().await;
+    players.create_match_with(1, "requireApproval: true").await;
+    players.join().await;
+    players.host.handle_received_messages().await;
+
+    let query = "query { pendingJoins { chainId name } game { status } }";
+    let host = players.query(&players.host, query).await;
+    assert_eq!(
+        host["pendingJoins"][0]["chainId"],
+        players.guest.id().to_string()
+    );
+    assert_eq!(host["pendingJoins"][0]["name"], "Guest");
+    assert_eq!(host["game"]["status"], "WAITING_FOR_PLAYER");
+    assert_eq!(
+        players.notification(&players.host).await,
+        "Guest wants to join"
+    );
+
+    players
+        .mutate(
+            &players.host,
+            &format!(
+                r#"mutation {{ acceptJoin(playerChainId: "{}") }}"#,
+                players.guest.id()
+            ),
+        )
+        .await;
+    players.guest.handle_received_messages().await;
+
+    let host = players.query(&players.host, query).await;
+    assert_eq!(host["pendingJoins"], serde_json::json!([]));
+    assert_eq!(host["game"]["status"], "ACTIVE");
+    let guest = players
+        .query(&players.guest, "query { game { status } }")
+        .await;
+    assert_eq!(guest["game"]["status"], "ACTIVE");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn declined_join_requests_are_told_why() {
+    let players = common::setup().await;
+    players.create_match_with(1, "requireApproval: true").await;
+    players.join().await;
+    players.host.handle_received_messages().await;
+
+    players
+        .mutate(
+            &players.host,
+            &format!(
+                r#"mutation {{ declineJoin(playerChainId: "{}") }}"#,
+                players.guest.id()
+            ),
+        )
+        .await;
+    players.guest.handle_received_messages().await;
+
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Join rejected: Declined by host"
+    );
+    let host = players
+        .query(
+            &players.host,
+            "query { pendingJoins { chainId } game { status } }",
+        )
+        .await;
+    assert_eq!(host["pendingJoins"], serde_json::json!([]));
+    assert_eq!(host["game"]["status"], "WAITING_FOR_PLAYER");
+    let guest = players
+        .query(&players.guest, "query { game { matchId } }")
+        .await;
+    assert!(guest["game"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn players_missing_from_the_allow_list_are_rejected() {
+    let players = common::setup().await;
+    let invited = players.validator.new_chain().await;
+    players
+        .create_match_with(1, &format!(r#"allowList: ["{}"]"#, invited.id()))
+        .await;
+
+    players.join().await;
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Join rejected: Not invited"
+    );
+    let host = players
+        .query(
+            &players.host,
+            "query { game { status players { chainId } } }",
+        )
+        .await;
+    assert_eq!(host["game"]["status"], "WAITING_FOR_PLAYER");
+    assert_eq!(host["game"]["players"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn only_the_right_invite_secret_opens_the_room() {
+    let players = common::setup().await;
+    players
+        .create_match_with(1, r#"inviteSecret: "open sesame""#)
+        .await;
+    let join = |secret: &str| {
+        format!(
+            r#"mutation {{ joinMatch(hostChainId: "{}", playerName: "Guest", inviteSecret: "{secret}") }}"#,
+            players.host.id()
+        )
+    };
+
+    players.mutate(&players.guest, &join("guess")).await;
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Join rejected: Wrong invite secret"
+    );
+
+    players.mutate(&players.guest, &join("open sesame")).await;
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+    let guest = players
+        .query(&players.guest, "query { game { status } }")
+        .await;
+    assert_eq!(guest["game"]["status"], "ACTIVE");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn kicking_before_the_first_round_frees_the_seat() {
+    let players = common::setup().await;
+    players.start_match_with(1, "turnOrder: SIMULTANEOUS").await;
+    let letters = players.letters().await;
+    players.seal(&players.guest, &letters[..3]).await;
+    players.host.handle_received_messages().await;
+
+    players
+        .mutate(&players.host, "mutation { kickPlayer }")
+        .await;
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+
+    let query = "query { game { status roundPhase guestSeal players { chainId } } }";
+    let host = players.query(&players.host, query).await;
+    assert_eq!(host["game"]["status"], "WAITING_FOR_PLAYER");
+    assert_eq!(host["game"]["roundPhase"], "BOTH_TO_PLAY");
+    assert!(host["game"]["guestSeal"].is_null());
+    assert_eq!(host["game"]["players"].as_array().unwrap().len(), 1);
+    let guest = players
+        .query(&players.guest, "query { game { matchId } }")
+        .await;
+    assert!(guest["game"].is_null());
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Removed by host"
+    );
+
+    // The next player's seal is taken, not refused as a second one.
+    let next = players.validator.new_chain().await;
+    players
+        .mutate(
+            &next,
+            &format!(
+                r#"mutation {{ joinMatch(hostChainId: "{}") }}"#,
+                players.host.id()
+            ),
+        )
+        .await;
+    players.host.handle_received_messages().await;
+    next.handle_received_messages().await;
+    players.seal(&next, &letters[..3]).await;
+    players.host.handle_received_messages().await;
+    let host = players.query(&players.host, query).await;
+    assert_eq!(host["game"]["status"], "ACTIVE");
+    assert_eq!(host["game"]["players"][1]["chainId"], next.id().to_string());
+    assert!(host["game"]["guestSeal"].is_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn joining_by_code_goes_through_the_lobby() {
+    let players = common::setup().await;
+    let room_host = players.validator.new_chain().await;
+    players
+        .mutate(
+            &room_host,
+            r#"mutation { createMatch(hostName: "Room", totalRounds: 1) }"#,
+        )
+        .await;
+    players.host.handle_received_messages().await;
+    let code = players.query(&room_host, "query { roomCode }").await["roomCode"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Typed the way a player reads it out.
+    let typed = format!("{}-{}", &code[..3], &code[3..]).to_lowercase();
+    players
+        .mutate(
+            &players.guest,
+            &format!(r#"mutation {{ joinByCode(code: "{typed}", playerName: "Guest") }}"#),
+        )
+        .await;
+    players.host.handle_received_messages().await;
+    room_host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+    players.host.handle_received_messages().await;
+
+    let guest = players
+        .query(&players.guest, "query { game { hostChainId status } }")
+        .await;
+    assert_eq!(guest["game"]["hostChainId"], room_host.id().to_string());
+    assert_eq!(guest["game"]["status"], "ACTIVE");
+    let lobby = players
+        .query(
+            &players.host,
+            &format!(r#"query {{ lookupRoom(code: "{code}") {{ hostChainId }} }}"#),
+        )
+        .await;
+    assert!(lobby["lookupRoom"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_room_codes_are_rejected_by_the_lobby() {
+    let players = common::setup().await;
+
+    players
+        .mutate(
+            &players.guest,
+            r#"mutation { joinByCode(code: "ZZZZZZ", playerName: "Guest") }"#,
+        )
+        .await;
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+
+    assert_eq!(
+        players.notification(&players.guest).await,
+        "Join rejected: Unknown room code"
+    );
+    let guest = players
+        .query(&players.guest, "query { game { matchId } }")
+        .await;
+    assert!(guest["game"].is_null());
+}
+
+/// Creation times, in microseconds, at which matches hosted by `a` and `b` get the same
+/// room code.
+fn colliding_creation_times(a: ChainId, b: ChainId) -> (u64, u64) {
+    let (a, b) = (a.to_string(), b.to_string());
+    let codes: HashMap<String, u64> = (1..=200_000u64)
+        .map(|micros| (word_duel::room_code(&a, &micros.to_string()), micros))
+        .collect();
+    (1..=50_000_000u64)
+        .find_map(|micros| {
+            let code = word_duel::room_code(&b, &micros.to_string());
+            codes.get(&code).map(|&other| (other, micros))
+        })
+        .expect("No colliding room codes")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_room_code_already_taken_is_replaced() {
+    let players = common::setup().await;
+    let rival = players.validator.new_chain().await;
+    let (host_time, rival_time) = colliding_creation_times(players.host.id(), rival.id());
+    let at = Timestamp::from(host_time.max(rival_time));
+    players.validator.clock().set(at);
+    let create = || Operation::CreateMatch {
+        host_name: "Host".to_string(),
+        join_policy: JoinPolicy::default(),
+        rules: MatchRules {
+            total_rounds: 1,
+            turn_order: TurnOrder::default(),
+            mode: GameMode::default(),
+            letters: LetterChoice::default(),
+            categories: false,
+            bonuses: false,
+            handicaps: HandicapChoice::Off,
+        },
+    };
+
+    let hosted = players
+        .operate_at(&players.host, Timestamp::from(host_time), create())
+        .await
+        .expect("Match not created");
+    players.deliver_at(&players.host, at, &hosted).await;
+    let rivalled = players
+        .operate_at(&rival, Timestamp::from(rival_time), create())
+        .await
+        .expect("Match not created");
+    let taken = players.deliver_at(&players.host, at, &rivalled).await;
+    let retried = players.deliver_at(&rival, at, &taken).await;
+    players.deliver_at(&players.host, at, &retried).await;
+
+    let code = players.query(&players.host, "query { roomCode }").await["roomCode"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let rival_code = players.query(&rival, "query { roomCode }").await["roomCode"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(
+        word_duel::room_code(&rival.id().to_string(), &rival_time.to_string()),
+        code
+    );
+    assert_ne!(rival_code, code);
+    assert_eq!(
+        players.notification(&rival).await,
+        format!("Room code {code} was taken, the room is now {rival_code}")
+    );
+    for (code, chain) in [(&code, &players.host), (&rival_code, &rival)] {
+        let lobby = players
+            .query(
+                &players.host,
+                &format!(r#"query {{ lookupRoom(code: "{code}") {{ hostChainId }} }}"#),
+            )
+            .await;
+        assert_eq!(lobby["lookupRoom"]["hostChainId"], chain.id().to_string());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn simultaneous_words_are_hidden_until_the_round_is_scored() {
+    let players = common::setup().await;
+    players.start_match_with(1, "turnOrder: SIMULTANEOUS").await;
+    let letters = players.letters().await;
+
+    players.seal(&players.guest, &letters[..4]).await;
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+    let hidden = "query { game { roundPhase guestWord guestSeal } revealDue }";
+    let host = players.query(&players.host, hidden).await;
+    assert_eq!(host["game"]["roundPhase"], "BOTH_TO_PLAY");
+    assert_eq!(host["game"]["guestWord"], serde_json::Value::Null);
+    assert!(host["game"]["guestSeal"].is_string());
+    let guest = players.query(&players.guest, hidden).await;
+    assert_eq!(guest["revealDue"], false);
+
+    players.seal(&players.host, &letters[..3]).await;
+    players.guest.handle_received_messages().await;
+    let guest = players.query(&players.guest, hidden).await;
+    assert_eq!(guest["revealDue"], true);
+
+    players.reveal(&players.guest, &letters[..4]).await;
+    players.host.handle_received_messages().await;
+    let host = players.query(&players.host, hidden).await;
+    assert_eq!(host["game"]["guestWord"], serde_json::Value::Null);
+    assert_eq!(host["revealDue"], true);
+
+    players.reveal(&players.host, &letters[..3]).await;
+    players.guest.handle_received_messages().await;
+
+    let query = format!("query {{ {GAME_FIELDS} }}");
+    let host = players.query(&players.host, &query).await;
+    let guest = players.query(&players.guest, &query).await;
+    assert_eq!(host["game"], guest["game"]);
+    assert_eq!(host["game"]["status"], "ENDED");
+    assert_eq!(host["game"]["history"][0]["guestWord"], &letters[..4]);
+    assert_eq!(
+        host["game"]["winnerChainId"],
+        players.guest.id().to_string()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn custom_racks_are_checked_and_shown_to_the_guest() {
+    let players = common::setup().await;
+    let unplayable = players
+        .host
+        .try_graphql_mutation(
+            players.app,
+            r#"mutation { createMatch(hostName: "Host", totalRounds: 1, letters: "QXZV") }"#,
+        )
+        .await;
+    assert!(unplayable.is_err());
+
+    players.start_match_with(1, r#"letters: "gardens""#).await;
+    let query = "query { game { letters letterSource letterPack } }";
+    let guest = players.query(&players.guest, query).await;
+    assert_eq!(guest["game"]["letters"], "GARDENS");
+    assert_eq!(guest["game"]["letterSource"], "CUSTOM");
+    assert_eq!(guest["game"]["letterPack"], serde_json::Value::Null);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn profiles_are_attached_when_joining_and_shown_to_the_opponent() {
+    let players = common::setup().await;
+    let unknown_avatar = players
+        .guest
+        .try_graphql_mutation(
+            players.app,
+            r#"mutation { setProfile(profile: { displayName: "Ana", avatarId: 99, language: "", country: "" }) }"#,
+        )
+        .await;
+    assert!(unknown_avatar.is_err());
+
+    players
+        .mutate(
+            &players.guest,
+            r#"mutation { setProfile(profile: { displayName: " Ana ", avatarId: 3, language: "EN", country: "br" }) }"#,
+        )
+        .await;
+    players.start_match(1).await;
+
+    let query = format!(
+        r#"query {{ profile(chainId: "{}") {{ displayName avatarId language country }} game {{ players {{ name profile {{ displayName }} }} }} }}"#,
+        players.guest.id()
+    );
+    let host = players.query(&players.host, &query).await;
+    assert_eq!(host["profile"]["displayName"], "Ana");
+    assert_eq!(host["profile"]["avatarId"], 3);
+    assert_eq!(host["profile"]["language"], "en");
+    assert_eq!(host["profile"]["country"], "BR");
+    // A name given when joining still wins over the display name.
+    assert_eq!(host["game"]["players"][1]["name"], "Guest");
+    assert_eq!(
+        host["game"]["players"][0]["profile"],
+        serde_json::Value::Null
+    );
+    let guest = players.query(&players.guest, &query).await;
+    assert_eq!(guest["profile"], host["profile"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invites_start_a_match_with_the_challengers_rules_once_accepted() {
+    let players = common::setup().await;
+    let guest_id = players.guest.id();
+    players
+        .mutate(
+            &players.host,
+            &format!(r#"mutation {{ addFriend(chainId: "{guest_id}", name: "Ana") }}"#),
+        )
+        .await;
+    let invite = format!(
+        r#"mutation {{ challengePlayer(chainId: "{guest_id}", rules: {{ totalRounds: 2, bonuses: true }}) }}"#
+    );
+    players.mutate(&players.host, &invite).await;
+    players.guest.handle_received_messages().await;
+
+    let host = players
+        .query(
+            &players.host,
+            "query { friends { chainId name } sentInvites }",
+        )
+        .await;
+    assert_eq!(host["friends"][0]["name"], "Ana");
+    assert_eq!(host["sentInvites"][0], guest_id.to_string());
+    let guest = players
+        .query(
+            &players.guest,
+            "query { invites { chainId rules { totalRounds bonuses } } }",
+        )
+        .await;
+    assert_eq!(
+        guest["invites"][0]["chainId"],
+        players.host.id().to_string()
+    );
+    assert_eq!(guest["invites"][0]["rules"]["totalRounds"], 2);
+
+    let answer = |mutation: &str| {
+        format!(
+            r#"mutation {{ {mutation}(chainId: "{}") }}"#,
+            players.host.id()
+        )
+    };
+    players
+        .mutate(&players.guest, &answer("declineChallenge"))
+        .await;
+    players.host.handle_received_messages().await;
+    assert_eq!(
+        players.query(&players.host, "query { sentInvites }").await["sentInvites"],
+        serde_json::json!([])
+    );
+    let declined_again = players
+        .guest
+        .try_graphql_mutation(players.app, &answer("acceptChallenge"))
+        .await;
+    assert!(declined_again.is_err());
+
+    players.mutate(&players.host, &invite).await;
+    players.guest.handle_received_messages().await;
+    players
+        .mutate(&players.guest, &answer("acceptChallenge"))
+        .await;
+    players.host.handle_received_messages().await;
+    players.guest.handle_received_messages().await;
+
+    let query =
+        "query { game { status totalRounds bonuses players { chainId } } invites { chainId } }";
+    let host = players.query(&players.host, query).await;
+    let guest = players.query(&players.guest, query).await;
+    assert_eq!(host["game"], guest["game"]);
+    assert_eq!(host["game"]["status"], "ACTIVE");
+    assert_eq!(host["game"]["totalRounds"], 2);
+    assert_eq!(host["game"]["bonuses"], true);
+    assert_eq!(host["game"]["players"][1]["chainId"], guest_id.to_string());
+    assert_eq!(guest["invites"], serde_json::json!([]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_reaches_the_opponent_within_its_limits() {
+    let players = common::setup().await;
+    players.start_match(2).await;
+
+    let say = |text: &str| format!(r#"mutation {{ sendChat(text: "{text}") }}"#);
+    players.mutate(&players.guest, &say(" good luck ")).await;
+    players
+        .mutate(&players.guest, r#"mutation { sendReaction(emoji: "👍") }"#)
+        .await;
+    players.host.handle_received_messages().await;
+
+    let query = "query { game { matchId } chat { senderChainId senderName text reaction } }";
+    let host = players.query(&players.host, query).await;
+    let guest = players.query(&players.guest, query).await;
+    assert_eq!(host["chat"], guest["chat"]);
+    assert_eq!(host["chat"][0]["senderName"], "Guest");
+    assert_eq!(host["chat"][0]["text"], "good luck");
+    assert_eq!(host["chat"][1]["reaction"], true);
+    let by_id = format!(
+        r#"query {{ chat(matchId: {}) {{ text }} }}"#,
+        host["game"]["matchId"]
+    );
+    assert_eq!(
+        players.query(&players.host, &by_id).await["chat"][1]["text"],
+        "👍"
+    );
+
+    let too_long = players
+        .guest
+        .try_graphql_mutation(players.app, &say(&"a".repeat(201)))
+        .await;
+    assert!(too_long.is_err());
+    let unknown_reaction = players
+        .guest
+        .try_graphql_mutation(players.app, r#"mutation { sendReaction(emoji: "🐢") }"#)
+        .await;
+    assert!(unknown_reaction.is_err());
+    // Every block here is stamped at time zero, so the guest hits the rate limit.
+    for _ in 0..3 {
+        players.mutate(&players.guest, &say("hurry up")).await;
+    }
+    let too_fast = players
+        .guest
+        .try_graphql_mutation(players.app, &say("hurry up"))
+        .await;
+    assert!(too_fast.is_err());
+    players.mutate(&players.host, &say("patience")).await;
+    players.guest.handle_received_messages().await;
+    let guest = players
+        .query(&players.guest, "query { chat { text } }")
+        .await;
+    assert_eq!(guest["chat"].as_array().unwrap().len(), 6);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_messages_are_stamped_on_arrival() {
+    let players = common::setup().await;
+    players.start_match(2).await;
+
+    let text = "hello".to_string();
+    let sent = players
+        .operate_at(
+            &players.guest,
+            Timestamp::from(1_000_000),
+            Operation::SendChat { text },
+        )
+        .await
+        .unwrap();
+    players
+        .deliver_at(&players.host, Timestamp::from(5_000_000), &sent)
+        .await;
+
+    let query = "query { chat { timestamp } }";
+    let guest = players.query(&players.guest, query).await;
+    let host = players.query(&players.host, query).await;
+    assert_eq!(guest["chat"][0]["timestamp"], 1_000_000);
+    assert_eq!(host["chat"][0]["timestamp"], 5_000_000);
+}
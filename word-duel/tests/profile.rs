@@ -0,0 +1,51 @@
+//! Player profiles and the names players go by.
+
+use word_duel::profile::{self, Profile, ProfileError, AVATAR_COUNT, MAX_DISPLAY_NAME_LEN};
+
+fn profile(display_name: &str, language: &str, country: &str) -> Profile {
+    Profile {
+        display_name: display_name.to_string(),
+        avatar_id: 0,
+        language: language.to_string(),
+        country: country.to_string(),
+    }
+}
+
+#[test]
+fn profiles_are_trimmed_and_their_codes_cased() {
+    let normalized = profile("  Ana ", " PT", "br ").normalized().unwrap();
+    assert_eq!(normalized, profile("Ana", "pt", "BR"));
+    assert_eq!(
+        profile("Ana", "", "").normalized(),
+        Ok(profile("Ana", "", ""))
+    );
+}
+
+#[test]
+fn invalid_profiles_are_refused() {
+    let too_long = "x".repeat(MAX_DISPLAY_NAME_LEN + 1);
+    let cases = [
+        (profile(" ", "", ""), ProfileError::EmptyName),
+        (profile(&too_long, "", ""), ProfileError::NameTooLong),
+        (profile("Ana", "eng", ""), ProfileError::InvalidLanguage),
+        (profile("Ana", "", "B1"), ProfileError::InvalidCountry),
+        (
+            Profile {
+                avatar_id: AVATAR_COUNT,
+                ..profile("Ana", "", "")
+            },
+            ProfileError::UnknownAvatar,
+        ),
+    ];
+    for (profile, error) in cases {
+        assert_eq!(profile.normalized(), Err(error));
+    }
+}
+
+#[test]
+fn given_names_win_over_the_display_name() {
+    let ana = profile("Ana", "", "");
+    assert_eq!(profile::player_name(" Bo ", Some(&ana)), "Bo");
+    assert_eq!(profile::player_name("", Some(&ana)), "Ana");
+    assert_eq!(profile::player_name("", None), profile::DEFAULT_NAME);
+}
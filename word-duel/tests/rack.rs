@@ -0,0 +1,68 @@
+//! Letter packs and racks chosen by the host.
+
+use word_duel::{
+    dictionary::Dictionary,
+    letters_for_match,
+    rack::{check_rack, letters_for, RackError, DEFAULT_LETTER_PACK, LETTER_PACKS},
+    solver, LetterChoice, LetterSource,
+};
+
+#[test]
+fn every_pack_rack_is_playable() {
+    let english = Dictionary::english();
+    for pack in LETTER_PACKS {
+        for rack in pack.racks {
+            assert_eq!(
+                check_rack(english, rack).as_deref(),
+                Ok(*rack),
+                "{}",
+                pack.name
+            );
+        }
+    }
+}
+
+#[test]
+fn packs_are_picked_by_name_and_match_id() {
+    let english = Dictionary::english();
+    let classic = LetterChoice::default();
+    assert_eq!(classic.pack_name().as_deref(), Some(DEFAULT_LETTER_PACK));
+    for match_id in 0..8 {
+        assert_eq!(
+            letters_for(&classic, match_id, english),
+            Ok(letters_for_match(match_id))
+        );
+    }
+
+    let long = LetterChoice::Pack(" Long ".to_string());
+    assert_eq!(long.pack_name().as_deref(), Some("long"));
+    assert_eq!(letters_for(&long, 0, english).unwrap().len(), 8);
+
+    let unknown = LetterChoice::Pack("emoji".to_string());
+    assert_eq!(unknown.pack_name(), None);
+    assert_eq!(
+        letters_for(&unknown, 0, english),
+        Err(RackError::UnknownPack("emoji".to_string()))
+    );
+}
+
+#[test]
+fn custom_racks_are_checked() {
+    let english = Dictionary::english();
+    let custom = LetterChoice::Custom(" gardens ".to_string());
+    assert_eq!(custom.source(), LetterSource::Custom);
+    assert_eq!(custom.pack_name(), None);
+    assert_eq!(letters_for(&custom, 3, english).as_deref(), Ok("GARDENS"));
+    assert!(!solver::playable_words(english, "GARDENS").is_empty());
+
+    assert_eq!(check_rack(english, "CAT"), Err(RackError::TooShort));
+    assert_eq!(
+        check_rack(english, "ABCDEFGHIJKLM"),
+        Err(RackError::TooLong)
+    );
+    assert_eq!(check_rack(english, "CAT S"), Err(RackError::NotLetters));
+    assert_eq!(check_rack(english, "ÉCRAN"), Err(RackError::NotLetters));
+    assert_eq!(check_rack(english, "QXZV"), Err(RackError::Unplayable));
+    assert_eq!(check_rack(english, "ca?s").as_deref(), Ok("CA?S"));
+    assert_eq!(check_rack(english, "CA???"), Err(RackError::TooManyBlanks));
+}
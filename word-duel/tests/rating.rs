@@ -0,0 +1,56 @@
+//! Elo rating updates after a match.
+
+use proptest::prelude::*;
+use word_duel::{updated_rating, MatchResult, INITIAL_RATING};
+
+#[test]
+fn even_players_move_by_half_the_k_factor() {
+    assert_eq!(
+        updated_rating(INITIAL_RATING, INITIAL_RATING, MatchResult::Win),
+        1216
+    );
+    assert_eq!(
+        updated_rating(INITIAL_RATING, INITIAL_RATING, MatchResult::Loss),
+        1184
+    );
+    assert_eq!(
+        updated_rating(INITIAL_RATING, INITIAL_RATING, MatchResult::Draw),
+        1200
+    );
+}
+
+#[test]
+fn beating_a_stronger_player_gains_more() {
+    assert_eq!(updated_rating(1200, 1600, MatchResult::Win), 1229);
+    assert_eq!(updated_rating(1600, 1200, MatchResult::Loss), 1571);
+    assert_eq!(updated_rating(1600, 1200, MatchResult::Win), 1603);
+}
+
+proptest! {
+    #[test]
+    fn ratings_change_by_at_most_the_k_factor(
+        rating in 0..4000u32,
+        opponent in 0..4000u32,
+        result in prop_oneof![
+            Just(MatchResult::Win),
+            Just(MatchResult::Draw),
+            Just(MatchResult::Loss),
+        ],
+    ) {
+        let next = updated_rating(rating, opponent, result);
+        prop_assert!(next.abs_diff(rating) <= 32);
+        match result {
+            MatchResult::Win => prop_assert!(next >= rating),
+            MatchResult::Loss => prop_assert!(next <= rating),
+            MatchResult::Draw => {}
+        }
+    }
+
+    #[test]
+    fn a_win_and_a_loss_balance_out(rating in 100..4000u32, opponent in 100..4000u32) {
+        let winner = updated_rating(rating, opponent, MatchResult::Win);
+        let loser = updated_rating(opponent, rating, MatchResult::Loss);
+        // Each side is rounded on its own.
+        prop_assert!((winner - rating).abs_diff(opponent - loser) <= 1);
+    }
+}
@@ -0,0 +1,370 @@
+//! Exporting finished matches and checking imported ones.
+
+use word_duel::{
+    challenge::Challenge,
+    engine::{Event, GameEngine, MAX_HISTORY},
+    handicap::{Handicap, HandicapMode},
+    replay::{MatchReplay, ReplayError, REPLAY_VERSION},
+    seal, Category, Game, GameMode, LetterSource, MatchStatus, PlayerInfo, RoundPhase, TurnOrder,
+    Verdict, INITIAL_RATING,
+};
+
+fn chain_id(n: u8) -> String {
+    format!("{n:064x}")
+}
+
+fn player(n: u8) -> PlayerInfo {
+    PlayerInfo {
+        chain_id: chain_id(n),
+        name: format!("player {n}"),
+        rating: INITIAL_RATING,
+        handicap: Handicap::default(),
+        profile: None,
+    }
+}
+
+/// A match between chains 1 (host) and 2 played to the end with the given words.
+fn played(rounds: &[(&str, &str)]) -> Game {
+    played_in(GameMode::Classic, TurnOrder::Alternating, rounds)
+}
+
+fn played_in(mode: GameMode, turn_order: TurnOrder, rounds: &[(&str, &str)]) -> Game {
+    play(waiting_game(mode, turn_order, rounds.len() as u32), rounds)
+}
+
+fn waiting_game(mode: GameMode, turn_order: TurnOrder, total_rounds: u32) -> Game {
+    Game {
+        match_id: "7".to_string(),
+        host_chain_id: chain_id(1),
+        room_code: "ABCDEF".to_string(),
+        status: MatchStatus::WaitingForPlayer,
+        players: vec![player(1)],
+        letters: "ATRESN".to_string(),
+        letter_source: LetterSource::Pack,
+        letter_pack: Some("classic".to_string()),
+        mode,
+        turn_order,
+        chain: Vec::new(),
+        categories: false,
+        category: None,
+        bonuses: false,
+        handicap_mode: HandicapMode::Off,
+        guest_handicap: Handicap::default(),
+        total_rounds,
+        current_round: 1,
+        host_score: 0,
+        guest_score: 0,
+        round_phase: RoundPhase::HostToPlay,
+        host_word: None,
+        guest_word: None,
+        host_seal: None,
+        guest_seal: None,
+        turn_deadline: 0,
+        turn_started: 0,
+        host_elapsed_micros: None,
+        guest_elapsed_micros: None,
+        winner_chain_id: None,
+        end_reason: None,
+        history: Vec::new(),
+        version: 0,
+    }
+}
+
+/// Plays `game` to the end with the given (host, guest) words.
+fn play(game: Game, rounds: &[(&str, &str)]) -> Game {
+    const SALT: &str = "0123456789abcdef";
+    let simultaneous = game.turn_order == TurnOrder::Simultaneous;
+    let mut engine = GameEngine::new(game, chain_id(1), 1_000);
+    engine.apply(Event::Join { player: player(2) }).unwrap();
+    for (host_word, guest_word) in rounds {
+        let plays = [(1, host_word), (2, guest_word)];
+        if simultaneous {
+            for (n, word) in plays {
+                engine
+                    .apply(Event::Seal {
+                        player_chain_id: chain_id(n),
+                        seal: seal::seal(word, SALT),
+                    })
+                    .unwrap();
+            }
+        }
+        for (n, word) in plays {
+            let player_chain_id = chain_id(n);
+            let word = word.to_string();
+            let event = if simultaneous {
+                Event::Reveal {
+                    player_chain_id,
+                    word,
+                    salt: SALT.to_string(),
+                }
+            } else {
+                Event::Submit {
+                    player_chain_id,
+                    word,
+                }
+            };
+            engine.apply(event).unwrap();
+        }
+    }
+    engine.into_game()
+}
+
+fn replay() -> MatchReplay {
+    MatchReplay::from_game(&played(&[("STAR", "RAT"), ("QQQ", "STERN")])).unwrap()
+}
+
+#[test]
+fn replays_round_trip_through_json_and_bcs() {
+    let replay = replay();
+    replay.verify().unwrap();
+
+    let from_json = MatchReplay::from_json(&replay.to_json()).unwrap();
+    let from_bcs = MatchReplay::from_bcs(&replay.to_bcs()).unwrap();
+    let expected = serde_json::to_value(&replay).unwrap();
+    assert_eq!(serde_json::to_value(&from_json).unwrap(), expected);
+    assert_eq!(serde_json::to_value(&from_bcs).unwrap(), expected);
+    from_bcs.verify().unwrap();
+
+    let export = replay.export();
+    assert_eq!(hex::decode(&export.bcs).unwrap(), replay.to_bcs());
+    assert_eq!(export.json, replay.to_json());
+}
+
+#[test]
+fn only_ended_matches_are_exported() {
+    let mut game = played(&[("STAR", "RAT")]);
+    game.status = MatchStatus::Active;
+    game.winner_chain_id = None;
+    game.end_reason = None;
+    assert_eq!(
+        MatchReplay::from_game(&game).unwrap_err(),
+        ReplayError::NotEnded
+    );
+}
+
+#[test]
+fn tampered_replays_fail_verification() {
+    let mut replay = replay();
+    replay.rounds[1].host_points = 3;
+    assert_eq!(
+        replay.verify().unwrap_err(),
+        ReplayError::PointsMismatch { round: 2 }
+    );
+
+    let mut replay = self::replay();
+    replay.rounds[0].guest_score += 1;
+    assert_eq!(
+        replay.verify().unwrap_err(),
+        ReplayError::ScoreMismatch { round: 1 }
+    );
+
+    let mut replay = self::replay();
+    replay.guest_score = 0;
+    assert_eq!(
+        replay.verify().unwrap_err(),
+        ReplayError::FinalScoreMismatch
+    );
+
+    let mut replay = self::replay();
+    replay.winner_chain_id = chain_id(1);
+    assert_eq!(replay.verify().unwrap_err(), ReplayError::WinnerMismatch);
+
+    let mut replay = self::replay();
+    replay.rounds.pop();
+    assert_eq!(replay.verify().unwrap_err(), ReplayError::MissingRounds);
+}
+
+#[test]
+fn unknown_formats_and_versions_are_refused() {
+    let mut replay = replay();
+    replay.version = REPLAY_VERSION + 1;
+    assert_eq!(
+        MatchReplay::from_bcs(&replay.to_bcs()).unwrap_err(),
+        ReplayError::UnsupportedVersion(REPLAY_VERSION + 1)
+    );
+    assert_eq!(
+        MatchReplay::from_json(&replay.to_json()).unwrap_err(),
+        ReplayError::UnsupportedVersion(REPLAY_VERSION + 1)
+    );
+
+    replay.format = "chess".to_string();
+    assert!(matches!(
+        MatchReplay::from_json(&replay.to_json()),
+        Err(ReplayError::UnsupportedFormat(_))
+    ));
+    assert!(matches!(
+        MatchReplay::from_json("{}"),
+        Err(ReplayError::Malformed(_))
+    ));
+    assert!(matches!(
+        MatchReplay::from_bcs(&[3, b'a']),
+        Err(ReplayError::Malformed(_))
+    ));
+}
+
+#[test]
+fn long_matches_verify_from_their_kept_rounds() {
+    let rounds = vec![("RAT", "STAR"); MAX_HISTORY + 3];
+    let replay = MatchReplay::from_game(&played(&rounds)).unwrap();
+    assert_eq!(replay.rounds.len(), MAX_HISTORY);
+    assert_eq!(replay.rounds[0].round, 4);
+    replay.verify().unwrap();
+}
+
+#[test]
+fn rush_replays_are_rescored_word_by_word() {
+    let game = played_in(
+        GameMode::Rush,
+        TurnOrder::Simultaneous,
+        &[("RAT TAR QQQ", "STERN")],
+    );
+    let mut replay = MatchReplay::from_game(&game).unwrap();
+    assert_eq!(replay.rules.mode, GameMode::Rush);
+    replay.verify().unwrap();
+
+    replay.rounds[0].host_words[2].points = 3;
+    assert_eq!(
+        replay.verify().unwrap_err(),
+        ReplayError::PointsMismatch { round: 1 }
+    );
+}
+
+#[test]
+fn word_chain_replays_check_the_chain() {
+    let game = played_in(
+        GameMode::Chain,
+        TurnOrder::Alternating,
+        &[("table", "eagle"), ("table", "elephant")],
+    );
+    let mut replay = MatchReplay::from_game(&game).unwrap();
+    assert_eq!(replay.chain, ["TABLE", "EAGLE", "ELEPHANT"]);
+    replay.verify().unwrap();
+
+    replay.chain[1] = "EASEL".to_string();
+    assert_eq!(replay.verify().unwrap_err(), ReplayError::ChainMismatch);
+}
+
+#[test]
+fn category_replays_check_each_round_category() {
+    let mut game = waiting_game(GameMode::Classic, TurnOrder::Alternating, 2);
+    game.categories = true;
+    let game = play(game, &[("tea", "rat"), ("tern", "stern")]);
+    let mut replay = MatchReplay::from_game(&game).unwrap();
+    assert!(replay.rules.categories);
+    let categories: Vec<_> = replay.rounds.iter().map(|r| r.category).collect();
+    assert_eq!(categories, [Some(Category::Food), Some(Category::Animals)]);
+    assert_eq!(replay.rounds[0].guest_verdict, Verdict::OffCategory);
+    replay.verify().unwrap();
+
+    replay.rounds[1].category = Some(Category::Countries);
+    assert_eq!(
+        replay.verify().unwrap_err(),
+        ReplayError::CategoryMismatch { round: 2 }
+    );
+
+    let mut replay = MatchReplay::from_game(&game).unwrap();
+    replay.rules.categories = false;
+    assert_eq!(
+        replay.verify().unwrap_err(),
+        ReplayError::CategoryMismatch { round: 1 }
+    );
+}
+
+#[test]
+fn bonus_replays_are_rescored_from_the_recorded_times() {
+    let mut game = waiting_game(GameMode::Classic, TurnOrder::Alternating, 2);
+    game.bonuses = true;
+    let game = play(game, &[("astern", "rat"), ("star", "qqq")]);
+    let mut replay = MatchReplay::from_game(&game).unwrap();
+    assert!(replay.rules.bonuses);
+    assert_eq!(
+        (replay.host_score, replay.guest_score),
+        (6 + 5 + 2 + 4 + 2, 3)
+    );
+    replay.verify().unwrap();
+
+    replay.rounds[0].host_elapsed_micros = 5;
+    assert_eq!(
+        replay.verify().unwrap_err(),
+        ReplayError::BonusMismatch { round: 1 }
+    );
+
+    let mut replay = MatchReplay::from_game(&game).unwrap();
+    replay.rounds[1].host_bonuses.clear();
+    assert_eq!(
+        replay.verify().unwrap_err(),
+        ReplayError::BonusMismatch { round: 2 }
+    );
+}
+
+#[test]
+fn handicapped_replays_use_each_players_rack_and_multiplier() {
+    let mut game = waiting_game(GameMode::Classic, TurnOrder::Alternating, 1);
+    game.handicap_mode = HandicapMode::Fixed;
+    game.guest_handicap = Handicap {
+        score_percent: 50,
+        extra_letters: 2,
+        extra_secs: 0,
+    };
+    let game = play(game, &[("stern", "assent")]);
+    let mut replay = MatchReplay::from_game(&game).unwrap();
+    assert_eq!((replay.host_score, replay.guest_score), (5, 3));
+    assert_eq!(replay.rounds[0].guest_handicap_points, -3);
+    replay.verify().unwrap();
+
+    replay.players[1].handicap = Handicap::default();
+    assert_eq!(
+        replay.verify().unwrap_err(),
+        ReplayError::PointsMismatch { round: 1 }
+    );
+}
+
+#[test]
+fn challenged_replays_check_each_challenge_against_the_dictionary() {
+    let game = play(
+        waiting_game(GameMode::Classic, TurnOrder::Alternating, 2),
+        &[("satrn", "stern")],
+    );
+    let mut engine = GameEngine::new(game, chain_id(1), 2_000);
+    engine
+        .apply(Event::Challenge {
+            challenger_chain_id: chain_id(2),
+            round: 1,
+        })
+        .unwrap();
+    for (n, word) in [(1, "star"), (2, "rat")] {
+        engine
+            .apply(Event::Submit {
+                player_chain_id: chain_id(n),
+                word: word.to_string(),
+            })
+            .unwrap();
+    }
+    let game = engine.into_game();
+    let mut replay = MatchReplay::from_game(&game).unwrap();
+    assert_eq!(
+        replay.rounds[0].guest_challenge,
+        Some(Challenge {
+            upheld: true,
+            struck_words: vec!["SATRN".to_string()],
+            points: 5,
+        })
+    );
+    assert_eq!((replay.host_score, replay.guest_score), (4, 8));
+    replay.verify().unwrap();
+
+    replay.rounds[0].guest_challenge = None;
+    assert_eq!(
+        replay.verify().unwrap_err(),
+        ReplayError::ScoreMismatch { round: 1 }
+    );
+
+    let mut replay = MatchReplay::from_game(&game).unwrap();
+    if let Some(challenge) = &mut replay.rounds[0].guest_challenge {
+        challenge.points = 6;
+    }
+    assert_eq!(
+        replay.verify().unwrap_err(),
+        ReplayError::ChallengeMismatch { round: 1 }
+    );
+}
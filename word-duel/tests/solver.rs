@@ -0,0 +1,70 @@
+//! Enumerating the words a rack can make.
+
+use word_duel::{
+    dictionary::Dictionary,
+    letters_for_match,
+    solver::{best_words, missed_words, playable_words},
+    validate_word,
+};
+
+fn small_dictionary() -> Dictionary {
+    Dictionary::new([
+        "rat", "tar", "star", "stare", "aster", "zebra", "tsar", "ox",
+    ])
+}
+
+fn words(scored: &[word_duel::solver::ScoredWord]) -> Vec<&str> {
+    scored.iter().map(|w| w.word.as_str()).collect()
+}
+
+#[test]
+fn playable_words_are_ranked_best_first() {
+    let playable = playable_words(&small_dictionary(), "trasen");
+    assert_eq!(
+        words(&playable),
+        ["ASTER", "STARE", "STAR", "TSAR", "RAT", "TAR"]
+    );
+    assert_eq!(playable[0].points, 5);
+    assert!(playable_words(&small_dictionary(), "QQQXXX").is_empty());
+}
+
+#[test]
+fn the_solver_finds_exactly_the_valid_words() {
+    let english = Dictionary::english();
+    for match_id in 0..8 {
+        let letters = letters_for_match(match_id);
+        let expected = english
+            .words()
+            .filter(|w| validate_word(&letters, w))
+            .count();
+        let playable = playable_words(english, &letters);
+        assert_eq!(playable.len(), expected, "{letters}");
+        assert!(playable.windows(2).all(|w| w[0].points >= w[1].points));
+    }
+}
+
+#[test]
+fn best_words_respects_the_limit() {
+    assert_eq!(
+        words(&best_words(&small_dictionary(), "TRASEN", 2)),
+        ["ASTER", "STARE"]
+    );
+    assert!(best_words(&small_dictionary(), "TRASEN", 0).is_empty());
+}
+
+#[test]
+fn missed_words_leave_out_what_was_played() {
+    let missed = missed_words(&small_dictionary(), "TRASEN", &["aster", ""], 3);
+    assert_eq!(words(&missed), ["STARE", "STAR", "TSAR"]);
+}
+
+#[test]
+fn blank_tiles_fill_in_for_missing_letters() {
+    let playable = playable_words(&small_dictionary(), "TRAS?");
+    assert_eq!(
+        words(&playable),
+        ["ASTER", "STAR", "STARE", "TSAR", "RAT", "TAR"]
+    );
+    assert_eq!((playable[0].points, playable[0].blanks.as_str()), (4, "E"));
+    assert_eq!(playable[1].blanks, "");
+}
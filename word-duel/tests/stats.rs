@@ -0,0 +1,201 @@
+//! Statistics over finished matches.
+
+use word_duel::{
+    dictionary::Dictionary,
+    handicap::{Handicap, HandicapMode},
+    stats::{match_stats, player_stats},
+    Category, Game, GameMode, LetterSource, MatchResult, MatchStatus, PlayerInfo, RoundPhase,
+    RoundRecord, TurnOrder, INITIAL_RATING,
+};
+
+fn chain_id(n: u8) -> String {
+    format!("{n:064x}")
+}
+
+fn player(n: u8) -> PlayerInfo {
+    PlayerInfo {
+        chain_id: chain_id(n),
+        name: format!("player {n}"),
+        rating: INITIAL_RATING,
+        handicap: Handicap::default(),
+        profile: None,
+    }
+}
+
+fn dictionary() -> Dictionary {
+    Dictionary::new(["rat", "tar", "star", "stare", "aster"])
+}
+
+/// An ended match hosted by chain 1 against chain 2, with the given (host, guest) words.
+fn ended_game(match_id: u64, rounds: &[(&str, &str)]) -> Game {
+    let mut history = Vec::new();
+    let (mut host_score, mut guest_score) = (0, 0);
+    for (i, (host_word, guest_word)) in rounds.iter().enumerate() {
+        let host = GameMode::Classic.score("ATRESN", None, &[], host_word);
+        let guest = GameMode::Classic.score("ATRESN", None, &[], guest_word);
+        let (host_points, guest_points) = (host.points, guest.points);
+        host_score += host_points;
+        guest_score += guest_points;
+        history.push(RoundRecord {
+            round: i as u32 + 1,
+            host_word: host_word.to_string(),
+            guest_word: guest_word.to_string(),
+            host_points,
+            guest_points,
+            host_score,
+            guest_score,
+            timestamp: String::new(),
+            host_words: Vec::new(),
+            guest_words: Vec::new(),
+            host_blanks: String::new(),
+            guest_blanks: String::new(),
+            category: None,
+            host_verdict: host.verdict,
+            guest_verdict: guest.verdict,
+            host_elapsed_micros: 0,
+            guest_elapsed_micros: 0,
+            host_streak: 0,
+            guest_streak: 0,
+            host_bonuses: Vec::new(),
+            guest_bonuses: Vec::new(),
+            host_handicap_points: 0,
+            guest_handicap_points: 0,
+            host_challenge: None,
+            guest_challenge: None,
+        });
+    }
+    let winner_chain_id = match host_score.cmp(&guest_score) {
+        std::cmp::Ordering::Greater => chain_id(1),
+        std::cmp::Ordering::Less => chain_id(2),
+        std::cmp::Ordering::Equal => String::new(),
+    };
+    Game {
+        match_id: match_id.to_string(),
+        host_chain_id: chain_id(1),
+        room_code: "ABCDEF".to_string(),
+        status: MatchStatus::Ended,
+        players: vec![player(1), player(2)],
+        letters: "ATRESN".to_string(),
+        letter_source: LetterSource::Pack,
+        letter_pack: Some("classic".to_string()),
+        mode: GameMode::Classic,
+        turn_order: TurnOrder::Alternating,
+        chain: Vec::new(),
+        categories: false,
+        category: None,
+        bonuses: false,
+        handicap_mode: HandicapMode::Off,
+        guest_handicap: Handicap::default(),
+        total_rounds: rounds.len() as u32,
+        current_round: rounds.len() as u32 + 1,
+        host_score,
+        guest_score,
+        round_phase: RoundPhase::RoundComplete,
+        host_word: None,
+        guest_word: None,
+        host_seal: None,
+        guest_seal: None,
+        turn_deadline: 0,
+        turn_started: 0,
+        host_elapsed_micros: None,
+        guest_elapsed_micros: None,
+        winner_chain_id: Some(winner_chain_id),
+        end_reason: None,
+        history,
+        version: 0,
+    }
+}
+
+#[test]
+fn match_stats_follow_the_players_side() {
+    let game = ended_game(1, &[("STAR", "RAT"), ("QQQ", ""), ("ASTER", "TAR")]);
+
+    let host = match_stats(&game, &chain_id(1), &dictionary()).unwrap();
+    assert_eq!(host.result, Some(MatchResult::Win));
+    assert_eq!((host.words_played, host.invalid_words), (3, 1));
+    assert_eq!(host.points, 9);
+    assert_eq!(host.optimal_points, 15);
+    assert_eq!(host.best_word.as_deref(), Some("ASTER"));
+    assert_eq!(host.average_word_length, 4.5);
+    assert_eq!(host.efficiency, 0.6);
+
+    let guest = match_stats(&game, &chain_id(2), &dictionary()).unwrap();
+    assert_eq!(guest.result, Some(MatchResult::Loss));
+    assert_eq!((guest.words_played, guest.invalid_words), (2, 0));
+    assert_eq!(guest.invalid_word_rate, 0.0);
+
+    assert!(match_stats(&game, &chain_id(3), &dictionary()).is_none());
+}
+
+#[test]
+fn player_stats_add_up_matches_and_track_streaks() {
+    let win = || ("STARE", "RAT");
+    let loss = || ("RAT", "STARE");
+    let games = [
+        ended_game(1, &[loss()]),
+        ended_game(2, &[win()]),
+        ended_game(3, &[win()]),
+        ended_game(4, &[win()]),
+        ended_game(5, &[("RAT", "TAR")]),
+        ended_game(6, &[loss()]),
+        ended_game(7, &[loss()]),
+    ];
+    let stats = player_stats(&games, &chain_id(1), &dictionary());
+
+    assert_eq!(stats.matches_played, 7);
+    assert_eq!((stats.wins, stats.losses, stats.draws), (3, 3, 1));
+    assert_eq!(stats.longest_win_streak, 3);
+    assert_eq!(stats.longest_loss_streak, 2);
+    assert_eq!(stats.current_streak, -2);
+    assert_eq!(stats.best_word.as_deref(), Some("STARE"));
+    assert_eq!(stats.words_played, 7);
+    assert_eq!(stats.efficiency, (3 * 5 + 4 * 3) as f64 / (7 * 5) as f64);
+    assert_eq!(stats.matches.len(), 7);
+}
+
+#[test]
+fn running_matches_count_words_but_not_results() {
+    let mut game = ended_game(1, &[("STAR", "RAT")]);
+    game.status = MatchStatus::Active;
+    let stats = player_stats(&[game], &chain_id(2), &dictionary());
+    assert_eq!(stats.matches_played, 0);
+    assert_eq!(stats.current_streak, 0);
+    assert_eq!(stats.words_played, 1);
+    assert_eq!(stats.matches[0].result, None);
+}
+
+#[test]
+fn rush_rounds_count_each_word() {
+    let mut game = ended_game(1, &[("", "")]);
+    game.mode = GameMode::Rush;
+    let record = &mut game.history[0];
+    record.host_word = "RAT STAR QQQ".to_string();
+    let score = GameMode::Rush.score("ATRESN", None, &[], &record.host_word);
+    (record.host_points, record.host_words) = (score.points, score.words);
+
+    let stats = match_stats(&game, &chain_id(1), &dictionary()).unwrap();
+    assert_eq!((stats.words_played, stats.invalid_words), (3, 1));
+    assert_eq!(stats.points, 7);
+    assert_eq!(stats.best_word.as_deref(), Some("STAR"));
+    // Every word of the dictionary fits the rack.
+    assert_eq!(stats.optimal_points, 3 + 3 + 4 + 5 + 5);
+}
+
+#[test]
+fn optimal_points_follow_the_players_rack_and_the_rounds_category() {
+    let mut game = ended_game(1, &[("RAT", "STARE"), ("ANT", "RAT")]);
+    game.letters = "ATRN".to_string();
+    game.players[1].handicap = Handicap {
+        extra_letters: 2,
+        ..Handicap::default()
+    };
+    game.history[1].category = Some(Category::Animals);
+
+    // The host's rack makes RAT at best, and ANT or RAT among the animals.
+    let host = match_stats(&game, &chain_id(1), &dictionary()).unwrap();
+    assert_eq!(host.optimal_points, 3 + 3);
+    // With E and S the guest's rack makes STARE, and TERN among the animals.
+    let guest = match_stats(&game, &chain_id(2), &dictionary()).unwrap();
+    assert_eq!(guest.optimal_points, 5 + 4);
+    assert!(guest.efficiency <= 1.0);
+}
@@ -0,0 +1,119 @@
+//! Properties of the pure word rules every round is decided by.
+
+use proptest::{prelude::*, sample::subsequence};
+use word_duel::{
+    blank_letters, dictionary::Dictionary, letters_for_match, validate_word, word_score, Category,
+    GameMode, Verdict, BLANK,
+};
+
+/// A rack and a shuffled pick of at least three of its letters.
+fn rack_and_anagram() -> impl Strategy<Value = (String, String)> {
+    "[A-Z]{3,12}".prop_flat_map(|rack| {
+        let letters: Vec<char> = rack.chars().collect();
+        let len = letters.len();
+        subsequence(letters, 3..=len)
+            .prop_shuffle()
+            .prop_map(move |word| (rack.clone(), word.into_iter().collect()))
+    })
+}
+
+proptest! {
+    #[test]
+    fn racks_are_six_uppercase_letters(match_id in any::<u64>()) {
+        let letters = letters_for_match(match_id);
+        prop_assert_eq!(letters.chars().count(), 6);
+        prop_assert!(letters.chars().all(|c| c.is_ascii_uppercase()));
+    }
+
+    #[test]
+    fn any_anagram_of_the_rack_is_valid((rack, word) in rack_and_anagram()) {
+        prop_assert!(validate_word(&rack, &word));
+        prop_assert_eq!(word_score(&rack, &word), word.len() as u32);
+    }
+
+    #[test]
+    fn case_and_surrounding_spaces_are_ignored((rack, word) in rack_and_anagram()) {
+        let typed = format!("  {}\t", word.to_lowercase());
+        prop_assert!(validate_word(&rack.to_lowercase(), &typed));
+        prop_assert_eq!(word_score(&rack, &typed), word.len() as u32);
+    }
+
+    #[test]
+    fn using_a_letter_once_too_often_is_invalid(
+        (rack, word) in rack_and_anagram(),
+        position in any::<prop::sample::Index>(),
+    ) {
+        let extra = word.chars().nth(position.index(word.len())).unwrap();
+        let available = rack.chars().filter(|&c| c == extra).count();
+        let used = word.chars().filter(|&c| c == extra).count();
+        let overused = format!("{word}{}", extra.to_string().repeat(available - used + 1));
+        prop_assert!(!validate_word(&rack, &overused));
+        prop_assert_eq!(word_score(&rack, &overused), 0);
+    }
+
+    #[test]
+    fn letters_missing_from_the_rack_are_invalid(
+        (rack, word) in rack_and_anagram(),
+        extra in "[A-Z]",
+    ) {
+        prop_assume!(!rack.contains(&extra));
+        let appended = format!("{word}{extra}");
+        let prepended = format!("{extra}{word}");
+        prop_assert!(!validate_word(&rack, &appended));
+        prop_assert!(!validate_word(&rack, &prepended));
+    }
+
+    #[test]
+    fn score_never_exceeds_the_rack(rack in "\\PC{0,12}", word in "\\PC{0,16}") {
+        let score = word_score(&rack, &word);
+        prop_assert!(score as usize <= rack.to_uppercase().chars().count());
+        // Letters played with blank tiles score nothing.
+        let blanks = blank_letters(&rack, &word).map_or(0, |b| b.chars().count());
+        let expected = if validate_word(&rack, &word) {
+            word.trim().to_uppercase().chars().count() - blanks
+        } else {
+            0
+        };
+        prop_assert_eq!(score as usize, expected);
+    }
+
+    #[test]
+    fn blanks_stand_in_for_missing_letters(
+        (rack, word) in rack_and_anagram(),
+        position in any::<prop::sample::Index>(),
+    ) {
+        // Swap one rack letter for a blank; the word needs the blank only if it uses every
+        // copy of that letter.
+        let i = position.index(rack.len());
+        let replaced = rack.as_bytes()[i] as char;
+        let blanked = format!("{}{BLANK}{}", &rack[..i], &rack[i + 1..]);
+        let needed = word.matches(replaced).count() == rack.matches(replaced).count();
+        prop_assert!(validate_word(&blanked, &word));
+        let covered = if needed { replaced.to_string() } else { String::new() };
+        prop_assert_eq!(blank_letters(&blanked, &word), Some(covered));
+        prop_assert_eq!(
+            word_score(&blanked, &word),
+            word.len() as u32 - needed as u32
+        );
+    }
+
+    #[test]
+    fn every_classic_round_has_a_playable_category(match_id in any::<u64>(), round in 1..=20u32) {
+        let letters = letters_for_match(match_id);
+        let category = Category::for_round(&letters, match_id, round).unwrap();
+        let word = Dictionary::category(category)
+            .words()
+            .find(|word| validate_word(&letters, word))
+            .unwrap();
+        let score = GameMode::Classic.score(&letters, Some(category), &[], word);
+        prop_assert_eq!(score.verdict, Verdict::Valid);
+        prop_assert_eq!(score.points, word_score(&letters, word));
+    }
+
+    #[test]
+    fn multibyte_letters_are_counted_as_one(rack in "[ÄÖÜÉ]{3,8}") {
+        let two: String = rack.chars().take(2).collect();
+        prop_assert!(!validate_word(&rack, &two));
+        prop_assert_eq!(word_score(&rack, &rack), rack.chars().count() as u32);
+    }
+}